@@ -0,0 +1,35 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use binrw::BinRead;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rekordcrate::anlz::ANLZ;
+use std::io::Cursor;
+
+const ANLZ_DIR: &str = "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E";
+
+/// Parse analysis files of each kind from an in-memory buffer.
+fn anlz_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("anlz_parse");
+    for extension in ["DAT", "EXT", "2EX"] {
+        let path = format!("{}/ANLZ0000.{}", ANLZ_DIR, extension);
+        let data = std::fs::read(path).expect("failed to read analysis file");
+        group.bench_function(extension, |b| {
+            b.iter(|| {
+                ANLZ::read(&mut Cursor::new(data.as_slice()))
+                    .expect("failed to parse analysis file")
+                    .sections
+                    .len()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, anlz_parse);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use binrw::BinRead;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rekordcrate::pdb::{io::Database, BoxedRow, Header, PageType};
+use std::io::Cursor;
+
+const LARGE_PDB: &str = "data/pdb/num_rows/export.pdb";
+
+/// Read every page of every table.
+fn read_all_pages(db: &Database) -> usize {
+    db.header()
+        .tables
+        .iter()
+        .flat_map(|table| db.pages(table))
+        .map(|page| page.expect("failed to read page").row_groups.len())
+        .sum()
+}
+
+fn page_iteration(c: &mut Criterion) {
+    let mut group = c.benchmark_group("page_iteration");
+    group.bench_function("buffered_reader", |b| {
+        let db = Database::open(LARGE_PDB).expect("failed to open database");
+        b.iter(|| read_all_pages(&db));
+    });
+    #[cfg(feature = "mmap")]
+    group.bench_function("mmap", |b| {
+        // SAFETY: The fixture is not modified while the benchmark runs.
+        #[allow(unsafe_code)]
+        let db = unsafe { Database::open_mmap(LARGE_PDB) }.expect("failed to open database");
+        b.iter(|| read_all_pages(&db));
+    });
+    group.finish();
+}
+
+/// Parse the header and all pages of all tables from an in-memory buffer.
+fn full_parse(c: &mut Criterion) {
+    let data = std::fs::read(LARGE_PDB).expect("failed to read database");
+    c.bench_function("full_parse", |b| {
+        b.iter(|| {
+            let mut reader = Cursor::new(data.as_slice());
+            let header = Header::read(&mut reader).expect("failed to parse header");
+            header
+                .tables
+                .iter()
+                .map(|table| {
+                    header
+                        .read_pages(
+                            &mut reader,
+                            binrw::Endian::NATIVE,
+                            (&table.first_page, &table.last_page),
+                        )
+                        .expect("failed to read pages")
+                        .len()
+                })
+                .sum::<usize>()
+        });
+    });
+}
+
+/// Decode all rows of a single table.
+fn table_iteration(c: &mut Criterion) {
+    let mut group = c.benchmark_group("table_iteration");
+    let db = Database::open(LARGE_PDB).expect("failed to open database");
+    for page_type in [PageType::Tracks, PageType::PlaylistEntries] {
+        group.bench_function(format!("{:?}", page_type), |b| {
+            b.iter(|| {
+                db.iter_rows(page_type)
+                    .collect::<rekordcrate::Result<Vec<_>>>()
+                    .expect("failed to read rows")
+                    .len()
+            });
+        });
+        group.bench_function(format!("{:?}/boxed", page_type), |b| {
+            b.iter(|| {
+                db.iter_rows(page_type)
+                    .map(|row| row.map(BoxedRow::from))
+                    .collect::<rekordcrate::Result<Vec<_>>>()
+                    .expect("failed to read rows")
+                    .len()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, page_iteration, full_parse, table_iteration);
+criterion_main!(benches);
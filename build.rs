@@ -51,7 +51,7 @@ fn main() {
 
     write_test!(
         out_dir.join("tests_pdb.rs"),
-        "data/complete_export/*/PIONEER/rekordbox/export.pdb",
+        "data/complete_export/*/PIONEER/rekordbox/export*.pdb",
         r#"// THIS FILE IS AUTOGENERATED - DO NOT EDIT!
 use binrw::{{BinRead}};
 use rekordcrate::pdb::Header;
@@ -0,0 +1,19 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+#![no_main]
+
+use binrw::BinRead;
+use libfuzzer_sys::fuzz_target;
+use rekordcrate::anlz::ANLZ;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    // Parsing arbitrary input may fail, but must never panic.
+    let _ = ANLZ::read(&mut Cursor::new(data));
+});
@@ -0,0 +1,27 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rekordcrate::pdb::io::Database;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    // Parsing arbitrary input may fail, but must never panic.
+    let Ok(db) = Database::open_non_persistent(Cursor::new(data.to_vec())) else {
+        return;
+    };
+    for table in &db.header().tables {
+        for row in db.iter_rows(table.page_type) {
+            if row.is_err() {
+                break;
+            }
+        }
+    }
+});
@@ -26,7 +26,10 @@
 
 #![allow(clippy::must_use_candidate)]
 
-use crate::{util::ColorIndex, xor::XorStream};
+use crate::{
+    util::{ColorIndex, RekordcrateResult},
+    xor::XorStream,
+};
 use binrw::{
     binrw,
     io::{Read, Seek, Write},
@@ -45,6 +48,11 @@ pub enum ContentKind {
     /// All beats found in the track.
     #[brw(magic = b"PQTZ")]
     BeatGrid,
+    /// Extended version of the `BeatGrid` section.
+    ///
+    /// Used in `.EXT` files.
+    #[brw(magic = b"PQT2")]
+    ExtendedBeatGrid,
     /// Either memory points and loops or hotcues and hot loops of the track.
     ///
     /// *Note:* Since the release of the Nexus 2 series, there also exists the `ExtendedCueList`
@@ -100,6 +108,32 @@ pub enum ContentKind {
     Unknown([u8; 4]),
 }
 
+impl ContentKind {
+    /// The four-character code that identifies this kind of section in the file (e.g. `PQTZ`
+    /// for the beat grid).
+    #[must_use]
+    pub fn four_cc(&self) -> [u8; 4] {
+        match self {
+            Self::File => *b"PMAI",
+            Self::BeatGrid => *b"PQTZ",
+            Self::ExtendedBeatGrid => *b"PQT2",
+            Self::CueList => *b"PCOB",
+            Self::ExtendedCueList => *b"PCO2",
+            Self::ExtendedCue => *b"PCP2",
+            Self::Cue => *b"PCPT",
+            Self::Path => *b"PPTH",
+            Self::VBR => *b"PVBR",
+            Self::WaveformPreview => *b"PWAV",
+            Self::TinyWaveformPreview => *b"PWV2",
+            Self::WaveformDetail => *b"PWV3",
+            Self::WaveformColorPreview => *b"PWV4",
+            Self::WaveformColorDetail => *b"PWV5",
+            Self::SongStructure => *b"PSSI",
+            Self::Unknown(four_cc) => *four_cc,
+        }
+    }
+}
+
 /// Header of a section that contains type and size information.
 #[binrw]
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -138,7 +172,7 @@ pub struct Beat {
 
 /// Describes the types of entries found in a Cue List section.
 #[binrw]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[brw(big, repr = u32)]
 pub enum CueListType {
     /// Memory cues or loops.
@@ -149,7 +183,7 @@ pub enum CueListType {
 
 /// Indicates if the cue is point or a loop.
 #[binrw]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[brw(repr = u8)]
 pub enum CueType {
     /// Cue is a single point.
@@ -224,6 +258,13 @@ pub struct Cue {
     unknown7: u32,
 }
 
+impl Cue {
+    /// Returns `true` if this cue is a loop (and not a single point).
+    pub fn is_loop(&self) -> bool {
+        self.cue_type == CueType::Loop
+    }
+}
+
 /// A memory or hot cue (or loop).
 #[binrw]
 #[derive(Debug, PartialEq, Eq)]
@@ -357,6 +398,125 @@ pub struct ExtendedCue {
     unknown10: u32,
 }
 
+impl ExtendedCue {
+    /// Returns `true` if this cue is a loop (and not a single point).
+    pub fn is_loop(&self) -> bool {
+        self.cue_type == CueType::Loop
+    }
+}
+
+/// A memory or hot cue (or loop), independent of the section it was read from.
+///
+/// This is returned by [`ANLZ::cues`] and unifies [`Cue`] (found in `PCOB` sections) and
+/// [`ExtendedCue`] (found in `PCO2` sections).
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct CuePoint {
+    /// Whether this is a hot cue or a memory cue.
+    list_type: CueListType,
+    /// Hot cue number (`0` if this is not a hot cue).
+    hot_cue: u32,
+    /// Time in milliseconds after which this cue would occur.
+    time_ms: u32,
+    /// End of the loop in milliseconds, or `None` for single cue points.
+    loop_end_ms: Option<u32>,
+    /// Color of a memory cue ([`ColorIndex::None`] for hot cues and for cues from legacy cue
+    /// lists, which don't store colors).
+    color: ColorIndex,
+    /// RGB color of a hot cue, or `None` if no color is assigned (or the cue is from a legacy cue
+    /// list).
+    hot_cue_color_rgb: Option<(u8, u8, u8)>,
+    /// Comment of the cue (empty for cues from legacy cue lists).
+    comment: String,
+}
+
+impl CuePoint {
+    /// Whether this is a hot cue or a memory cue.
+    pub fn list_type(&self) -> CueListType {
+        self.list_type
+    }
+
+    /// Hot cue number (`0` if this is not a hot cue, `1` for A, `2` for B, etc.).
+    pub fn hot_cue(&self) -> u32 {
+        self.hot_cue
+    }
+
+    /// Time in milliseconds after which this cue would occur (at normal playback speed).
+    pub fn time_ms(&self) -> u32 {
+        self.time_ms
+    }
+
+    /// Time in milliseconds after which the loop would jump back to [`CuePoint::time_ms`] (at
+    /// normal playback speed).
+    ///
+    /// This is only set for loops, and `None` for single cue points.
+    pub fn loop_end_ms(&self) -> Option<u32> {
+        self.loop_end_ms
+    }
+
+    /// Returns `true` if this cue is a loop (and not a single point).
+    pub fn is_loop(&self) -> bool {
+        self.loop_end_ms.is_some()
+    }
+
+    /// Length of the loop in milliseconds, or `None` if this cue is not a loop.
+    pub fn loop_length_ms(&self) -> Option<u32> {
+        self.loop_end_ms
+            .map(|loop_end_ms| loop_end_ms.saturating_sub(self.time_ms))
+    }
+
+    /// Comment of this cue (e.g. `drop` or `breakdown`), or `None` if the cue has no comment.
+    ///
+    /// Both hot cues and memory cues can have comments, but only extended cue lists (`PCO2`)
+    /// contain them.
+    pub fn comment(&self) -> Option<&str> {
+        (!self.comment.is_empty()).then_some(self.comment.as_str())
+    }
+
+    /// Color of this memory cue, or `None` if no color is assigned.
+    ///
+    /// Hot cues use RGB colors instead (see [`CuePoint::hot_cue_color_rgb`]).
+    pub fn color(&self) -> Option<ColorIndex> {
+        (self.color != ColorIndex::None).then(|| self.color.clone())
+    }
+
+    /// RGB color of this hot cue, or `None` if no color is assigned.
+    ///
+    /// Like comments, hot cue colors are only stored in extended cue lists (`PCO2`).
+    pub fn hot_cue_color_rgb(&self) -> Option<(u8, u8, u8)> {
+        self.hot_cue_color_rgb
+    }
+}
+
+impl From<(CueListType, &Cue)> for CuePoint {
+    fn from((list_type, cue): (CueListType, &Cue)) -> Self {
+        Self {
+            list_type,
+            hot_cue: cue.hot_cue,
+            time_ms: cue.time,
+            loop_end_ms: cue.is_loop().then_some(cue.loop_time),
+            color: ColorIndex::None,
+            hot_cue_color_rgb: None,
+            comment: String::new(),
+        }
+    }
+}
+
+impl From<(CueListType, &ExtendedCue)> for CuePoint {
+    fn from((list_type, cue): (CueListType, &ExtendedCue)) -> Self {
+        Self {
+            list_type,
+            hot_cue: cue.hot_cue,
+            time_ms: cue.time,
+            loop_end_ms: cue.is_loop().then_some(cue.loop_time),
+            color: cue.color.clone(),
+            hot_cue_color_rgb: (cue.hot_cue_color_rgb != (0, 0, 0))
+                .then_some(cue.hot_cue_color_rgb),
+            comment: cue.comment.to_string(),
+        }
+    }
+}
+
 impl Default for WaveformPreviewColumn {
     fn default() -> Self {
         Self::new()
@@ -387,10 +547,10 @@ impl Default for TinyWaveformPreviewColumn {
 #[br(big, map = Self::from_bytes)]
 #[bw(big, map = |x: &TinyWaveformPreviewColumn| x.into_bytes())]
 pub struct TinyWaveformPreviewColumn {
-    #[allow(dead_code)]
-    unused: B4,
     /// Height of the Column in pixels.
     pub height: B4,
+    #[allow(dead_code)]
+    unused: B4,
 }
 
 /// Single Column value in a Waveform Color Preview.
@@ -422,22 +582,82 @@ impl Default for WaveformColorDetailColumn {
 }
 
 /// Single Column value in a Waveform Color Detail section.
+///
+/// Each column is a big-endian 16-bit value with the bits `rrrgggbb bhhhhh00`, i.e. 3 bits per
+/// color component (from the most significant bits), followed by a 5-bit height and two unused
+/// bits.
 #[bitfield]
 #[derive(BinRead, BinWrite, Debug, PartialEq, Eq, Clone, Copy)]
-#[br(map = Self::from_bytes)]
-#[bw(big, map = |x: &WaveformColorDetailColumn| x.into_bytes())]
+#[br(big, map = |x: u16| Self::from_bytes(x.to_le_bytes()))]
+#[bw(big, map = |x: &WaveformColorDetailColumn| u16::from_le_bytes(x.into_bytes()))]
 pub struct WaveformColorDetailColumn {
-    /// Red color component.
-    pub red: B3,
-    /// Green color component.
-    pub green: B3,
-    /// Blue color component.
-    pub blue: B3,
-    /// Height of the column.
-    pub height: B5,
     /// Unknown field
     #[allow(dead_code)]
     unknown: B2,
+    /// Height of the column.
+    pub height: B5,
+    /// Blue color component.
+    pub blue: B3,
+    /// Green color component.
+    pub green: B3,
+    /// Red color component.
+    pub red: B3,
+}
+
+/// Decoded column of a colored waveform (see [`ANLZ::waveform_color_preview_columns`] and
+/// [`ANLZ::waveform_color_detail_columns`]).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ColorColumn {
+    /// Red color component (0-255).
+    pub red: u8,
+    /// Green color component (0-255).
+    pub green: u8,
+    /// Blue color component (0-255).
+    pub blue: u8,
+    /// Height of the column (0-31 for the detail waveform, 0-255 for the preview).
+    pub height: u8,
+}
+
+impl From<&WaveformColorPreviewColumn> for ColorColumn {
+    /// Decode a preview column.
+    ///
+    /// The preview doesn't store colors directly, but the sound energy in the low, mid and high
+    /// frequency range (bytes 3, 4 and 5 of each 6-byte column), which are mapped to red, green
+    /// and blue. The components are scaled so that the loudest band has the value 255, and the
+    /// height of the column is the energy of the loudest band.
+    fn from(column: &WaveformColorPreviewColumn) -> Self {
+        let bands = [
+            column.energy_bottom_third_freq,
+            column.energy_mid_third_freq,
+            column.energy_top_third_freq,
+        ];
+        let height = bands.into_iter().max().unwrap_or_default();
+        let [red, green, blue] = bands.map(|energy| match height {
+            0 => 0,
+            _ => (u16::from(energy) * 255 / u16::from(height)) as u8,
+        });
+        Self {
+            red,
+            green,
+            blue,
+            height,
+        }
+    }
+}
+
+impl From<&WaveformColorDetailColumn> for ColorColumn {
+    /// Decode a detail column.
+    ///
+    /// The color components are scaled from 0-7 to 0-255.
+    fn from(column: &WaveformColorDetailColumn) -> Self {
+        let component = |value: u8| (u16::from(value) * 255 / 7) as u8;
+        Self {
+            red: component(column.red()),
+            green: component(column.green()),
+            blue: component(column.blue()),
+            height: column.height(),
+        }
+    }
 }
 
 /// Music classification that is used for Lightnight mode and based on rhythm, tempo kick drum and
@@ -549,6 +769,11 @@ pub enum Content {
     /// All beats in the track.
     #[br(pre_assert(header.kind == ContentKind::BeatGrid))]
     BeatGrid(BeatGrid),
+    /// Extended version of the beat grid.
+    ///
+    /// Used in `.EXT` files.
+    #[br(pre_assert(header.kind == ContentKind::ExtendedBeatGrid))]
+    ExtendedBeatGrid(#[br(args(header.clone()))] ExtendedBeatGrid),
     /// List of cue points or loops (either hot cues or memory cues).
     #[br(pre_assert(header.kind == ContentKind::CueList))]
     CueList(CueList),
@@ -594,7 +819,12 @@ pub enum Content {
     ///
     /// This allows handling files that contain unknown section types and allows to access later
     /// sections in the file that have a known type instead of failing to parse the whole file.
-    #[br(pre_assert(matches!(header.kind, ContentKind::Unknown(_))))]
+    /// Sections of a known type whose layout is not fully understood (currently `PQT2`) are also
+    /// parsed as unknown content if they don't match the expected layout.
+    #[br(pre_assert(matches!(
+        header.kind,
+        ContentKind::Unknown(_) | ContentKind::ExtendedBeatGrid
+    )))]
     Unknown(#[br(args(header.clone()))] Unknown),
 }
 
@@ -617,6 +847,91 @@ pub struct BeatGrid {
     pub beats: Vec<Beat>,
 }
 
+impl BeatGrid {
+    /// Position of the given time in beats, counted from the first beat of the grid (i.e. `0.0`
+    /// is the first beat and `1.5` is halfway between the second and the third beat).
+    ///
+    /// Times between two beats are interpolated linearly. Times before the first or after the last
+    /// beat are extrapolated using the tempo of that beat (which results in negative positions for
+    /// times before the first beat). Returns `None` if the grid is empty, if the tempo needed for
+    /// extrapolation is zero, or if the beats around `time_ms` are not sorted by time (which
+    /// only happens in corrupt files).
+    #[must_use]
+    pub fn beat_position(&self, time_ms: u32) -> Option<f64> {
+        let index = self
+            .beats
+            .partition_point(|beat| beat.time <= time_ms)
+            .saturating_sub(1);
+        let beat = self.beats.get(index)?;
+        let beat_length_ms = match self.beats.get(index + 1) {
+            Some(next_beat) if beat.time <= time_ms => {
+                f64::from(next_beat.time.checked_sub(beat.time)?)
+            }
+            _ if beat.tempo == 0 => return None,
+            _ => 6_000_000.0 / f64::from(beat.tempo),
+        };
+        if beat_length_ms <= 0.0 {
+            return None;
+        }
+        let offset_ms = f64::from(time_ms) - f64::from(beat.time);
+        Some(index as f64 + offset_ms / beat_length_ms)
+    }
+}
+
+/// Extended version of the beat grid.
+///
+/// Unlike the legacy beat grid (`PQTZ`, see [`BeatGrid`]), this section (`PQT2`) does not list the
+/// time and tempo of every beat. Instead, it contains a few reference beats that define the tempo
+/// (in all known files, these are the first and the last beat of the track), followed by two
+/// bytes of data per beat whose meaning is unknown.
+///
+/// Since this layout has only been observed in a few files, sections that don't match it are
+/// parsed as [`Content::Unknown`] instead of failing to parse the whole file.
+///
+/// Used in `.EXT` files.
+#[binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[br(import(header: Header))]
+pub struct ExtendedBeatGrid {
+    /// Unknown field (apparently always zero).
+    unknown1: u32,
+    /// Unknown field (apparently always `0x01000002`).
+    unknown2: u32,
+    /// Unknown field (apparently always zero).
+    unknown3: u32,
+    /// Reference beats that define the tempo of the beat grid.
+    ///
+    /// The header contains as many of these as fit between the fixed fields, which is two (the
+    /// first and the last beat of the track) in all known files.
+    #[br(assert(
+        header.remaining_size() >= 28 && (header.remaining_size() - 28) % 8 == 0,
+        "unexpected PQT2 header size {}",
+        header.size
+    ))]
+    #[br(count = header.remaining_size().saturating_sub(28) / 8)]
+    pub reference_beats: Vec<Beat>,
+    /// Number of beats in this beat grid.
+    #[br(temp)]
+    #[br(assert(u64::from(len_beats) * 2 == u64::from(header.content_size())))]
+    #[bw(calc = beat_data.len() as u32)]
+    len_beats: u32,
+    /// Unknown field.
+    unknown4: u32,
+    /// Unknown field (apparently always zero).
+    unknown5: u64,
+    /// Unknown data for each beat.
+    #[br(count = len_beats)]
+    pub beat_data: Vec<u16>,
+}
+
+impl ExtendedBeatGrid {
+    /// Number of beats in this beat grid.
+    #[must_use]
+    pub fn len_beats(&self) -> usize {
+        self.beat_data.len()
+    }
+}
+
 /// List of cue points or loops (either hot cues or memory cues).
 #[binrw]
 #[derive(Debug, PartialEq, Eq)]
@@ -959,6 +1274,143 @@ pub struct ANLZ {
 }
 
 impl ANLZ {
+    /// Returns all hot cues and memory cues (including loops) in this file.
+    ///
+    /// If the file contains an extended cue list (`PCO2`, usually found in `.EXT` files) for a
+    /// given list type, the extended cues are returned and the legacy cue list (`PCOB`) of the
+    /// same type is ignored, because both contain the same cues.
+    pub fn cues(&self) -> Vec<CuePoint> {
+        let has_extended_list = |list_type: CueListType| {
+            self.sections.iter().any(|section| {
+                matches!(&section.content, Content::ExtendedCueList(list) if list.list_type == list_type)
+            })
+        };
+
+        self.sections
+            .iter()
+            .flat_map(|section| match &section.content {
+                Content::CueList(list) if !has_extended_list(list.list_type) => list
+                    .cues
+                    .iter()
+                    .map(|cue| CuePoint::from((list.list_type, cue)))
+                    .collect(),
+                Content::ExtendedCueList(list) => list
+                    .cues
+                    .iter()
+                    .map(|cue| CuePoint::from((list.list_type, cue)))
+                    .collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    /// Decoded columns of the fixed-width colored preview of the track waveform (`PWV4`, found in
+    /// `.EXT` files), or `None` if this file contains no such section.
+    ///
+    /// See [`ColorColumn`] for how the columns are decoded.
+    pub fn waveform_color_preview_columns(&self) -> Option<Vec<ColorColumn>> {
+        self.sections
+            .iter()
+            .find_map(|section| match &section.content {
+                Content::WaveformColorPreview(waveform) => {
+                    Some(waveform.data.iter().map(ColorColumn::from).collect())
+                }
+                _ => None,
+            })
+    }
+
+    /// Decoded columns of the variable-width colored version of the track waveform (`PWV5`, found
+    /// in `.EXT` files), or `None` if this file contains no such section.
+    ///
+    /// See [`ColorColumn`] for how the columns are decoded.
+    pub fn waveform_color_detail_columns(&self) -> Option<Vec<ColorColumn>> {
+        self.sections
+            .iter()
+            .find_map(|section| match &section.content {
+                Content::WaveformColorDetail(waveform) => {
+                    Some(waveform.data.iter().map(ColorColumn::from).collect())
+                }
+                _ => None,
+            })
+    }
+
+    /// Column heights of the tiny monochrome preview of the track waveform (`PWV2`, found in
+    /// `.DAT` files), or `None` if this file contains no such section.
+    ///
+    /// This is a smaller version of the monochrome waveform preview (`PWAV`, see
+    /// [`WaveformPreview`]), which was used by the CDJ-900 and is suitable for thumbnails. While
+    /// the `PWAV` section consists of 400 one-byte columns with a height from 0 to 31 and a
+    /// whiteness value, the `PWV2` section (20 header bytes followed by the data) consists of 100
+    /// one-byte columns that only contain a height from 0 to 15. Neither preview contains color
+    /// information, for that see [`ANLZ::waveform_color_preview_columns`].
+    pub fn tiny_waveform_preview_heights(&self) -> Option<Vec<u8>> {
+        self.sections
+            .iter()
+            .find_map(|section| match &section.content {
+                Content::TinyWaveformPreview(waveform) => {
+                    Some(waveform.data.iter().map(|column| column.height()).collect())
+                }
+                _ => None,
+            })
+    }
+
+    /// Iterate over the type (four-character code) and content of all sections (tags), in the
+    /// order in which they appear in the file.
+    ///
+    /// This includes sections of unknown type (see [`ANLZ::unknown_tags`]). Since the sections
+    /// are written in the same order, the file structure is preserved when writing the file.
+    pub fn tags(&self) -> impl Iterator<Item = ([u8; 4], &Content)> + '_ {
+        self.sections
+            .iter()
+            .map(|section| (section.header.kind.four_cc(), &section.content))
+    }
+
+    /// Returns the type (four-character code) and raw data of all sections that have an unknown
+    /// type, in the order in which they appear in the file.
+    ///
+    /// The data is everything that follows the common 12-byte section header (i.e. the remaining
+    /// header data followed by the content). These sections are written back unchanged, so they
+    /// are preserved when modifying and writing the file.
+    #[must_use]
+    pub fn unknown_tags(&self) -> Vec<([u8; 4], Vec<u8>)> {
+        self.sections
+            .iter()
+            .filter_map(|section| match (&section.header.kind, &section.content) {
+                (ContentKind::Unknown(four_cc), Content::Unknown(unknown)) => Some((
+                    *four_cc,
+                    [unknown.header_data.as_slice(), &unknown.content_data].concat(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Parse all analysis files of a track.
+    ///
+    /// `base_path` is the path of the `.DAT` file (e.g. from the analysis path of a track row),
+    /// with or without extension. The `.EXT` and `.2EX` files next to it are parsed as well, if
+    /// they exist.
+    pub fn load_all<P: AsRef<std::path::Path>>(base_path: P) -> RekordcrateResult<TrackAnalysis> {
+        let read = |path: std::path::PathBuf| -> RekordcrateResult<ANLZ> {
+            let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+            Ok(ANLZ::read(&mut reader)?)
+        };
+        let read_optional = |path: std::path::PathBuf| -> RekordcrateResult<Option<ANLZ>> {
+            if path.exists() {
+                read(path).map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+
+        let base_path = base_path.as_ref();
+        Ok(TrackAnalysis {
+            dat: read(base_path.with_extension("DAT"))?,
+            ext: read_optional(base_path.with_extension("EXT"))?,
+            ex2: read_optional(base_path.with_extension("2EX"))?,
+        })
+    }
+
     fn parse_sections<R: Read + Seek>(
         reader: &mut R,
         endian: Endian,
@@ -976,3 +1428,662 @@ impl ANLZ {
         Ok(sections)
     }
 }
+
+/// All analysis files of a track, returned by [`ANLZ::load_all`].
+///
+/// The accessors look up the data in all files, preferring the newer files (`.EXT` and `.2EX`)
+/// over the `.DAT` file, because they contain extended versions of the same data.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TrackAnalysis {
+    /// The `.DAT` file.
+    pub dat: ANLZ,
+    /// The `.EXT` file, if present.
+    pub ext: Option<ANLZ>,
+    /// The `.2EX` file, if present.
+    pub ex2: Option<ANLZ>,
+}
+
+impl TrackAnalysis {
+    /// Iterate over the files, ordered by preference (`.EXT`, `.2EX` and `.DAT`).
+    pub fn files(&self) -> impl Iterator<Item = &ANLZ> + '_ {
+        self.ext
+            .iter()
+            .chain(self.ex2.iter())
+            .chain(std::iter::once(&self.dat))
+    }
+
+    /// Return the first section content for which `f` returns `Some`.
+    fn find_content<'a, T>(&'a self, f: impl Fn(&'a Content) -> Option<&'a T>) -> Option<&'a T> {
+        self.iter_sections().find_map(|section| f(&section.content))
+    }
+
+    /// Returns all hot cues and memory cues (including loops) of the first file that contains
+    /// cue lists (see [`ANLZ::cues`]).
+    pub fn cues(&self) -> Vec<CuePoint> {
+        self.files()
+            .find(|file| {
+                file.sections.iter().any(|section| {
+                    matches!(
+                        section.content,
+                        Content::CueList(_) | Content::ExtendedCueList(_)
+                    )
+                })
+            })
+            .map(ANLZ::cues)
+            .unwrap_or_default()
+    }
+
+    /// All beats in the track.
+    ///
+    /// This is the legacy beat grid (`PQTZ`), which lists the time and tempo of every beat and
+    /// thus also covers tracks with tempo changes. The extended beat grid (`PQT2`, see
+    /// [`TrackAnalysis::extended_beat_grid`]) is not used here, because it only contains a few
+    /// reference beats and per-beat data that is not understood yet.
+    pub fn beat_grid(&self) -> Option<&BeatGrid> {
+        self.find_content(|content| match content {
+            Content::BeatGrid(beat_grid) => Some(beat_grid),
+            _ => None,
+        })
+    }
+
+    /// Extended version of the beat grid, which is only present in `.EXT` files.
+    pub fn extended_beat_grid(&self) -> Option<&ExtendedBeatGrid> {
+        self.find_content(|content| match content {
+            Content::ExtendedBeatGrid(beat_grid) => Some(beat_grid),
+            _ => None,
+        })
+    }
+
+    /// Path of the audio file that this analysis belongs to.
+    pub fn path(&self) -> Option<&Path> {
+        self.find_content(|content| match content {
+            Content::Path(path) => Some(path),
+            _ => None,
+        })
+    }
+
+    /// Fixed-width monochrome preview of the track waveform.
+    pub fn waveform_preview(&self) -> Option<&WaveformPreview> {
+        self.find_content(|content| match content {
+            Content::WaveformPreview(waveform) => Some(waveform),
+            _ => None,
+        })
+    }
+
+    /// Smaller version of the fixed-width monochrome preview of the track waveform.
+    pub fn tiny_waveform_preview(&self) -> Option<&TinyWaveformPreview> {
+        self.find_content(|content| match content {
+            Content::TinyWaveformPreview(waveform) => Some(waveform),
+            _ => None,
+        })
+    }
+
+    /// Variable-width large monochrome version of the track waveform.
+    pub fn waveform_detail(&self) -> Option<&WaveformDetail> {
+        self.find_content(|content| match content {
+            Content::WaveformDetail(waveform) => Some(waveform),
+            _ => None,
+        })
+    }
+
+    /// Fixed-width colored version of the track waveform.
+    pub fn waveform_color_preview(&self) -> Option<&WaveformColorPreview> {
+        self.find_content(|content| match content {
+            Content::WaveformColorPreview(waveform) => Some(waveform),
+            _ => None,
+        })
+    }
+
+    /// Variable-width large colored version of the track waveform.
+    pub fn waveform_color_detail(&self) -> Option<&WaveformColorDetail> {
+        self.find_content(|content| match content {
+            Content::WaveformColorDetail(waveform) => Some(waveform),
+            _ => None,
+        })
+    }
+
+    /// Structure of the song (intro, chorus, verse, etc.).
+    pub fn song_structure(&self) -> Option<&SongStructure> {
+        self.find_content(|content| match content {
+            Content::SongStructure(song_structure) => Some(song_structure),
+            _ => None,
+        })
+    }
+}
+
+/// Source of analysis data, i.e. a single analysis file ([`ANLZ`]) or all analysis files of a
+/// track ([`TrackAnalysis`]).
+///
+/// The sections (tags) of the analysis data of a track are split across several files, because
+/// newer sections were only added to copies of the `.DAT` file that older players don't read.
+/// Typically, the files contain these tags:
+///
+/// - `.DAT`: `PPTH` (path), `PVBR` (seek information), `PQTZ` (beat grid), `PWAV` and `PWV2`
+///   (monochrome waveform previews) and `PCOB` (cue lists).
+/// - `.EXT`: `PPTH`, `PWV3` (monochrome waveform detail), `PCOB`, `PCO2` (extended cue lists),
+///   `PQT2` (extended beat grid), `PWV4` and `PWV5` (colored waveforms) and `PSSI` (song
+///   structure).
+/// - `.2EX`: `PPTH`, `PWV6`, `PWV7` and `PWVC` (three-band waveforms).
+///
+/// This trait allows querying tags regardless of which file they live in. Tags that this library
+/// can't parse (e.g. `PWV6`) can be looked up using [`ContentKind::Unknown`].
+pub trait AnalysisSource {
+    /// Iterate over all sections. If there are several files, they are ordered by preference
+    /// (see [`TrackAnalysis::files`]).
+    fn iter_sections(&self) -> Box<dyn Iterator<Item = &Section> + '_>;
+
+    /// Content of the first section of the given kind, or `None` if there is no such section.
+    fn find_tag(&self, kind: &ContentKind) -> Option<&Content> {
+        self.iter_sections()
+            .find(|section| &section.header.kind == kind)
+            .map(|section| &section.content)
+    }
+
+    /// Returns `true` if there is a section of the given kind.
+    fn has_tag(&self, kind: &ContentKind) -> bool {
+        self.find_tag(kind).is_some()
+    }
+}
+
+impl AnalysisSource for ANLZ {
+    fn iter_sections(&self) -> Box<dyn Iterator<Item = &Section> + '_> {
+        Box::new(self.sections.iter())
+    }
+}
+
+impl AnalysisSource for TrackAnalysis {
+    fn iter_sections(&self) -> Box<dyn Iterator<Item = &Section> + '_> {
+        Box::new(self.files().flat_map(|file| file.sections.iter()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cue(hot_cue: u32, cue_type: CueType, time: u32, loop_time: u32) -> Cue {
+        Cue {
+            header: Header {
+                kind: ContentKind::Cue,
+                size: 28,
+                total_size: 56,
+            },
+            hot_cue,
+            status: 0,
+            unknown1: 0x0010_0000,
+            order_first: 0xFFFF,
+            order_last: 0xFFFF,
+            cue_type,
+            unknown2: 0,
+            unknown3: 0x03E8,
+            time,
+            loop_time,
+            unknown4: 0,
+            unknown5: 0,
+            unknown6: 0,
+            unknown7: 0,
+        }
+    }
+
+    fn cue_list_section(list_type: CueListType, cues: Vec<Cue>) -> Section {
+        let len_cues = u32::try_from(cues.len()).unwrap();
+        Section {
+            header: Header {
+                kind: ContentKind::CueList,
+                size: 24,
+                total_size: 24 + 56 * len_cues,
+            },
+            content: Content::CueList(CueList {
+                list_type,
+                unknown: 0,
+                memory_count: len_cues,
+                cues,
+            }),
+        }
+    }
+
+    fn anlz(sections: Vec<Section>) -> ANLZ {
+        ANLZ {
+            header: Header {
+                kind: ContentKind::File,
+                size: 28,
+                total_size: 28 + sections.iter().map(|s| s.header.total_size).sum::<u32>(),
+            },
+            header_data: vec![0; 16],
+            sections,
+        }
+    }
+
+    #[test]
+    fn cues_distinguish_points_and_loops() {
+        let file = anlz(vec![
+            cue_list_section(
+                CueListType::HotCues,
+                vec![cue(1, CueType::Point, 1000, 0xFFFF_FFFF)],
+            ),
+            cue_list_section(
+                CueListType::MemoryCues,
+                vec![cue(0, CueType::Loop, 5000, 7000)],
+            ),
+        ]);
+
+        let cues = file.cues();
+        assert_eq!(
+            cues,
+            vec![
+                CuePoint {
+                    list_type: CueListType::HotCues,
+                    hot_cue: 1,
+                    time_ms: 1000,
+                    loop_end_ms: None,
+                    color: ColorIndex::None,
+                    hot_cue_color_rgb: None,
+                    comment: String::new(),
+                },
+                CuePoint {
+                    list_type: CueListType::MemoryCues,
+                    hot_cue: 0,
+                    time_ms: 5000,
+                    loop_end_ms: Some(7000),
+                    color: ColorIndex::None,
+                    hot_cue_color_rgb: None,
+                    comment: String::new(),
+                },
+            ]
+        );
+        assert!(!cues[0].is_loop());
+        assert_eq!(cues[0].loop_length_ms(), None);
+        assert!(cues[1].is_loop());
+        assert_eq!(cues[1].loop_length_ms(), Some(2000));
+    }
+
+    fn extended_cue(hot_cue: u32, time: u32, color: ColorIndex, comment: &str) -> ExtendedCue {
+        let len_comment = (u32::try_from(comment.encode_utf16().count()).unwrap() + 1) * 2;
+        ExtendedCue {
+            header: Header {
+                kind: ContentKind::ExtendedCue,
+                size: 16,
+                total_size: 44 + len_comment + 24,
+            },
+            hot_cue,
+            cue_type: CueType::Point,
+            unknown1: 0,
+            unknown2: 0x03E8,
+            time,
+            loop_time: 0xFFFF_FFFF,
+            color,
+            unknown3: 0,
+            unknown4: 0,
+            unknown5: 0,
+            loop_numerator: 0,
+            loop_denominator: 0,
+            comment: comment.into(),
+            hot_cue_color_index: 0,
+            hot_cue_color_rgb: (0, 0, 0),
+            unknown6: 0,
+            unknown7: 0,
+            unknown8: 0,
+            unknown9: 0,
+            unknown10: 0,
+        }
+    }
+
+    fn extended_cue_list_section(list_type: CueListType, cues: Vec<ExtendedCue>) -> Section {
+        Section {
+            header: Header {
+                kind: ContentKind::ExtendedCueList,
+                size: 20,
+                total_size: 20 + cues.iter().map(|cue| cue.header.total_size).sum::<u32>(),
+            },
+            content: Content::ExtendedCueList(ExtendedCueList {
+                list_type,
+                unknown: 0,
+                cues,
+            }),
+        }
+    }
+
+    #[test]
+    fn extended_cue_comments_and_colors() {
+        let mut file = anlz(vec![extended_cue_list_section(
+            CueListType::MemoryCues,
+            vec![
+                extended_cue(0, 1000, ColorIndex::Red, "drop"),
+                extended_cue(0, 5000, ColorIndex::None, ""),
+                extended_cue(0, 9000, ColorIndex::Aqua, "breakdown"),
+            ],
+        )]);
+        let mut writer = binrw::io::Cursor::new(vec![]);
+        file.write(&mut writer).unwrap();
+        writer.set_position(0);
+        assert_eq!(ANLZ::read(&mut writer).unwrap(), file);
+
+        // The legacy memory cue list is ignored in favor of the extended one.
+        file.sections.push(cue_list_section(
+            CueListType::MemoryCues,
+            vec![cue(0, CueType::Point, 1000, 0xFFFF_FFFF)],
+        ));
+        let cues = file.cues();
+        assert_eq!(
+            cues.iter()
+                .map(|cue| (cue.list_type(), cue.time_ms(), cue.comment(), cue.color()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    CueListType::MemoryCues,
+                    1000,
+                    Some("drop"),
+                    Some(ColorIndex::Red)
+                ),
+                (CueListType::MemoryCues, 5000, None, None),
+                (
+                    CueListType::MemoryCues,
+                    9000,
+                    Some("breakdown"),
+                    Some(ColorIndex::Aqua)
+                ),
+            ]
+        );
+        assert!(cues.iter().all(|cue| cue.hot_cue_color_rgb().is_none()));
+    }
+
+    #[test]
+    fn cue_list_roundtrip() {
+        let file = anlz(vec![cue_list_section(
+            CueListType::HotCues,
+            vec![
+                cue(1, CueType::Point, 1000, 0xFFFF_FFFF),
+                cue(2, CueType::Loop, 5000, 7000),
+            ],
+        )]);
+        let mut writer = binrw::io::Cursor::new(vec![]);
+        file.write(&mut writer).unwrap();
+        assert_eq!(
+            u64::try_from(writer.get_ref().len()).unwrap(),
+            u64::from(file.header.total_size)
+        );
+        writer.set_position(0);
+        assert_eq!(ANLZ::read(&mut writer).unwrap(), file);
+    }
+
+    #[test]
+    fn beat_position() {
+        let beat = |time| Beat {
+            beat_number: 1,
+            tempo: 12000,
+            time,
+        };
+        let beat_grid = BeatGrid {
+            unknown1: 0,
+            unknown2: 0x0080_0000,
+            beats: vec![beat(1000), beat(1500), beat(2100)],
+        };
+        assert_eq!(beat_grid.beat_position(1000), Some(0.0));
+        assert_eq!(beat_grid.beat_position(1250), Some(0.5));
+        assert_eq!(beat_grid.beat_position(1800), Some(1.5));
+        // Outside of the grid, the tempo of the first/last beat (120 BPM) is used.
+        assert_eq!(beat_grid.beat_position(750), Some(-0.5));
+        assert_eq!(beat_grid.beat_position(2350), Some(2.5));
+
+        let empty = BeatGrid {
+            unknown1: 0,
+            unknown2: 0x0080_0000,
+            beats: vec![],
+        };
+        assert_eq!(empty.beat_position(1000), None);
+
+        // Corrupt grids that are not sorted by time must not cause a panic.
+        let unsorted = BeatGrid {
+            unknown1: 0,
+            unknown2: 0x0080_0000,
+            beats: vec![beat(1000), beat(3000), beat(2000), beat(500)],
+        };
+        for time_ms in (0..4000).step_by(100) {
+            let _ = unsorted.beat_position(time_ms);
+        }
+    }
+
+    #[test]
+    fn tiny_waveform_preview_heights() {
+        let dat = ANLZ::read(
+            &mut std::fs::File::open(
+                "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let preview = dat.tiny_waveform_preview_heights().unwrap();
+        assert_eq!(preview.len(), 100);
+        assert!(preview.iter().all(|height| *height <= 15));
+        assert_eq!(preview[..5], [14, 14, 15, 15, 14]);
+
+        let ext = ANLZ::read(
+            &mut std::fs::File::open(
+                "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.EXT",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(ext.tiny_waveform_preview_heights(), None);
+    }
+
+    #[test]
+    fn waveform_colors() {
+        let mut reader = std::fs::File::open(
+            "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.EXT",
+        )
+        .unwrap();
+        let anlz = ANLZ::read(&mut reader).unwrap();
+
+        let preview = anlz.waveform_color_preview_columns().unwrap();
+        assert_eq!(preview.len(), 1200);
+        let color = |red, green, blue, height| ColorColumn {
+            red,
+            green,
+            blue,
+            height,
+        };
+        assert_eq!(
+            preview[100..103],
+            [
+                color(255, 247, 27, 65),
+                color(255, 171, 5, 98),
+                color(255, 221, 4, 61),
+            ]
+        );
+
+        let detail = anlz.waveform_color_detail_columns().unwrap();
+        assert_eq!(detail.len(), 25866);
+        assert_eq!(
+            detail[1003..1006],
+            [
+                color(255, 0, 0, 1),
+                color(255, 0, 0, 0),
+                color(255, 109, 36, 11),
+            ]
+        );
+
+        // The `.DAT` file contains no colored waveforms.
+        let mut reader = std::fs::File::open(
+            "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT",
+        )
+        .unwrap();
+        let anlz = ANLZ::read(&mut reader).unwrap();
+        assert_eq!(anlz.waveform_color_preview_columns(), None);
+    }
+
+    #[test]
+    fn extended_beat_grid() {
+        for base_path in [
+            "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000",
+            "data/complete_export/demo_tracks/PIONEER/USBANLZ/P053/0001D21F/ANLZ0000",
+        ] {
+            let analysis = ANLZ::load_all(base_path).unwrap();
+            let beats = &analysis.beat_grid().unwrap().beats;
+            let extended_beat_grid = analysis.extended_beat_grid().unwrap();
+            assert_eq!(extended_beat_grid.len_beats(), beats.len());
+            assert_eq!(
+                extended_beat_grid
+                    .reference_beats
+                    .iter()
+                    .collect::<Vec<_>>(),
+                [beats.first().unwrap(), beats.last().unwrap()]
+            );
+            assert!(analysis
+                .dat
+                .find_tag(&ContentKind::ExtendedBeatGrid)
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn extended_beat_grid_fallback() {
+        // The number of beats (4) does not match the size of the beat data (3 beats).
+        let mut data = b"PQT2".to_vec();
+        data.extend(56u32.to_be_bytes());
+        data.extend(62u32.to_be_bytes());
+        data.extend([0; 12]);
+        data.extend([0, 1, 0x32, 0, 0, 0, 0, 25]);
+        data.extend([0, 4, 0x32, 0, 0, 0, 0x0B, 0xB8]);
+        data.extend(4u32.to_be_bytes());
+        data.extend([0; 12]);
+        data.extend([0; 6]);
+
+        let section =
+            Section::read_options(&mut binrw::io::Cursor::new(&data), Endian::Big, ()).unwrap();
+        assert_eq!(section.header.kind, ContentKind::ExtendedBeatGrid);
+        assert!(matches!(section.content, Content::Unknown(_)));
+        let mut writer = binrw::io::Cursor::new(vec![]);
+        section.write_options(&mut writer, Endian::Big, ()).unwrap();
+        assert_eq!(writer.into_inner(), data);
+    }
+
+    #[test]
+    fn load_all() {
+        let base_path = "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000";
+        let analysis = ANLZ::load_all(format!("{base_path}.DAT")).unwrap();
+        assert_eq!(ANLZ::load_all(base_path).unwrap(), analysis);
+        let ext = analysis.ext.as_ref().unwrap();
+        assert!(analysis.ex2.is_some());
+
+        // Data from the `.DAT` file.
+        assert!(analysis.beat_grid().is_some());
+        assert!(analysis.waveform_preview().is_some());
+        assert!(analysis.tiny_waveform_preview().is_some());
+        assert_eq!(
+            analysis.path().unwrap().path.to_string(),
+            "/Contents/Loopmasters/UnknownAlbum/Demo Track 1.mp3"
+        );
+        // Data that only exists in the `.EXT` file.
+        assert!(analysis.waveform_detail().is_some());
+        assert!(analysis.waveform_color_preview().is_some());
+        assert!(analysis.waveform_color_detail().is_some());
+        assert_eq!(analysis.cues(), ext.cues());
+
+        assert!(ANLZ::load_all("data/does-not-exist/ANLZ0000.DAT").is_err());
+    }
+
+    #[test]
+    fn analysis_source() {
+        let base_path = "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000";
+        let analysis = ANLZ::load_all(base_path).unwrap();
+        let ext = analysis.ext.as_ref().unwrap();
+        let ex2 = analysis.ex2.as_ref().unwrap();
+
+        // Tags that only exist in the `.EXT` file.
+        for kind in [
+            ContentKind::WaveformColorPreview,
+            ContentKind::ExtendedBeatGrid,
+        ] {
+            assert!(analysis.has_tag(&kind));
+            assert!(ext.has_tag(&kind));
+            assert!(!analysis.dat.has_tag(&kind));
+        }
+        assert_eq!(
+            analysis.find_tag(&ContentKind::WaveformColorPreview),
+            ext.find_tag(&ContentKind::WaveformColorPreview)
+        );
+        // Tags that only exist in the `.DAT` and `.2EX` files.
+        assert!(analysis.has_tag(&ContentKind::VBR));
+        assert!(!ext.has_tag(&ContentKind::VBR));
+        assert!(analysis.has_tag(&ContentKind::Unknown(*b"PWV6")));
+        assert!(ex2.has_tag(&ContentKind::Unknown(*b"PWV6")));
+        assert!(!analysis.has_tag(&ContentKind::Unknown(*b"XXXX")));
+
+        // The `.EXT` file is preferred over the `.DAT` file.
+        assert!(std::ptr::eq(
+            analysis.find_tag(&ContentKind::Path).unwrap(),
+            ext.find_tag(&ContentKind::Path).unwrap()
+        ));
+        assert_eq!(
+            analysis.iter_sections().count(),
+            analysis
+                .files()
+                .map(|file| file.iter_sections().count())
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn tags_in_file_order() {
+        let data = include_bytes!(
+            "../data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.EXT"
+        );
+        let file = ANLZ::read(&mut binrw::io::Cursor::new(data)).unwrap();
+        let four_ccs: Vec<[u8; 4]> = file.tags().map(|(four_cc, _)| four_cc).collect();
+        assert_eq!(
+            four_ccs,
+            [
+                b"PPTH", b"PWV3", b"PCOB", b"PCOB", b"PCO2", b"PCO2", b"PQT2", b"PWV5", b"PWV4",
+                b"PSSI",
+            ]
+            .map(|four_cc| *four_cc)
+        );
+        assert!(matches!(
+            file.tags().nth(1),
+            Some((_, Content::WaveformDetail(_)))
+        ));
+        assert!(matches!(
+            file.tags().nth(6),
+            Some((_, Content::ExtendedBeatGrid(_)))
+        ));
+
+        // The four-character codes match the magic values that are written to the file.
+        for section in &file.sections {
+            let mut writer = binrw::io::Cursor::new(vec![]);
+            section.header.kind.write(&mut writer).unwrap();
+            assert_eq!(writer.into_inner(), section.header.kind.four_cc());
+        }
+    }
+
+    #[test]
+    fn unknown_tags_roundtrip() {
+        let data = include_bytes!(
+            "../data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.2EX"
+        );
+        let file = ANLZ::read(&mut binrw::io::Cursor::new(data)).unwrap();
+        let unknown_tags = file.unknown_tags();
+        let four_ccs: Vec<&[u8; 4]> = unknown_tags.iter().map(|(four_cc, _)| four_cc).collect();
+        assert_eq!(four_ccs, vec![b"PWV7", b"PWV6", b"PWVC"]);
+        for (four_cc, tag_data) in &unknown_tags {
+            let section = file
+                .sections
+                .iter()
+                .find(|section| section.header.kind == ContentKind::Unknown(*four_cc))
+                .unwrap();
+            assert_eq!(
+                u32::try_from(tag_data.len()).unwrap() + 12,
+                section.header.total_size
+            );
+        }
+
+        let mut writer = binrw::io::Cursor::new(vec![]);
+        file.write(&mut writer).unwrap();
+        assert_eq!(writer.get_ref().as_slice(), data.as_slice());
+        writer.set_position(0);
+        assert_eq!(
+            ANLZ::read(&mut writer).unwrap().unknown_tags(),
+            unknown_tags
+        );
+    }
+}
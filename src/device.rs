@@ -0,0 +1,1600 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! High-level access to complete device exports.
+//!
+//! A device export (e.g. on a USB drive) contains a `PIONEER` directory with the databases in
+//! `PIONEER/rekordbox`:
+//!
+//! - `export.pdb` contains the tracks, their metadata and the playlists.
+//! - `exportExt.pdb` contains additional data, such as "My Tag" tags. This file does not exist in
+//!   exports created by older Rekordbox versions.
+//!
+//! The `PIONEER` directory also contains the device settings (`*SETTING.DAT`) files, which can be
+//! parsed using the [`setting`](crate::setting) module or all at once using
+//! [`DeviceExport::get_all_settings`].
+
+use crate::anlz::{TrackAnalysis, ANLZ};
+use crate::pdb::{
+    io::Database, ArtistId, Artwork, ArtworkId, ExtPageType, ExtRow, PageType, PlaylistEntry,
+    PlaylistTreeNode, PlaylistTreeNodeId, Row, Tag, TagId, Track, TrackId,
+};
+use crate::setting::{Setting, SettingType};
+use crate::util::{normalize_path, RekordcrateResult};
+use binrw::BinRead;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// A device export, consisting of the plain database and (optionally) the extended database.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::{device::DeviceExport, pdb::TrackId};
+///
+/// let export = DeviceExport::open("data/complete_export/demo_tracks")?;
+/// let track = export.merged_track(TrackId(1))?.expect("track not found");
+/// assert!(track.tags.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DeviceExport {
+    /// Path of the directory that contains the `PIONEER` directory, or `None` if the export was
+    /// opened from memory (see [`DeviceExport::from_pdb_bytes`]).
+    root: Option<PathBuf>,
+    /// The plain database (`export.pdb`).
+    pdb: Database,
+    /// The extended database (`exportExt.pdb`), if present.
+    ext_pdb: Option<Database>,
+    /// Parsed analysis files, keyed by the analysis path of the track.
+    anlz_cache: RefCell<HashMap<PathBuf, Rc<TrackAnalysis>>>,
+    /// Parsed device settings, if they were loaded when opening the export (see
+    /// [`DeviceExport::load`]).
+    settings: Option<HashMap<SettingType, Setting>>,
+}
+
+/// Overview of the contents of a device export, returned by [`DeviceExport::export_summary`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExportSummary {
+    /// Number of tracks in the plain database.
+    pub track_count: usize,
+    /// Number of playlists (excluding folders) in the plain database.
+    pub playlist_count: usize,
+    /// Number of artists in the plain database.
+    pub artist_count: usize,
+    /// Number of artwork entries in the plain database.
+    pub artwork_count: usize,
+    /// Whether the export contains an extended database (`exportExt.pdb`).
+    pub has_ext_pdb: bool,
+    /// Whether the export contains any device settings (`*SETTING.DAT`) files.
+    pub has_settings: bool,
+}
+
+/// A track with the data from both databases of a device export, returned by
+/// [`DeviceExport::merged_track`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct MergedTrack {
+    /// The track row from the plain database (`export.pdb`), which contains the track metadata
+    /// (title, artist, file path, etc.).
+    pub track: Track,
+    /// Name of the artist, or `None` if the track has no artist.
+    pub artist: Option<String>,
+    /// Name of the composer, or `None` if the track has no composer.
+    pub composer: Option<String>,
+    /// Name of the remixer, or `None` if the track has no remixer.
+    pub remixer: Option<String>,
+    /// Name of the original artist, or `None` if the track has no original artist.
+    pub original_artist: Option<String>,
+    /// The "My Tag" tags of this track from the extended database (`exportExt.pdb`).
+    ///
+    /// This is empty if the export does not contain an extended database.
+    pub tags: Vec<Tag>,
+}
+
+/// A "My Tag" tag of a track, returned by [`DeviceExport::my_tags`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MyTag {
+    /// ID of the tag.
+    pub id: TagId,
+    /// Name of the tag (e.g. `Acid House`).
+    pub name: String,
+    /// Name of the category that the tag belongs to (e.g. `Genre`), or `None` if the category
+    /// does not exist in the database.
+    pub category: Option<String>,
+}
+
+/// Reason why a track is returned by [`DeviceExport::tracks_without_analysis`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MissingAnalysisReason {
+    /// The track row does not reference an analysis file.
+    NoPath,
+    /// The track row references an analysis file that does not exist in the export.
+    FileMissing,
+}
+
+/// Files that are referenced by the database but missing in the export, returned by
+/// [`DeviceExport::validate_against_filesystem`].
+///
+/// The tracks in each list are in the order in which they are stored in the database.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ValidationReport {
+    /// Tracks whose audio file does not exist.
+    pub missing_media: Vec<Track>,
+    /// Tracks that have no analysis file path or whose analysis (`.DAT`) file does not exist.
+    pub missing_analysis: Vec<(Track, MissingAnalysisReason)>,
+    /// Tracks that reference an artwork image that does not exist.
+    pub missing_artwork: Vec<Track>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no files are missing.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.missing_media.is_empty()
+            && self.missing_analysis.is_empty()
+            && self.missing_artwork.is_empty()
+    }
+}
+
+/// Best guess of the Rekordbox version that created an export, returned by
+/// [`DeviceExport::detect_format`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ExportFormat {
+    /// Export created by Rekordbox 6 or later, which writes an extended database
+    /// (`exportExt.pdb`) in addition to the plain database.
+    Rekordbox6,
+    /// Export created by Rekordbox 5 or earlier, which only writes the plain database.
+    Rekordbox5,
+    /// The export does not match any of the known layouts.
+    Unknown(FormatSignals),
+}
+
+/// The characteristics of an export that are used to detect its [`ExportFormat`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FormatSignals {
+    /// Size of a single page of the plain database in bytes.
+    pub page_size: u32,
+    /// Number of tables in the plain database.
+    pub num_tables: usize,
+    /// Whether the tables of the plain database have the page types (and order) that Rekordbox
+    /// writes.
+    pub has_standard_tables: bool,
+    /// Number of tables in the extended database, or `None` if the export does not contain an
+    /// extended database.
+    pub ext_num_tables: Option<usize>,
+}
+
+/// What a [`DeviceExport`] supports, depending on how it was constructed, returned by
+/// [`DeviceExport::capabilities`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Capabilities {
+    /// Whether the plain database can be written back with [`DeviceExport::save`], i.e. the export
+    /// was opened from a directory and its plain database (`export.pdb`) is not read-only.
+    ///
+    /// Only the permissions of the file are checked, so this is still `true` if the export is on
+    /// a file system that is mounted read-only.
+    pub can_write: bool,
+    /// Whether the export was opened from a directory, so that files referenced by the database
+    /// (audio files, analysis files, artwork and settings files) can be resolved.
+    pub has_filesystem: bool,
+    /// Whether the export contains an extended database (`exportExt.pdb`).
+    pub has_ext_db: bool,
+}
+
+impl DeviceExport {
+    /// Path of the plain database, relative to the export root.
+    const PDB_PATH: &'static str = "PIONEER/rekordbox/export.pdb";
+    /// Path of the extended database, relative to the export root.
+    const EXT_PDB_PATH: &'static str = "PIONEER/rekordbox/exportExt.pdb";
+    /// Directory that contains the device settings files, relative to the export root.
+    const SETTINGS_DIR: &'static str = "PIONEER";
+
+    /// Open the device export at `root` (i.e. the directory that contains the `PIONEER`
+    /// directory).
+    ///
+    /// This parses the headers of the plain database (`export.pdb`, which is required) and of
+    /// the extended database (`exportExt.pdb`, which is optional and only opened if it exists).
+    /// Failing to open either of them is an error. Everything else (rows, analysis files and
+    /// settings files) is only read on demand, so errors in these files are reported by the
+    /// methods that access them. See [`DeviceExport::load`] for loading the settings up front.
+    pub fn open<P: AsRef<Path>>(root: P) -> RekordcrateResult<Self> {
+        let root = root.as_ref().to_path_buf();
+        let pdb = Database::open(root.join(Self::PDB_PATH))?;
+        let ext_pdb_path = root.join(Self::EXT_PDB_PATH);
+        let ext_pdb = if ext_pdb_path.exists() {
+            Some(Database::open(ext_pdb_path)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            root: Some(root),
+            pdb,
+            ext_pdb,
+            anlz_cache: RefCell::default(),
+            settings: None,
+        })
+    }
+
+    /// Open the device export at `root` like [`DeviceExport::open`] and parse all device settings
+    /// (`*SETTING.DAT`) files right away.
+    ///
+    /// This fails fast: if one of the settings files exists but can't be parsed, the whole export
+    /// fails to load, instead of [`DeviceExport::get_all_settings`] failing later. Settings files
+    /// that don't exist are skipped, because they are optional. The parsed settings are kept, so
+    /// [`DeviceExport::get_all_settings`] does not read the files again. Analysis files are still
+    /// read on demand, because there is one per track and most callers only need a few of them.
+    pub fn load<P: AsRef<Path>>(root: P) -> RekordcrateResult<Self> {
+        let mut export = Self::open(root)?;
+        export.settings = Some(export.get_all_settings()?);
+        Ok(export)
+    }
+
+    /// Open an export that only consists of the plain database (`export.pdb`), which has already
+    /// been read into memory (e.g. after downloading it).
+    ///
+    /// Since there is no export directory, all features that access other files are unavailable:
+    /// analysis files and artwork can't be resolved, audio files and settings files are treated
+    /// as missing, and the paths in M3U playlists are relative to the export root.
+    pub fn from_pdb_bytes(data: &[u8]) -> RekordcrateResult<Self> {
+        Ok(Self {
+            root: None,
+            pdb: Database::open_non_persistent(std::io::Cursor::new(data.to_vec()))?,
+            ext_pdb: None,
+            anlz_cache: RefCell::default(),
+            settings: None,
+        })
+    }
+
+    /// Path of the directory that contains the `PIONEER` directory.
+    ///
+    /// This is an empty path if the export was opened from memory (see
+    /// [`DeviceExport::from_pdb_bytes`]).
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        self.root.as_deref().unwrap_or(Path::new(""))
+    }
+
+    /// Resolve a path relative to the export root, or return `None` if the export was opened
+    /// from memory.
+    ///
+    /// Paths from the database have to be passed through [`normalize_path`] first, so that they
+    /// can't point outside of the export root.
+    fn resolve<P: AsRef<Path>>(&self, relative_path: P) -> Option<PathBuf> {
+        self.root.as_ref().map(|root| root.join(relative_path))
+    }
+
+    /// The plain database (`export.pdb`).
+    #[must_use]
+    pub fn pdb(&self) -> &Database {
+        &self.pdb
+    }
+
+    /// The plain database (`export.pdb`), for editing it.
+    ///
+    /// Changes are only kept in memory until they are written back with [`DeviceExport::save`].
+    pub fn pdb_mut(&mut self) -> &mut Database {
+        &mut self.pdb
+    }
+
+    /// Write the plain database (`export.pdb`) back to the export.
+    ///
+    /// Fails if the export was opened from memory (see [`DeviceExport::from_pdb_bytes`]), or if
+    /// the file can't be written. The extended database is never written, because it can't be
+    /// edited.
+    pub fn save(&self) -> RekordcrateResult<()> {
+        let path = self.resolve(Self::PDB_PATH).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "export was opened from memory and can't be saved",
+            )
+        })?;
+        // Unless the database has been edited, its pages are still read from the file, so the
+        // file must not be truncated before the complete database has been read.
+        let mut data = Vec::new();
+        self.pdb.write(&mut data)?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    /// The extended database (`exportExt.pdb`), if present.
+    #[must_use]
+    pub fn ext_pdb(&self) -> Option<&Database> {
+        self.ext_pdb.as_ref()
+    }
+
+    /// Returns what this export supports, so that callers can check this up front instead of
+    /// running into errors or missing files.
+    ///
+    /// Exports created with [`DeviceExport::from_pdb_bytes`] have no filesystem and can't be
+    /// [saved](DeviceExport::save). The read-only flag of the plain database is checked on every call, so
+    /// `can_write` reflects permission changes made after opening the export. Read-only mounts are
+    /// not detected (see [`Capabilities::can_write`]).
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        let can_write = self
+            .resolve(Self::PDB_PATH)
+            .and_then(|path| std::fs::metadata(path).ok())
+            .is_some_and(|metadata| !metadata.permissions().readonly());
+        Capabilities {
+            can_write,
+            has_filesystem: self.root.is_some(),
+            has_ext_db: self.ext_pdb.is_some(),
+        }
+    }
+
+    /// Returns an overview of the contents of this export.
+    ///
+    /// The row counts are determined using [`Database::row_count`], so this does not need to parse
+    /// the rows, except for the playlist tree (to tell playlists and folders apart).
+    pub fn export_summary(&self) -> RekordcrateResult<ExportSummary> {
+        let mut playlist_count = 0;
+        for row in self.pdb.iter_rows(PageType::PlaylistTree) {
+            if let Row::PlaylistTreeNode(node) = row? {
+                if !node.is_folder() {
+                    playlist_count += 1;
+                }
+            }
+        }
+
+        Ok(ExportSummary {
+            track_count: self.pdb.row_count(PageType::Tracks)?,
+            playlist_count,
+            artist_count: self.pdb.row_count(PageType::Artists)?,
+            artwork_count: self.pdb.row_count(PageType::Artwork)?,
+            has_ext_pdb: self.ext_pdb.is_some(),
+            has_settings: SettingType::ALL.iter().any(|setting_type| {
+                self.setting_path(*setting_type)
+                    .is_some_and(|path| path.exists())
+            }),
+        })
+    }
+
+    /// Guess which Rekordbox version created this export.
+    ///
+    /// This is a heuristic that only looks at the database headers:
+    ///
+    /// - The plain database must use 4096 byte pages and contain the 20 tables that Rekordbox
+    ///   writes, in the usual order. Otherwise, the format is [`ExportFormat::Unknown`].
+    /// - If the export contains an extended database (`exportExt.pdb`) with the 9 tables that
+    ///   Rekordbox writes, the export was created by Rekordbox 6 or later.
+    /// - If there is no extended database, the export was created by Rekordbox 5 or earlier.
+    /// - If there is an extended database with a different number of tables, the format is
+    ///   [`ExportFormat::Unknown`].
+    ///
+    /// Exports written by other software (or by this library) that mimic the Rekordbox layout are
+    /// detected as the Rekordbox version they mimic.
+    #[must_use]
+    pub fn detect_format(&self) -> ExportFormat {
+        /// Page size used by all known Rekordbox versions.
+        const PAGE_SIZE: u32 = 4096;
+        /// Number of tables in extended databases written by Rekordbox.
+        const EXT_NUM_TABLES: usize = 9;
+
+        let info = self.pdb.info();
+        let signals = FormatSignals {
+            page_size: info.page_size,
+            num_tables: info.num_tables,
+            has_standard_tables: self.pdb.has_standard_tables(),
+            ext_num_tables: self
+                .ext_pdb
+                .as_ref()
+                .map(|ext_pdb| ext_pdb.info().num_tables),
+        };
+        if signals.page_size != PAGE_SIZE || !signals.has_standard_tables {
+            return ExportFormat::Unknown(signals);
+        }
+        match signals.ext_num_tables {
+            None => ExportFormat::Rekordbox5,
+            Some(EXT_NUM_TABLES) => ExportFormat::Rekordbox6,
+            Some(_) => ExportFormat::Unknown(signals),
+        }
+    }
+
+    /// Path of the device settings file of the given type.
+    fn setting_path(&self, setting_type: SettingType) -> Option<PathBuf> {
+        self.resolve(Path::new(Self::SETTINGS_DIR).join(setting_type.file_name()))
+    }
+
+    /// Parse all device settings (`*SETTING.DAT`) files in the `PIONEER` directory.
+    ///
+    /// Settings files that don't exist are not included in the result. If the export was opened
+    /// using [`DeviceExport::load`], the settings that were parsed back then are returned.
+    pub fn get_all_settings(&self) -> RekordcrateResult<HashMap<SettingType, Setting>> {
+        if let Some(settings) = &self.settings {
+            return Ok(settings.clone());
+        }
+        let mut settings = HashMap::new();
+        for setting_type in SettingType::ALL {
+            let Some(path) = self.setting_path(setting_type).filter(|path| path.exists()) else {
+                continue;
+            };
+            let mut reader = BufReader::new(File::open(path)?);
+            settings.insert(setting_type, Setting::read(&mut reader)?);
+        }
+        Ok(settings)
+    }
+
+    /// Iterate over the tracks in the plain database.
+    ///
+    /// The tracks are streamed from the database, i.e. pages are only read when the iterator is
+    /// advanced and at most one page is held in memory at a time. This keeps the memory usage
+    /// bounded even for huge libraries.
+    pub fn tracks(&self) -> impl Iterator<Item = RekordcrateResult<Track>> + '_ {
+        self.pdb
+            .iter_rows(PageType::Tracks)
+            .filter_map(|row| match row {
+                Ok(Row::Track(track)) => Some(Ok(track)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+    }
+
+    /// Find the track with the given ID in the plain database.
+    pub fn track(&self, id: TrackId) -> RekordcrateResult<Option<Track>> {
+        for track in self.tracks() {
+            let track = track?;
+            if track.id() == id {
+                return Ok(Some(track));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find all tracks that lack analysis data, i.e. tracks that have no analysis file path or
+    /// whose analysis (`.DAT`) file does not exist in the export.
+    ///
+    /// Analysis file paths are resolved relative to the [export root](Self::root).
+    pub fn tracks_without_analysis(
+        &self,
+    ) -> RekordcrateResult<Vec<(Track, MissingAnalysisReason)>> {
+        let mut tracks = vec![];
+        for track in self.tracks() {
+            let track = track?;
+            if let Some(reason) = self.missing_analysis_reason(&track)? {
+                tracks.push((track, reason));
+            }
+        }
+        Ok(tracks)
+    }
+
+    /// Check whether the analysis (`.DAT`) file of the given track exists in the export, and
+    /// return the reason if it doesn't.
+    fn missing_analysis_reason(
+        &self,
+        track: &Track,
+    ) -> RekordcrateResult<Option<MissingAnalysisReason>> {
+        let relative_path = normalize_path(&track.analyze_path()?);
+        if relative_path.as_os_str().is_empty() {
+            Ok(Some(MissingAnalysisReason::NoPath))
+        } else if !self
+            .resolve(relative_path)
+            .is_some_and(|path| path.is_file())
+        {
+            Ok(Some(MissingAnalysisReason::FileMissing))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Check whether the audio file of the given track exists in the export.
+    ///
+    /// The file path is resolved relative to the [export root](Self::root). Returns `false` if the
+    /// file path can't be decoded.
+    #[must_use]
+    pub fn track_file_exists(&self, track: &Track) -> bool {
+        track
+            .normalized_file_path()
+            .ok()
+            .filter(|path| !path.as_os_str().is_empty())
+            .and_then(|path| self.resolve(path))
+            .is_some_and(|path| path.is_file())
+    }
+
+    /// Find all tracks whose audio file does not exist in the export (e.g. because it was moved
+    /// or deleted after the export was created).
+    ///
+    /// See [`DeviceExport::track_file_exists`] for details.
+    pub fn missing_files(&self) -> RekordcrateResult<Vec<Track>> {
+        let mut tracks = vec![];
+        for track in self.tracks() {
+            let track = track?;
+            if !self.track_file_exists(&track) {
+                tracks.push(track);
+            }
+        }
+        Ok(tracks)
+    }
+
+    /// Parse the analysis files (`.DAT`, `.EXT` and `.2EX`) of the track with the given ID.
+    ///
+    /// Returns `None` if the track does not exist, has no analysis file path or the export was
+    /// opened from memory.
+    ///
+    /// The parsed files are cached, so that repeated calls for the same track (or for tracks that
+    /// share the same analysis files) don't parse them again. Since the waveforms make up a few
+    /// hundred kilobytes per track, the cache can grow large when iterating over a whole
+    /// collection; use [`DeviceExport::clear_anlz_cache`] to free the memory.
+    pub fn anlz_for_track(&self, id: TrackId) -> RekordcrateResult<Option<Rc<TrackAnalysis>>> {
+        let Some(track) = self.track(id)? else {
+            return Ok(None);
+        };
+        let relative_path = normalize_path(&track.analyze_path()?);
+        if relative_path.as_os_str().is_empty() {
+            return Ok(None);
+        }
+        if let Some(analysis) = self.anlz_cache.borrow().get(&relative_path) {
+            return Ok(Some(Rc::clone(analysis)));
+        }
+
+        let Some(path) = self.resolve(&relative_path) else {
+            return Ok(None);
+        };
+        let analysis = Rc::new(ANLZ::load_all(path)?);
+        self.anlz_cache
+            .borrow_mut()
+            .insert(relative_path, Rc::clone(&analysis));
+        Ok(Some(analysis))
+    }
+
+    /// Absolute path of the artwork image of the given track (i.e. the artwork path joined to the
+    /// [export root](Self::root)).
+    ///
+    /// Returns `None` if the track has no artwork, the artwork row does not exist or the export
+    /// was opened from memory.
+    pub fn artwork_path(&self, track: &Track) -> RekordcrateResult<Option<PathBuf>> {
+        if track.artwork_id() == ArtworkId(0) {
+            return Ok(None);
+        }
+        for artwork in self.pdb.iter_present_rows::<Artwork>() {
+            let artwork = artwork?;
+            if artwork.id() == track.artwork_id() {
+                return Ok(self.resolve(normalize_path(&artwork.path()?)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Iterate over the tracks in the plain database together with their artwork.
+    ///
+    /// The artwork table is read up front, while the tracks are streamed like in
+    /// [`DeviceExport::tracks`]. The artwork is `None` for tracks without artwork (i.e. with an
+    /// artwork ID of zero) and for tracks that reference an artwork row that doesn't exist.
+    pub fn tracks_with_artwork(
+        &self,
+    ) -> RekordcrateResult<impl Iterator<Item = RekordcrateResult<(Track, Option<Artwork>)>> + '_>
+    {
+        let mut artworks = HashMap::new();
+        for artwork in self.pdb.iter_present_rows::<Artwork>() {
+            let artwork = artwork?;
+            artworks.insert(artwork.id(), artwork);
+        }
+        Ok(self.tracks().map(move |track| {
+            let track = track?;
+            let artwork = if track.artwork_id() == ArtworkId(0) {
+                None
+            } else {
+                artworks.get(&track.artwork_id()).cloned()
+            };
+            Ok((track, artwork))
+        }))
+    }
+
+    /// Check the files referenced by all tracks against the filesystem, i.e. the audio files,
+    /// analysis files and artwork images.
+    ///
+    /// This combines [`DeviceExport::missing_files`] and
+    /// [`DeviceExport::tracks_without_analysis`] with a check of the artwork images, but only
+    /// reads the database once. The artwork of a track is considered missing if the track
+    /// references an artwork row that doesn't exist or whose image file doesn't exist; tracks
+    /// without artwork are not reported. All paths are resolved relative to the
+    /// [export root](Self::root), so every file is missing if the export was opened from memory.
+    pub fn validate_against_filesystem(&self) -> RekordcrateResult<ValidationReport> {
+        let mut artwork_paths = HashMap::new();
+        for artwork in self.pdb.iter_present_rows::<Artwork>() {
+            let artwork = artwork?;
+            artwork_paths.insert(artwork.id(), normalize_path(&artwork.path()?));
+        }
+
+        let mut report = ValidationReport::default();
+        for track in self.tracks() {
+            let track = track?;
+            if !self.track_file_exists(&track) {
+                report.missing_media.push(track.clone());
+            }
+            if let Some(reason) = self.missing_analysis_reason(&track)? {
+                report.missing_analysis.push((track.clone(), reason));
+            }
+            if track.artwork_id() != ArtworkId(0)
+                && !artwork_paths
+                    .get(&track.artwork_id())
+                    .and_then(|path| self.resolve(path))
+                    .is_some_and(|path| path.is_file())
+            {
+                report.missing_artwork.push(track);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Remove all parsed analysis files from the cache used by [`DeviceExport::anlz_for_track`].
+    pub fn clear_anlz_cache(&self) {
+        self.anlz_cache.borrow_mut().clear();
+    }
+
+    /// Write the playlist with the given ID as extended M3U playlist to `writer`.
+    ///
+    /// Each track is written as `#EXTINF` line with the duration in seconds and `Artist - Title`
+    /// (or just the title, if the track has no artist), followed by the absolute path of the
+    /// audio file (i.e. the file path of the track joined to the [export root](Self::root)).
+    /// Playlist entries that reference tracks that don't exist in the database are skipped with a
+    /// warning. Fails if the playlist contains more than one entry with the same index (see
+    /// [`Database::playlist_entries`]).
+    pub fn write_playlist_m3u<W: Write>(
+        &self,
+        id: PlaylistTreeNodeId,
+        mut writer: W,
+    ) -> RekordcrateResult<()> {
+        let entries = self.pdb.playlist_entries(id)?;
+        let track_ids: HashSet<TrackId> = entries.iter().map(PlaylistEntry::track_id).collect();
+        let mut tracks = HashMap::new();
+        for row in self.pdb.iter_rows(PageType::Tracks) {
+            if let Row::Track(track) = row? {
+                if track_ids.contains(&track.id()) {
+                    tracks.insert(track.id(), track);
+                }
+            }
+        }
+        let mut artists = HashMap::new();
+        for row in self.pdb.iter_rows(PageType::Artists) {
+            if let Row::Artist(artist) = row? {
+                artists.insert(artist.id(), artist.name()?);
+            }
+        }
+
+        writeln!(writer, "#EXTM3U")?;
+        for entry in entries {
+            let Some(track) = tracks.get(&entry.track_id()) else {
+                log::warn!(
+                    "Skipping entry {} of playlist {}: track {} does not exist",
+                    entry.entry_index(),
+                    id.0,
+                    entry.track_id().0
+                );
+                continue;
+            };
+            let title = track.title()?;
+            let name = match artists.get(&track.artist_id()) {
+                Some(artist) => format!("{} - {}", artist, title),
+                None => title,
+            };
+            let file_path = track.normalized_file_path()?;
+            writeln!(writer, "#EXTINF:{},{}", track.duration(), name)?;
+            writeln!(writer, "{}", self.root().join(file_path).display())?;
+        }
+        Ok(())
+    }
+
+    /// Find the track with the given ID and combine it with its data from the extended database.
+    ///
+    /// The artist IDs of the track (artist, composer, remixer and original artist) are resolved
+    /// to names. Returns `None` if the track does not exist in the plain database.
+    pub fn merged_track(&self, id: TrackId) -> RekordcrateResult<Option<MergedTrack>> {
+        let Some(track) = self.track(id)? else {
+            return Ok(None);
+        };
+        let mut artists = self.artist_names([
+            track.artist_id(),
+            track.composer_id(),
+            track.remixer_id(),
+            track.original_artist_id(),
+        ])?;
+        let tags = match &self.ext_pdb {
+            Some(ext_pdb) => Self::track_tags(ext_pdb, id)?,
+            None => vec![],
+        };
+        Ok(Some(MergedTrack {
+            artist: artists.get(&track.artist_id()).cloned(),
+            composer: artists.get(&track.composer_id()).cloned(),
+            remixer: artists.get(&track.remixer_id()).cloned(),
+            original_artist: artists.remove(&track.original_artist_id()),
+            track,
+            tags,
+        }))
+    }
+
+    /// Look up the names of the artists with the given IDs in the plain database.
+    ///
+    /// Unset IDs (`ArtistId(0)`) and IDs that don't exist in the database are not included in
+    /// the result.
+    fn artist_names<const N: usize>(
+        &self,
+        ids: [ArtistId; N],
+    ) -> RekordcrateResult<HashMap<ArtistId, String>> {
+        let ids: HashSet<ArtistId> = ids.into_iter().filter(|id| id.0 != 0).collect();
+        let mut names = HashMap::new();
+        if ids.is_empty() {
+            return Ok(names);
+        }
+        for row in self.pdb.iter_rows(PageType::Artists) {
+            if let Row::Artist(artist) = row? {
+                if ids.contains(&artist.id()) {
+                    names.insert(artist.id(), artist.name()?);
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Returns the "My Tag" tags of the track with the given ID, together with the names of their
+    /// categories.
+    ///
+    /// The tags are ordered like in Rekordbox, i.e. by the position of their category and then by
+    /// their position inside of the category. "My Tags" are only stored in the extended database
+    /// (`exportExt.pdb`) of device exports; Rekordbox XML files don't contain them. Returns an
+    /// empty list if the export has no extended database or the track has no tags.
+    pub fn my_tags(&self, id: TrackId) -> RekordcrateResult<Vec<MyTag>> {
+        let Some(ext_pdb) = &self.ext_pdb else {
+            return Ok(vec![]);
+        };
+        let tags = Self::track_tags(ext_pdb, id)?;
+        if tags.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut categories = HashMap::new();
+        for row in ext_pdb.iter_rows(ExtPageType::Tags.into()) {
+            if let Row::Ext(ExtRow::Tag(tag)) = row? {
+                if tag.is_category() {
+                    categories.insert(tag.id(), tag);
+                }
+            }
+        }
+        Self::resolve_my_tags(tags, &categories)
+    }
+
+    /// Sort the given tags by category and position, and resolve the names of their categories.
+    fn resolve_my_tags(
+        mut tags: Vec<Tag>,
+        categories: &HashMap<TagId, Tag>,
+    ) -> RekordcrateResult<Vec<MyTag>> {
+        let category = |tag: &Tag| tag.category().and_then(|id| categories.get(&id));
+        tags.sort_by_key(|tag| {
+            (
+                category(tag).map_or(u32::MAX, Tag::category_position),
+                tag.category_position(),
+            )
+        });
+        let mut my_tags = vec![];
+        for tag in &tags {
+            my_tags.push(MyTag {
+                id: tag.id(),
+                name: tag.name()?,
+                category: category(tag).map(Tag::name).transpose()?,
+            });
+        }
+        Ok(my_tags)
+    }
+
+    /// Collect the tags of the track with the given ID from the extended database.
+    fn track_tags(ext_pdb: &Database, id: TrackId) -> RekordcrateResult<Vec<Tag>> {
+        let mut tag_ids = HashSet::new();
+        for row in ext_pdb.iter_rows(ExtPageType::TrackTags.into()) {
+            if let Row::Ext(ExtRow::TrackTag(track_tag)) = row? {
+                if track_tag.track_id() == id {
+                    tag_ids.insert(track_tag.tag_id());
+                }
+            }
+        }
+        if tag_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut tags = vec![];
+        for row in ext_pdb.iter_rows(ExtPageType::Tags.into()) {
+            if let Row::Ext(ExtRow::Tag(tag)) = row? {
+                if tag_ids.contains(&tag.id()) {
+                    tags.push(tag);
+                }
+            }
+        }
+        Ok(tags)
+    }
+}
+
+/// An item of a [`MultiDeviceExport`], along with the index of the export that it comes from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Sourced<T> {
+    /// Index of the export in [`MultiDeviceExport::exports`].
+    pub source: usize,
+    /// The item itself.
+    pub value: T,
+}
+
+/// A combined view of several device exports, e.g. of a laptop library and two USB drives.
+///
+/// Row IDs are only unique within a single export, so all items are returned as [`Sourced`]
+/// values and the pair of the source index and the row ID identifies an item across all exports.
+/// Items that exist in several exports (e.g. the same track on two USB drives) are not merged,
+/// but returned once for each export, in the order of the exports.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::device::MultiDeviceExport;
+///
+/// let exports = MultiDeviceExport::open([
+///     "data/complete_export/demo_tracks",
+///     "data/complete_export/demo_tracks",
+/// ])?;
+/// for track in exports.tracks() {
+///     let track = track?;
+///     println!("{} (export {})", track.value.title()?, track.source);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MultiDeviceExport {
+    /// The individual exports, in the order in which they were added.
+    exports: Vec<DeviceExport>,
+}
+
+impl MultiDeviceExport {
+    /// Combine the given exports. The index of an export in `exports` is used as its source
+    /// index.
+    #[must_use]
+    pub fn new(exports: Vec<DeviceExport>) -> Self {
+        Self { exports }
+    }
+
+    /// Open the device exports at the given roots (see [`DeviceExport::open`]).
+    pub fn open<P: AsRef<Path>>(roots: impl IntoIterator<Item = P>) -> RekordcrateResult<Self> {
+        let exports = roots
+            .into_iter()
+            .map(DeviceExport::open)
+            .collect::<RekordcrateResult<_>>()?;
+        Ok(Self::new(exports))
+    }
+
+    /// The individual exports, indexed by their source index.
+    #[must_use]
+    pub fn exports(&self) -> &[DeviceExport] {
+        &self.exports
+    }
+
+    /// Iterate over the tracks of all exports, in the order of the exports.
+    ///
+    /// Like [`DeviceExport::tracks`], the tracks are streamed from the databases.
+    pub fn tracks(&self) -> impl Iterator<Item = RekordcrateResult<Sourced<Track>>> + '_ {
+        self.exports
+            .iter()
+            .enumerate()
+            .flat_map(|(source, export)| {
+                export
+                    .tracks()
+                    .map(move |track| track.map(|value| Sourced { source, value }))
+            })
+    }
+
+    /// Find the track with the given ID in the export with the given source index.
+    ///
+    /// Returns `None` if there is no such export or if it does not contain the track.
+    pub fn track(&self, source: usize, id: TrackId) -> RekordcrateResult<Option<Track>> {
+        match self.exports.get(source) {
+            Some(export) => export.track(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Iterate over the playlists (excluding folders) of all exports, in the order of the
+    /// exports.
+    ///
+    /// Use [`Database::playlist_full_path`] and [`Database::playlist_entries`] of the
+    /// [source export](Self::exports) to get the path and tracks of a playlist.
+    pub fn playlists(
+        &self,
+    ) -> impl Iterator<Item = RekordcrateResult<Sourced<PlaylistTreeNode>>> + '_ {
+        self.exports
+            .iter()
+            .enumerate()
+            .flat_map(|(source, export)| {
+                export
+                    .pdb
+                    .iter_present_rows::<PlaylistTreeNode>()
+                    .map(move |node| node.map(|value| Sourced { source, value }))
+            })
+            .filter(|node| !matches!(node, Ok(node) if node.value.is_folder()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::setting::SettingData;
+
+    const DEMO_TRACKS: &str = "data/complete_export/demo_tracks";
+    const DEMO_TRACKS_PDB: &str = "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb";
+    const NUM_ROWS_PDB: &str = "data/pdb/num_rows/export.pdb";
+
+    /// Copy of a device export in a temporary directory, which is deleted when dropped (even if
+    /// an assertion fails).
+    struct TempExport(PathBuf);
+
+    impl TempExport {
+        /// Copy the database at `pdb_path` and the given files of the demo export into a
+        /// temporary directory.
+        fn new(name: &str, pdb_path: &str, files: &[&str]) -> Self {
+            let root =
+                std::env::temp_dir().join(format!("rekordcrate-{name}-{}", std::process::id()));
+            let export = Self(root);
+            let target = export.join(DeviceExport::PDB_PATH);
+            std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+            std::fs::copy(pdb_path, target).unwrap();
+            for path in files {
+                let target = export.join(path);
+                std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+                std::fs::copy(Path::new(DEMO_TRACKS).join(path), target).unwrap();
+            }
+            export
+        }
+    }
+
+    impl std::ops::Deref for TempExport {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempExport {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempExport {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Reader that counts the number of bytes read from it.
+    struct CountingReader {
+        inner: std::io::Cursor<Vec<u8>>,
+        bytes_read: Rc<std::cell::Cell<usize>>,
+    }
+
+    impl std::io::Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.inner.read(buf)?;
+            self.bytes_read.set(self.bytes_read.get() + len);
+            Ok(len)
+        }
+    }
+
+    impl std::io::Seek for CountingReader {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn tracks_are_streamed() {
+        let data = std::fs::read("data/pdb/num_rows/export.pdb").unwrap();
+        let file_size = data.len();
+        let bytes_read = Rc::new(std::cell::Cell::new(0));
+        let pdb = Database::open_non_persistent(CountingReader {
+            inner: std::io::Cursor::new(data),
+            bytes_read: Rc::clone(&bytes_read),
+        })
+        .unwrap();
+        let page_size = pdb.header().page_size as usize;
+        let export = DeviceExport {
+            root: None,
+            pdb,
+            ext_pdb: None,
+            anlz_cache: RefCell::default(),
+            settings: None,
+        };
+
+        let first_track = export.tracks().next().unwrap().unwrap();
+        assert_eq!(export.track(first_track.id()).unwrap(), Some(first_track));
+        // Only the header and the first few pages of the tracks table have been read.
+        let bytes_read_for_first_track = bytes_read.get();
+        assert!(bytes_read_for_first_track <= 4 * page_size);
+
+        let num_tracks = export.tracks().map(Result::unwrap).count();
+        assert!(num_tracks > 1000);
+        assert!(bytes_read.get() - bytes_read_for_first_track > file_size / 2);
+    }
+
+    #[test]
+    fn detect_format() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        assert_eq!(export.detect_format(), ExportFormat::Rekordbox6);
+
+        let root = TempExport::new("detect-format-rb5", NUM_ROWS_PDB, &[]);
+        let export = DeviceExport::open(&root).unwrap();
+        assert_eq!(export.detect_format(), ExportFormat::Rekordbox5);
+
+        let root = TempExport::new("detect-format-unknown", NUM_ROWS_PDB, &[]);
+        let mut data = vec![];
+        Database::new_empty(8192).unwrap().write(&mut data).unwrap();
+        std::fs::write(root.join(DeviceExport::PDB_PATH), data).unwrap();
+        let export = DeviceExport::open(&root).unwrap();
+        assert_eq!(
+            export.detect_format(),
+            ExportFormat::Unknown(FormatSignals {
+                page_size: 8192,
+                num_tables: 20,
+                has_standard_tables: true,
+                ext_num_tables: None,
+            })
+        );
+    }
+
+    #[test]
+    fn open_demo_tracks() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        assert!(export.ext_pdb().is_some());
+
+        let tags: Vec<Tag> = export
+            .ext_pdb()
+            .unwrap()
+            .iter_rows(ExtPageType::Tags.into())
+            .filter_map(|row| match row.unwrap() {
+                Row::Ext(ExtRow::Tag(tag)) => Some(tag),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tags.len(), 28);
+        let genre = tags.iter().find(|tag| tag.id() == TagId(1)).unwrap();
+        assert!(genre.is_category());
+        assert_eq!(genre.name().unwrap(), "Genre");
+        assert!(tags
+            .iter()
+            .any(|tag| tag.category() == Some(TagId(1)) && tag.name().unwrap() == "Acid House"));
+    }
+
+    #[test]
+    fn export_summary() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        let summary = export.export_summary().unwrap();
+        assert_eq!(
+            summary.track_count,
+            export.pdb().row_count(PageType::Tracks).unwrap()
+        );
+        assert!(summary.track_count > 0);
+        // The demo export has an empty playlist tree.
+        assert_eq!(summary.playlist_count, 0);
+        assert!(summary.has_ext_pdb);
+        assert!(summary.has_settings);
+    }
+
+    #[test]
+    fn tracks_without_analysis() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        assert_eq!(export.tracks_without_analysis().unwrap(), vec![]);
+
+        // Copy the database and only the analysis file of track 1, so that the analysis path of
+        // track 2 points to a nonexistent file.
+        let root = TempExport::new(
+            "tracks-without-analysis",
+            DEMO_TRACKS_PDB,
+            &["PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT"],
+        );
+        let export = DeviceExport::open(&root).unwrap();
+        let missing: Vec<(TrackId, MissingAnalysisReason)> = export
+            .tracks_without_analysis()
+            .unwrap()
+            .into_iter()
+            .map(|(track, reason)| (track.id(), reason))
+            .collect();
+        assert_eq!(
+            missing,
+            vec![(TrackId(2), MissingAnalysisReason::FileMissing)]
+        );
+    }
+
+    #[test]
+    fn missing_files() {
+        // The audio files are not part of the test data, so all tracks are missing.
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        let mut missing: Vec<u32> = export
+            .missing_files()
+            .unwrap()
+            .iter()
+            .map(|track| track.id().0)
+            .collect();
+        missing.sort_unstable();
+        assert_eq!(missing, vec![1, 2]);
+
+        // Create a (fake) audio file for track 1 only.
+        let root = TempExport::new("missing-files", DEMO_TRACKS_PDB, &[]);
+        let audio_path = root.join("Contents/Loopmasters/UnknownAlbum/Demo Track 1.mp3");
+        std::fs::create_dir_all(audio_path.parent().unwrap()).unwrap();
+        std::fs::write(&audio_path, b"").unwrap();
+        let export = DeviceExport::open(&root).unwrap();
+        let track = export.track(TrackId(1)).unwrap().unwrap();
+        assert!(export.track_file_exists(&track));
+        let missing: Vec<TrackId> = export
+            .missing_files()
+            .unwrap()
+            .iter()
+            .map(Track::id)
+            .collect();
+        assert_eq!(missing, vec![TrackId(2)]);
+    }
+
+    #[test]
+    fn tracks_with_artwork() {
+        let data = std::fs::read("data/pdb/num_rows/export.pdb").unwrap();
+        let export = DeviceExport::from_pdb_bytes(&data).unwrap();
+        let tracks: Vec<(Track, Option<Artwork>)> = export
+            .tracks_with_artwork()
+            .unwrap()
+            .collect::<RekordcrateResult<_>>()
+            .unwrap();
+        assert_eq!(
+            tracks.len(),
+            export.pdb().row_count(PageType::Tracks).unwrap()
+        );
+        assert!(tracks.iter().any(|(_, artwork)| artwork.is_some()));
+        assert!(tracks.iter().any(|(_, artwork)| artwork.is_none()));
+        for (track, artwork) in tracks {
+            match artwork {
+                Some(artwork) => assert_eq!(artwork.id(), track.artwork_id()),
+                None => assert_eq!(track.artwork_id(), ArtworkId(0)),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_against_filesystem() {
+        let root = TempExport::new("validate-against-filesystem", NUM_ROWS_PDB, &[]);
+        let export = DeviceExport::open(&root).unwrap();
+        let mut artwork_paths = HashMap::new();
+        for artwork in export.pdb().iter_present_rows::<Artwork>() {
+            let artwork = artwork.unwrap();
+            artwork_paths.insert(artwork.id(), artwork.path().unwrap());
+        }
+
+        // Pick four tracks with analysis files and artwork that don't share any files, and create
+        // (empty) files for all of them except for one file of each kind.
+        let mut tracks: Vec<Track> = vec![];
+        for track in export.tracks() {
+            let track = track.unwrap();
+            let shares_files = |other: &Track| {
+                other.analyze_path().unwrap() == track.analyze_path().unwrap()
+                    || other.artwork_id() == track.artwork_id()
+                    || other.file_path().unwrap() == track.file_path().unwrap()
+            };
+            if !track.analyze_path().unwrap().is_empty()
+                && artwork_paths.contains_key(&track.artwork_id())
+                && !tracks.iter().any(shares_files)
+            {
+                tracks.push(track);
+            }
+            if tracks.len() == 4 {
+                break;
+            }
+        }
+        assert_eq!(tracks.len(), 4);
+        for (index, track) in tracks.iter().enumerate() {
+            let paths = [
+                track.file_path().unwrap(),
+                track.analyze_path().unwrap(),
+                artwork_paths[&track.artwork_id()].clone(),
+            ];
+            for (kind, path) in paths.iter().enumerate() {
+                if index == kind + 1 {
+                    continue;
+                }
+                let path = root.join(normalize_path(path));
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, b"").unwrap();
+            }
+        }
+
+        let report = export.validate_against_filesystem().unwrap();
+        assert!(!report.is_ok());
+        let contains =
+            |tracks: &[Track], track: &Track| tracks.iter().any(|t| t.id() == track.id());
+        let missing_analysis: Vec<Track> = report
+            .missing_analysis
+            .iter()
+            .map(|(track, reason)| {
+                assert_eq!(*reason, MissingAnalysisReason::FileMissing);
+                track.clone()
+            })
+            .collect();
+        for (index, track) in tracks.iter().enumerate() {
+            assert_eq!(contains(&report.missing_media, track), index == 1);
+            assert_eq!(contains(&missing_analysis, track), index == 2);
+            assert_eq!(contains(&report.missing_artwork, track), index == 3);
+        }
+    }
+
+    #[test]
+    fn get_all_settings() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        let settings = export.get_all_settings().unwrap();
+        assert_eq!(settings.len(), 4);
+        assert!(matches!(
+            settings[&SettingType::DevSetting].data,
+            SettingData::DevSetting(_)
+        ));
+        assert!(matches!(
+            settings[&SettingType::DJMMySetting].data,
+            SettingData::DJMMySetting(_)
+        ));
+        assert!(matches!(
+            settings[&SettingType::MySetting].data,
+            SettingData::MySetting(_)
+        ));
+        assert!(matches!(
+            settings[&SettingType::MySetting2].data,
+            SettingData::MySetting2(_)
+        ));
+
+        let root = TempExport::new(
+            "get-all-settings",
+            DEMO_TRACKS_PDB,
+            &["PIONEER/MYSETTING.DAT"],
+        );
+        let settings = DeviceExport::open(&root)
+            .unwrap()
+            .get_all_settings()
+            .unwrap();
+        assert_eq!(
+            settings.keys().collect::<Vec<_>>(),
+            vec![&SettingType::MySetting]
+        );
+    }
+
+    #[test]
+    fn load() {
+        let export = DeviceExport::load(DEMO_TRACKS).unwrap();
+        assert!(export.ext_pdb().is_some());
+        assert_eq!(
+            export.get_all_settings().unwrap(),
+            DeviceExport::open(DEMO_TRACKS)
+                .unwrap()
+                .get_all_settings()
+                .unwrap()
+        );
+
+        // Missing settings files are skipped, but broken ones fail the whole export.
+        let root = TempExport::new("load", DEMO_TRACKS_PDB, &["PIONEER/MYSETTING.DAT"]);
+        assert_eq!(
+            DeviceExport::load(&root)
+                .unwrap()
+                .get_all_settings()
+                .unwrap()
+                .len(),
+            1
+        );
+        std::fs::write(root.join("PIONEER/MYSETTING.DAT"), b"broken").unwrap();
+        let opened = DeviceExport::open(&root).map(|export| export.get_all_settings());
+        let loaded = DeviceExport::load(&root);
+        assert!(opened.unwrap().is_err());
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn capabilities() {
+        // Whether the checked-in demo export is writable depends on the checkout, so `can_write`
+        // is only checked on the temporary copy below.
+        let demo = DeviceExport::open(DEMO_TRACKS).unwrap().capabilities();
+        assert!(demo.has_filesystem);
+        assert!(demo.has_ext_db);
+        let data = std::fs::read("data/pdb/num_rows/export.pdb").unwrap();
+        assert_eq!(
+            DeviceExport::from_pdb_bytes(&data).unwrap().capabilities(),
+            Capabilities {
+                can_write: false,
+                has_filesystem: false,
+                has_ext_db: false,
+            }
+        );
+
+        let root = TempExport::new("capabilities", DEMO_TRACKS_PDB, &[]);
+        let export = DeviceExport::load(&root).unwrap();
+        let writable = export.capabilities();
+        let pdb_path = root.join(DeviceExport::PDB_PATH);
+        let mut permissions = std::fs::metadata(&pdb_path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&pdb_path, permissions).unwrap();
+        let read_only = export.capabilities();
+        assert_eq!(
+            writable,
+            Capabilities {
+                can_write: true,
+                has_filesystem: true,
+                has_ext_db: false,
+            }
+        );
+        assert_eq!(
+            read_only,
+            Capabilities {
+                can_write: false,
+                ..writable
+            }
+        );
+    }
+
+    #[test]
+    fn save() {
+        let root = TempExport::new("save", DEMO_TRACKS_PDB, &[]);
+        let mut export = DeviceExport::open(&root).unwrap();
+        assert!(export
+            .pdb_mut()
+            .set_track_title(TrackId(1), "Saved")
+            .unwrap());
+        export.save().unwrap();
+        let reopened = DeviceExport::open(&root).unwrap();
+        let track = reopened.track(TrackId(1)).unwrap().unwrap();
+        assert_eq!(track.title().unwrap(), "Saved");
+
+        let data = std::fs::read(DEMO_TRACKS_PDB).unwrap();
+        assert!(DeviceExport::from_pdb_bytes(&data).unwrap().save().is_err());
+    }
+
+    #[test]
+    fn anlz_for_track() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        let analysis = export.anlz_for_track(TrackId(1)).unwrap().unwrap();
+        assert!(analysis.beat_grid().is_some());
+        assert!(analysis.ext.is_some());
+        let cached = export.anlz_for_track(TrackId(1)).unwrap().unwrap();
+        assert!(Rc::ptr_eq(&analysis, &cached));
+
+        let other = export.anlz_for_track(TrackId(2)).unwrap().unwrap();
+        assert!(!Rc::ptr_eq(&analysis, &other));
+        assert_ne!(analysis.path(), other.path());
+
+        export.clear_anlz_cache();
+        let reloaded = export.anlz_for_track(TrackId(1)).unwrap().unwrap();
+        assert!(!Rc::ptr_eq(&analysis, &reloaded));
+        assert_eq!(analysis, reloaded);
+
+        assert!(export.anlz_for_track(TrackId(12345)).unwrap().is_none());
+    }
+
+    #[test]
+    fn windows_path_separators() {
+        let root = TempExport::new(
+            "windows-path-separators",
+            DEMO_TRACKS_PDB,
+            &[
+                "PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT",
+                "PIONEER/USBANLZ/P016/0000875E/ANLZ0000.EXT",
+            ],
+        );
+        // Replace the forward slashes in the paths of track 1 by backslashes. Since both are
+        // single ASCII characters, this doesn't change the layout of the database.
+        let pdb_path = root.join(DeviceExport::PDB_PATH);
+        let mut data = std::fs::read(&pdb_path).unwrap();
+        for path in [
+            "/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT",
+            "/Contents/Loopmasters/UnknownAlbum/Demo Track 1.mp3",
+        ] {
+            let windows_path = path.replace('/', "\\");
+            let mut offset = 0;
+            while let Some(pos) = data[offset..]
+                .windows(path.len())
+                .position(|window| window == path.as_bytes())
+            {
+                let start = offset + pos;
+                data[start..start + path.len()].copy_from_slice(windows_path.as_bytes());
+                offset = start + path.len();
+            }
+        }
+        std::fs::write(&pdb_path, data).unwrap();
+
+        let export = DeviceExport::open(&root).unwrap();
+        let track = export.track(TrackId(1)).unwrap().unwrap();
+        let analysis = export.anlz_for_track(TrackId(1));
+        let missing = export.tracks_without_analysis();
+
+        assert_eq!(
+            track.file_path().unwrap(),
+            "\\Contents\\Loopmasters\\UnknownAlbum\\Demo Track 1.mp3"
+        );
+        assert_eq!(
+            track.normalized_file_path().unwrap(),
+            [
+                "Contents",
+                "Loopmasters",
+                "UnknownAlbum",
+                "Demo Track 1.mp3"
+            ]
+            .iter()
+            .collect::<PathBuf>()
+        );
+        assert!(analysis.unwrap().unwrap().ext.is_some());
+        let missing: Vec<(TrackId, MissingAnalysisReason)> = missing
+            .unwrap()
+            .into_iter()
+            .map(|(track, reason)| (track.id(), reason))
+            .collect();
+        assert_eq!(
+            missing,
+            vec![(TrackId(2), MissingAnalysisReason::FileMissing)]
+        );
+        assert_eq!(export.artwork_path(&track).unwrap(), None);
+    }
+
+    #[test]
+    fn write_playlist_m3u() {
+        let root = TempExport::new("write-playlist-m3u", NUM_ROWS_PDB, &[]);
+        let export = DeviceExport::open(&root).unwrap();
+
+        let mut output = Vec::new();
+        export
+            .write_playlist_m3u(PlaylistTreeNodeId(93), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("#EXTM3U"));
+        let lines: Vec<&str> = lines.collect();
+        assert_eq!(lines.len(), 6);
+        for pair in lines.chunks(2) {
+            let (duration, name) = pair[0]
+                .strip_prefix("#EXTINF:")
+                .unwrap()
+                .split_once(',')
+                .unwrap();
+            assert!(duration.parse::<u16>().unwrap() > 0);
+            assert!(!name.is_empty());
+            assert!(Path::new(pair[1]).starts_with(&root));
+        }
+
+        let track = export.track(TrackId(3447)).unwrap().unwrap();
+        let expected_path = root.join(track.file_path().unwrap().trim_start_matches('/'));
+        assert_eq!(lines[1], expected_path.to_str().unwrap());
+    }
+
+    #[test]
+    fn multi_device_export() {
+        let num_rows = std::fs::read("data/pdb/num_rows/export.pdb").unwrap();
+        let exports = MultiDeviceExport::new(vec![
+            DeviceExport::open(DEMO_TRACKS).unwrap(),
+            DeviceExport::open(DEMO_TRACKS).unwrap(),
+            DeviceExport::from_pdb_bytes(&num_rows).unwrap(),
+        ]);
+        assert_eq!(exports.exports().len(), 3);
+
+        // The demo tracks exist in both demo exports with the same IDs, so they are
+        // distinguished by their source index.
+        let mut demo_tracks: Vec<(usize, TrackId)> = exports
+            .tracks()
+            .map(Result::unwrap)
+            .filter(|track| track.source < 2)
+            .map(|track| (track.source, track.value.id()))
+            .collect();
+        demo_tracks.sort_unstable_by_key(|(source, id)| (*source, id.0));
+        assert_eq!(
+            demo_tracks,
+            vec![
+                (0, TrackId(1)),
+                (0, TrackId(2)),
+                (1, TrackId(1)),
+                (1, TrackId(2)),
+            ]
+        );
+        let num_rows_tracks = exports.exports()[2].tracks().count();
+        assert_eq!(
+            exports
+                .tracks()
+                .filter(|track| track.as_ref().unwrap().source == 2)
+                .count(),
+            num_rows_tracks
+        );
+        assert_eq!(
+            exports.track(1, TrackId(2)).unwrap(),
+            exports.exports()[1].track(TrackId(2)).unwrap()
+        );
+        assert_eq!(exports.track(3, TrackId(1)).unwrap(), None);
+
+        // The demo exports don't contain any playlists.
+        let playlists: Vec<Sourced<PlaylistTreeNode>> =
+            exports.playlists().map(Result::unwrap).collect();
+        assert!(!playlists.is_empty());
+        assert!(playlists.iter().all(|playlist| playlist.source == 2));
+        assert!(playlists.iter().all(|playlist| !playlist.value.is_folder()));
+        assert_eq!(
+            playlists.len(),
+            exports.exports()[2]
+                .export_summary()
+                .unwrap()
+                .playlist_count
+        );
+    }
+
+    #[test]
+    fn from_pdb_bytes() {
+        let data = std::fs::read(Path::new(DEMO_TRACKS).join(DeviceExport::PDB_PATH)).unwrap();
+        let export = DeviceExport::from_pdb_bytes(&data).unwrap();
+        assert_eq!(export.root(), Path::new(""));
+        assert!(export.ext_pdb().is_none());
+        let track = export.track(TrackId(1)).unwrap().unwrap();
+        assert_eq!(track.title().unwrap(), "Demo Track 1");
+        assert_eq!(
+            export.merged_track(TrackId(1)).unwrap().unwrap().artist,
+            Some("Loopmasters".to_string())
+        );
+
+        // Features that need other files of the export are unavailable.
+        assert!(export.anlz_for_track(TrackId(1)).unwrap().is_none());
+        assert!(!export.track_file_exists(&track));
+        assert!(export.get_all_settings().unwrap().is_empty());
+        assert_eq!(export.tracks_without_analysis().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn my_tags() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        // The demo export does not assign any tags to tracks.
+        assert_eq!(export.my_tags(TrackId(1)).unwrap(), vec![]);
+
+        let mut tags = HashMap::new();
+        let mut categories = HashMap::new();
+        for row in export
+            .ext_pdb()
+            .unwrap()
+            .iter_rows(ExtPageType::Tags.into())
+        {
+            if let Row::Ext(ExtRow::Tag(tag)) = row.unwrap() {
+                if tag.is_category() {
+                    categories.insert(tag.id(), tag);
+                } else {
+                    tags.insert(tag.name().unwrap(), tag);
+                }
+            }
+        }
+        let track_tags = ["Peak Time", "Synth", "Techno", "Acid House"]
+            .map(|name| tags[name].clone())
+            .to_vec();
+        let my_tags: Vec<(Option<String>, String)> =
+            DeviceExport::resolve_my_tags(track_tags, &categories)
+                .unwrap()
+                .into_iter()
+                .map(|tag| (tag.category, tag.name))
+                .collect();
+        let tag = |category: &str, name: &str| (Some(category.to_string()), name.to_string());
+        assert_eq!(
+            my_tags,
+            vec![
+                tag("Genre", "Acid House"),
+                tag("Genre", "Techno"),
+                tag("Components", "Synth"),
+                tag("Situation", "Peak Time"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merged_track() {
+        let export = DeviceExport::open(DEMO_TRACKS).unwrap();
+        let merged = export.merged_track(TrackId(1)).unwrap().unwrap();
+        assert_eq!(merged.track.id(), TrackId(1));
+        assert_eq!(Some(merged.track), export.track(TrackId(1)).unwrap());
+        assert_eq!(merged.artist.as_deref(), Some("Loopmasters"));
+        // The demo tracks have neither composer, remixer nor original artist.
+        assert_eq!(merged.composer, None);
+        assert_eq!(merged.remixer, None);
+        assert_eq!(merged.original_artist, None);
+        // The demo export does not assign any tags to tracks.
+        assert!(merged.tags.is_empty());
+        assert_eq!(export.merged_track(TrackId(12345)).unwrap(), None);
+    }
+}
@@ -24,7 +24,9 @@
 #![cfg_attr(not(debug_assertions), deny(clippy::used_underscore_binding))]
 
 pub mod anlz;
+pub mod device;
 pub mod pdb;
+pub mod prelude;
 pub mod setting;
 pub mod util;
 pub mod xml;
@@ -7,12 +7,22 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use binrw::BinRead;
-use clap::{Parser, Subcommand};
-use rekordcrate::anlz::ANLZ;
-use rekordcrate::pdb::{Header, PageType, Row};
+use clap::{Parser, Subcommand, ValueEnum};
+use rekordcrate::anlz::{CueListType, CuePoint, TrackAnalysis, ANLZ};
+use rekordcrate::device::DeviceExport;
+use rekordcrate::pdb::{
+    io::Database, Artist, DatabaseType, ExtPageType, Genre, PageType, PlaylistEntry,
+    PlaylistTreeNode, PlaylistTreeNodeId, Row, Track, TrackId,
+};
 use rekordcrate::setting::Setting;
+use rekordcrate::util::ColorIndex;
 use rekordcrate::xml::Document;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(author, version, about)]
@@ -20,6 +30,51 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Show more log messages (can be repeated, e.g. `-vv`).
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Only show errors.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Pretty-print JSON output (default if the output is a terminal).
+    #[arg(long, global = true)]
+    pretty: bool,
+    /// Print JSON output on a single line (default if the output is not a terminal).
+    #[arg(long, global = true, conflicts_with = "pretty")]
+    compact: bool,
+}
+
+impl Cli {
+    /// The log level selected by the `--verbose` and `--quiet` flags.
+    fn log_level(&self) -> log::LevelFilter {
+        if self.quiet {
+            return log::LevelFilter::Error;
+        }
+        match self.verbose {
+            0 => log::LevelFilter::Warn,
+            1 => log::LevelFilter::Info,
+            2 => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        }
+    }
+
+    /// Whether JSON output should be pretty-printed, as selected by the `--pretty` and
+    /// `--compact` flags.
+    fn pretty_json(&self) -> bool {
+        if self.pretty || self.compact {
+            return self.pretty;
+        }
+        std::io::stdout().is_terminal()
+    }
+}
+
+/// Output format of commands that support machine-readable output.
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// JSON.
+    Json,
 }
 
 #[derive(Subcommand)]
@@ -36,6 +91,19 @@ enum Commands {
         #[arg(value_name = "ANLZ_FILE")]
         path: PathBuf,
     },
+    /// Print the hot cues and memory cues of a track as JSON.
+    ///
+    /// The `.EXT` file next to the given `.DAT` file is read as well (if present), because only
+    /// the extended cue lists contain colors and comments.
+    ExtractAnlzCues {
+        /// Analysis file to parse, or the root directory of a device export if `--track-id` is
+        /// given.
+        #[arg(value_name = "PATH")]
+        path: PathBuf,
+        /// Look up the analysis files of the track with this ID in the device export at `PATH`.
+        #[arg(long)]
+        track_id: Option<u32>,
+    },
     /// Parse and dump a Pioneer Database (`.PDB`) file.
     DumpPDB {
         /// File to parse.
@@ -54,14 +122,79 @@ enum Commands {
         #[arg(value_name = "XML_FILE")]
         path: PathBuf,
     },
+    /// Summarize the collection in a Pioneer Database (`.PDB`) file: the number of rows per
+    /// table, the number of tracks per genre and tempo range, and the artists with the most
+    /// tracks.
+    Stats {
+        /// File to parse.
+        #[arg(value_name = "PDB_FILE")]
+        path: PathBuf,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Compare two Pioneer Database (`.PDB`) files and show added, removed and changed tracks and
+    /// playlists.
+    Diff {
+        /// Old version of the database.
+        #[arg(value_name = "PDB_A")]
+        path_a: PathBuf,
+        /// New version of the database.
+        #[arg(value_name = "PDB_B")]
+        path_b: PathBuf,
+        /// List the individual tracks and playlists instead of only showing a summary.
+        #[arg(long)]
+        detailed: bool,
+    },
 }
 
-fn list_playlists(path: &PathBuf) -> rekordcrate::Result<()> {
-    use rekordcrate::pdb::{PlaylistTreeNode, PlaylistTreeNodeId};
-    use std::collections::HashMap;
+/// IDs of the rows that differ between two databases.
+struct Changes<K> {
+    added: Vec<K>,
+    removed: Vec<K>,
+    changed: Vec<K>,
+}
+
+impl<K: Copy + Eq + Hash> Changes<K> {
+    /// Compare the rows of two databases by ID, using `equals` to detect changed rows.
+    ///
+    /// The IDs are sorted using `sort_key`.
+    fn new<V>(
+        a: &HashMap<K, V>,
+        b: &HashMap<K, V>,
+        equals: impl Fn(&V, &V) -> bool,
+        sort_key: impl Fn(&K) -> u32,
+    ) -> Self {
+        let mut changes = Self {
+            added: b.keys().filter(|id| !a.contains_key(id)).copied().collect(),
+            removed: a.keys().filter(|id| !b.contains_key(id)).copied().collect(),
+            changed: a
+                .iter()
+                .filter(|(id, row)| b.get(id).is_some_and(|other| !equals(row, other)))
+                .map(|(id, _)| *id)
+                .collect(),
+        };
+        changes.added.sort_by_key(&sort_key);
+        changes.removed.sort_by_key(&sort_key);
+        changes.changed.sort_by_key(&sort_key);
+        changes
+    }
 
+    /// Print the number of added, removed and changed rows.
+    fn print_summary(&self, name: &str) {
+        println!(
+            "{}: {} added, {} removed, {} changed",
+            name,
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+    }
+}
+
+fn list_playlists(path: &PathBuf) -> rekordcrate::Result<()> {
     fn print_children_of(
-        tree: &HashMap<PlaylistTreeNodeId, Vec<PlaylistTreeNode>>,
+        tree: &HashMap<PlaylistTreeNodeId, Vec<&PlaylistTreeNode>>,
         id: PlaylistTreeNodeId,
         level: usize,
     ) {
@@ -79,40 +212,22 @@ fn list_playlists(path: &PathBuf) -> rekordcrate::Result<()> {
             });
     }
 
-    let mut reader = std::fs::File::open(path)?;
-    let header = Header::read(&mut reader)?;
-
-    let mut tree: HashMap<PlaylistTreeNodeId, Vec<PlaylistTreeNode>> = HashMap::new();
+    let db = Database::open(path)?;
+    let table = db.load_table(PageType::PlaylistTree)?;
 
-    header
-        .tables
-        .iter()
-        .filter(|table| table.page_type == PageType::PlaylistTree)
-        .flat_map(|table| {
-            header
-                .read_pages(
-                    &mut reader,
-                    binrw::Endian::NATIVE,
-                    (&table.first_page, &table.last_page),
-                )
-                .unwrap()
-                .into_iter()
-                .flat_map(|page| page.row_groups.into_iter())
-                .flat_map(|row_group| {
-                    row_group
-                        .present_rows()
-                        .map(|row| {
-                            if let Row::PlaylistTreeNode(playlist_tree) = row {
-                                playlist_tree
-                            } else {
-                                unreachable!("encountered non-playlist tree row in playlist table");
-                            }
-                        })
-                        .collect::<Vec<PlaylistTreeNode>>()
-                        .into_iter()
-                })
+    let mut tree: HashMap<PlaylistTreeNodeId, Vec<&PlaylistTreeNode>> = HashMap::new();
+    table
+        .iter_rows_ref()
+        .map(|row| {
+            if let Row::PlaylistTreeNode(playlist_tree) = row {
+                playlist_tree
+            } else {
+                unreachable!("encountered non-playlist tree row in playlist table");
+            }
         })
         .for_each(|row| tree.entry(row.parent_id).or_default().push(row));
+    tree.values_mut()
+        .for_each(|nodes| nodes.sort_by_key(|node| node.sort_order()));
 
     print_children_of(&tree, PlaylistTreeNodeId(0), 0);
 
@@ -127,23 +242,112 @@ fn dump_anlz(path: &PathBuf) -> rekordcrate::Result<()> {
     Ok(())
 }
 
+/// Print a value as pretty-printed JSON, or on a single line if `pretty` is `false`.
+fn print_json<T: Serialize>(value: &T, pretty: bool) -> rekordcrate::Result<()> {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+    .map_err(std::io::Error::from)?;
+    println!("{json}");
+    Ok(())
+}
+
+fn extract_anlz_cues(
+    path: &PathBuf,
+    track_id: Option<u32>,
+    pretty: bool,
+) -> rekordcrate::Result<()> {
+    /// JSON representation of a cue.
+    #[derive(Serialize)]
+    struct CueJson {
+        #[serde(rename = "type")]
+        cue_type: &'static str,
+        hot_cue: u32,
+        time_ms: u32,
+        beat: Option<f64>,
+        loop_end_ms: Option<u32>,
+        loop_end_beat: Option<f64>,
+        color: Option<&'static str>,
+        hot_cue_color: Option<String>,
+        comment: String,
+    }
+
+    fn color_name(color: &ColorIndex) -> Option<&'static str> {
+        match color {
+            ColorIndex::None => None,
+            ColorIndex::Pink => Some("pink"),
+            ColorIndex::Red => Some("red"),
+            ColorIndex::Orange => Some("orange"),
+            ColorIndex::Yellow => Some("yellow"),
+            ColorIndex::Green => Some("green"),
+            ColorIndex::Aqua => Some("aqua"),
+            ColorIndex::Blue => Some("blue"),
+            ColorIndex::Purple => Some("purple"),
+        }
+    }
+
+    fn cue_to_json(cue: &CuePoint, analysis: &TrackAnalysis) -> CueJson {
+        // Beat positions are rounded to three decimal places (i.e. 1/1000 of a beat).
+        let beat_position = |time_ms| {
+            analysis
+                .beat_grid()
+                .and_then(|beat_grid| beat_grid.beat_position(time_ms))
+                .map(|beat| (beat * 1000.0).round() / 1000.0)
+        };
+        CueJson {
+            cue_type: match cue.list_type() {
+                CueListType::HotCues => "hot",
+                CueListType::MemoryCues => "memory",
+            },
+            hot_cue: cue.hot_cue(),
+            time_ms: cue.time_ms(),
+            beat: beat_position(cue.time_ms()),
+            loop_end_ms: cue.loop_end_ms(),
+            loop_end_beat: cue.loop_end_ms().and_then(beat_position),
+            color: cue.color().as_ref().and_then(color_name),
+            hot_cue_color: cue
+                .hot_cue_color_rgb()
+                .map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}")),
+            comment: cue.comment().unwrap_or_default().to_string(),
+        }
+    }
+
+    let analysis = match track_id {
+        Some(id) => DeviceExport::open(path)?
+            .anlz_for_track(TrackId(id))?
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("track {id} not found"),
+                )
+            })?,
+        None => std::rc::Rc::new(ANLZ::load_all(path)?),
+    };
+
+    let cues: Vec<CueJson> = analysis
+        .cues()
+        .iter()
+        .map(|cue| cue_to_json(cue, &analysis))
+        .collect();
+    print_json(&cues, pretty)
+}
+
 fn dump_pdb(path: &PathBuf) -> rekordcrate::Result<()> {
-    let mut reader = std::fs::File::open(path)?;
-    let header = Header::read(&mut reader)?;
+    let db = Database::open(path)?;
+    let header = db.header();
 
+    println!("{:#?}", db.info());
     println!("{:#?}", header);
 
     for (i, table) in header.tables.iter().enumerate() {
-        println!("Table {}: {:?}", i, table.page_type);
-        for page in header
-            .read_pages(
-                &mut reader,
-                binrw::Endian::NATIVE,
-                (&table.first_page, &table.last_page),
-            )
-            .unwrap()
-            .into_iter()
-        {
+        match db.info().database_type {
+            DatabaseType::Plain => println!("Table {}: {:?}", i, table.page_type),
+            DatabaseType::Ext => println!("Table {}: {:?}", i, ExtPageType::from(table.page_type)),
+        }
+        for page in db.pages(table) {
+            let page = page?;
             println!("  {:?}", page);
             page.row_groups.iter().for_each(|row_group| {
                 println!("    {:?}", row_group);
@@ -157,6 +361,222 @@ fn dump_pdb(path: &PathBuf) -> rekordcrate::Result<()> {
     Ok(())
 }
 
+fn diff(path_a: &PathBuf, path_b: &PathBuf, detailed: bool) -> rekordcrate::Result<()> {
+    type Playlist = (PlaylistTreeNode, Vec<TrackId>);
+
+    fn read_tracks(db: &Database) -> rekordcrate::Result<HashMap<TrackId, Track>> {
+        let mut tracks = HashMap::new();
+        for track in db.iter_present_rows::<Track>() {
+            let track = track?;
+            tracks.insert(track.id(), track);
+        }
+        Ok(tracks)
+    }
+
+    fn read_playlists(db: &Database) -> rekordcrate::Result<HashMap<PlaylistTreeNodeId, Playlist>> {
+        let mut playlists = HashMap::new();
+        for row in db.iter_rows(PageType::PlaylistTree) {
+            if let Row::PlaylistTreeNode(node) = row? {
+                playlists.insert(node.id, (node, vec![]));
+            }
+        }
+        let mut entries = vec![];
+        for row in db.iter_rows(PageType::PlaylistEntries) {
+            if let Row::PlaylistEntry(entry) = row? {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by_key(PlaylistEntry::entry_index);
+        for entry in entries {
+            if let Some((_, track_ids)) = playlists.get_mut(&entry.playlist_id()) {
+                track_ids.push(entry.track_id());
+            }
+        }
+        Ok(playlists)
+    }
+
+    fn print_tracks(prefix: char, ids: &[TrackId], tracks: &HashMap<TrackId, Track>) {
+        for id in ids {
+            let title = tracks[id].title().unwrap_or_default();
+            println!("  {} {} {}", prefix, id.0, title);
+        }
+    }
+
+    fn print_playlists(
+        prefix: char,
+        ids: &[PlaylistTreeNodeId],
+        db: &Database,
+    ) -> rekordcrate::Result<()> {
+        for id in ids {
+            let path = db.playlist_full_path(*id)?.unwrap_or_default();
+            println!("  {} {} {}", prefix, id.0, path);
+        }
+        Ok(())
+    }
+
+    let db_a = Database::open(path_a)?;
+    let db_b = Database::open(path_b)?;
+
+    let tracks_a = read_tracks(&db_a)?;
+    let tracks_b = read_tracks(&db_b)?;
+    let track_changes = Changes::new(&tracks_a, &tracks_b, Track::semantically_equals, |id| id.0);
+    track_changes.print_summary("Tracks");
+    if detailed {
+        print_tracks('+', &track_changes.added, &tracks_b);
+        print_tracks('-', &track_changes.removed, &tracks_a);
+        print_tracks('~', &track_changes.changed, &tracks_b);
+    }
+
+    let playlists_a = read_playlists(&db_a)?;
+    let playlists_b = read_playlists(&db_b)?;
+    let playlist_changes = Changes::new(
+        &playlists_a,
+        &playlists_b,
+        |(node_a, tracks_a), (node_b, tracks_b)| {
+            node_a.name == node_b.name
+                && node_a.parent_id == node_b.parent_id
+                && node_a.is_folder() == node_b.is_folder()
+                && tracks_a == tracks_b
+        },
+        |id| id.0,
+    );
+    playlist_changes.print_summary("Playlists");
+    if detailed {
+        print_playlists('+', &playlist_changes.added, &db_b)?;
+        print_playlists('-', &playlist_changes.removed, &db_a)?;
+        print_playlists('~', &playlist_changes.changed, &db_b)?;
+    }
+
+    Ok(())
+}
+
+fn stats(path: &PathBuf, format: OutputFormat, pretty: bool) -> rekordcrate::Result<()> {
+    /// Number of artists that are listed.
+    const NUM_TOP_ARTISTS: usize = 10;
+    /// Size of the tempo ranges in BPM.
+    const BPM_BUCKET_SIZE: u32 = 10;
+
+    /// Sort the counts in descending order (and by name for equal counts).
+    fn sorted_by_count(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|(name_a, count_a), (name_b, count_b)| {
+            count_b.cmp(count_a).then_with(|| name_a.cmp(name_b))
+        });
+        counts
+    }
+
+    fn print_text(title: &str, counts: &[(String, usize)]) {
+        println!("{title}:");
+        for (name, count) in counts {
+            println!("  {name}: {count}");
+        }
+    }
+
+    /// Counts serialized as a JSON object, keeping the order of the entries.
+    struct CountsJson<'a>(&'a [(String, usize)]);
+
+    impl Serialize for CountsJson<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(self.0.iter().map(|(name, count)| (name, count)))
+        }
+    }
+
+    #[derive(Serialize)]
+    struct ArtistJson<'a> {
+        name: &'a str,
+        tracks: usize,
+    }
+
+    #[derive(Serialize)]
+    struct StatsJson<'a> {
+        tables: CountsJson<'a>,
+        genres: CountsJson<'a>,
+        bpm: CountsJson<'a>,
+        top_artists: Vec<ArtistJson<'a>>,
+    }
+
+    let db = Database::open(path)?;
+
+    let mut tables = vec![];
+    for table in &db.header().tables {
+        let name = match db.info().database_type {
+            DatabaseType::Plain => format!("{:?}", table.page_type),
+            DatabaseType::Ext => format!("{:?}", ExtPageType::from(table.page_type)),
+        };
+        tables.push((name, db.row_count(table.page_type)?));
+    }
+
+    let mut genres = HashMap::new();
+    for genre in db.iter_present_rows::<Genre>() {
+        let genre = genre?;
+        genres.insert(genre.id(), genre.name()?);
+    }
+    let mut artists = HashMap::new();
+    for artist in db.iter_present_rows::<Artist>() {
+        let artist = artist?;
+        artists.insert(artist.id(), artist.name()?);
+    }
+
+    let mut genre_counts: HashMap<String, usize> = HashMap::new();
+    let mut bpm_counts: HashMap<u32, usize> = HashMap::new();
+    let mut artist_counts: HashMap<String, usize> = HashMap::new();
+    for track in db.iter_present_rows::<Track>() {
+        let track = track?;
+        let genre = genres
+            .get(&track.genre_id())
+            .map_or("(none)", String::as_str);
+        *genre_counts.entry(genre.to_string()).or_default() += 1;
+        if let Ok(bpm) = track.bpm() {
+            let bucket = (bpm.0 as u32) / BPM_BUCKET_SIZE * BPM_BUCKET_SIZE;
+            *bpm_counts.entry(bucket).or_default() += 1;
+        }
+        if let Some(artist) = artists.get(&track.artist_id()) {
+            *artist_counts.entry(artist.clone()).or_default() += 1;
+        }
+    }
+
+    let genre_counts = sorted_by_count(genre_counts);
+    let mut bpm_counts: Vec<(u32, usize)> = bpm_counts.into_iter().collect();
+    bpm_counts.sort_unstable();
+    let bpm_counts: Vec<(String, usize)> = bpm_counts
+        .into_iter()
+        .map(|(bucket, count)| {
+            (
+                format!("{}-{}", bucket, bucket + BPM_BUCKET_SIZE - 1),
+                count,
+            )
+        })
+        .collect();
+    let mut artist_counts = sorted_by_count(artist_counts);
+    artist_counts.truncate(NUM_TOP_ARTISTS);
+
+    match format {
+        OutputFormat::Text => {
+            print_text("Tables", &tables);
+            print_text("Genres", &genre_counts);
+            print_text("BPM", &bpm_counts);
+            print_text("Top artists", &artist_counts);
+        }
+        OutputFormat::Json => {
+            let stats = StatsJson {
+                tables: CountsJson(&tables),
+                genres: CountsJson(&genre_counts),
+                bpm: CountsJson(&bpm_counts),
+                top_artists: artist_counts
+                    .iter()
+                    .map(|(name, count)| ArtistJson {
+                        name,
+                        tracks: *count,
+                    })
+                    .collect(),
+            };
+            print_json(&stats, pretty)?;
+        }
+    }
+
+    Ok(())
+}
+
 fn dump_setting(path: &PathBuf) -> rekordcrate::Result<()> {
     let mut reader = std::fs::File::open(path)?;
     let setting = Setting::read(&mut reader)?;
@@ -169,20 +589,50 @@ fn dump_setting(path: &PathBuf) -> rekordcrate::Result<()> {
 fn dump_xml(path: &PathBuf) -> rekordcrate::Result<()> {
     let file = std::fs::File::open(path)?;
     let reader = std::io::BufReader::new(file);
-    let document: Document = quick_xml::de::from_reader(reader).expect("failed to deserialize XML");
+    let document: Document = quick_xml::de::from_reader(reader)?;
     println!("{:#?}", document);
 
     Ok(())
 }
 
-fn main() -> rekordcrate::Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    // The `RUST_LOG` environment variable takes precedence over the command line flags.
+    env_logger::Builder::new()
+        .filter_level(cli.log_level())
+        .parse_default_env()
+        .init();
 
-    match &cli.command {
+    let result = match &cli.command {
         Commands::ListPlaylists { path } => list_playlists(path),
         Commands::DumpPDB { path } => dump_pdb(path),
         Commands::DumpANLZ { path } => dump_anlz(path),
+        Commands::ExtractAnlzCues { path, track_id } => {
+            extract_anlz_cues(path, *track_id, cli.pretty_json())
+        }
         Commands::DumpSetting { path } => dump_setting(path),
         Commands::DumpXML { path } => dump_xml(path),
+        Commands::Stats { path, format } => stats(path, *format, cli.pretty_json()),
+        Commands::Diff {
+            path_a,
+            path_b,
+            detailed,
+        } => diff(path_a, path_b, *detailed),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            // Print the whole chain of causes, since the error itself only describes the kind of
+            // failure.
+            let mut message = error.to_string();
+            let mut source = std::error::Error::source(&error);
+            while let Some(cause) = source {
+                message.push_str(&format!(": {cause}"));
+                source = cause.source();
+            }
+            eprintln!("Error: {message}");
+            ExitCode::FAILURE
+        }
     }
 }
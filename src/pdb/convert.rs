@@ -0,0 +1,572 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Conversion between rekordbox XML tracks and PDB track rows.
+//!
+//! In a PDB file, the artist, album, genre and key of a track are stored in separate tables and
+//! the track row only references them by ID. Hence, converting an XML track requires resolving
+//! (or creating) these rows, which is done by the [`TrackConverter`].
+//!
+//! The following fields are mapped in both directions:
+//!
+//! | XML attribute                                    | PDB field                                 |
+//! | ------------------------------------------------ | ----------------------------------------- |
+//! | `TrackID`                                        | track ID                                  |
+//! | `Name`, `Comments`, `Mix`, `DateAdded`           | title, comment, mix name, date added      |
+//! | `Artist`, `Composer`, `Remixer`                  | artist rows                               |
+//! | `Album`                                          | album row (together with the artist)      |
+//! | `Genre`, `Tonality`                              | genre and key rows                        |
+//! | `AverageBpm`                                     | tempo (XML: BPM, PDB: centi-BPM)          |
+//! | `Rating`                                         | rating (XML: 0–255, PDB: 0–5 stars)       |
+//! | `Location`                                       | file path and file name                   |
+//! | `Size`, `TotalTime`, `BitRate`, `SampleRate`     | file size, duration, bitrate, sample rate |
+//! | `DiscNumber`, `TrackNumber`, `Year`, `PlayCount` | disc/track number, year, play count       |
+//!
+//! The remaining fields are dropped: `Grouping`, `Kind`, `DateModified`, `LastPlayed`, `Label`,
+//! `Colour`, the beat grid (`TEMPO`) and the cue points (`POSITION_MARK`) of XML tracks, and the
+//! artwork, label, color, sample depth and analysis file path of PDB tracks.
+//!
+//! **Note:** XML locations are absolute file URIs on the computer running Rekordbox, whereas
+//! PDB file paths are relative to the root of the device. The path is converted as-is, so callers
+//! that convert an XML collection for a device export need to rewrite the file paths.
+
+use crate::pdb::string::{DeviceSQLString, StringError};
+use crate::pdb::{
+    io::Database, Album, AlbumId, Artist, ArtistId, ArtworkId, ColorIndex, FileType, Genre,
+    GenreId, Key, KeyId, LabelId, PageType, Row, Track, TrackId,
+};
+use crate::util::RekordcrateResult;
+use crate::xml;
+
+/// Prefix of the `Location` attribute of XML tracks.
+const LOCATION_PREFIX: &str = "file://localhost";
+
+/// Converts tracks between the rekordbox XML format and PDB track rows.
+///
+/// The converter keeps track of the artist, album, genre and key rows, so that tracks with the
+/// same artist reference the same artist row.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::{pdb::convert::TrackConverter, xml::Document};
+///
+/// let data = std::fs::read_to_string("data/xml/database.xml").unwrap();
+/// let document: Document = quick_xml::de::from_str(&data)?;
+/// let mut converter = TrackConverter::new();
+/// let tracks = document
+///     .tracks()
+///     .iter()
+///     .map(|track| converter.to_pdb_track(track))
+///     .collect::<rekordcrate::Result<Vec<_>>>()?;
+/// assert_eq!(tracks.len(), 6);
+/// assert_eq!(converter.artists().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TrackConverter {
+    artists: Vec<Artist>,
+    albums: Vec<Album>,
+    genres: Vec<Genre>,
+    keys: Vec<Key>,
+}
+
+impl TrackConverter {
+    /// Create a new converter without any artist, album, genre or key rows.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new converter that resolves names using the rows of an existing database.
+    ///
+    /// This is necessary when converting PDB tracks to XML tracks, and can be used to reuse the
+    /// existing rows when adding XML tracks to a database.
+    pub fn from_database(database: &Database) -> RekordcrateResult<Self> {
+        let mut converter = Self::new();
+        for page_type in [
+            PageType::Artists,
+            PageType::Albums,
+            PageType::Genres,
+            PageType::Keys,
+        ] {
+            for row in database.iter_rows(page_type) {
+                match row? {
+                    Row::Artist(artist) => converter.artists.push(artist),
+                    Row::Album(album) => converter.albums.push(album),
+                    Row::Genre(genre) => converter.genres.push(genre),
+                    Row::Key(key) => converter.keys.push(key),
+                    _ => {}
+                }
+            }
+        }
+        Ok(converter)
+    }
+
+    /// The artist rows that are known to this converter, including the ones created during
+    /// conversion.
+    #[must_use]
+    pub fn artists(&self) -> &[Artist] {
+        &self.artists
+    }
+
+    /// The album rows that are known to this converter, including the ones created during
+    /// conversion.
+    #[must_use]
+    pub fn albums(&self) -> &[Album] {
+        &self.albums
+    }
+
+    /// The genre rows that are known to this converter, including the ones created during
+    /// conversion.
+    #[must_use]
+    pub fn genres(&self) -> &[Genre] {
+        &self.genres
+    }
+
+    /// The key rows that are known to this converter, including the ones created during
+    /// conversion.
+    #[must_use]
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Convert an XML track into a PDB track row.
+    ///
+    /// Artist, album, genre and key rows are looked up by name and created if they don't exist
+    /// yet.
+    pub fn to_pdb_track(&mut self, track: &xml::Track) -> RekordcrateResult<Track> {
+        let artist_id = self.artist_id(track.artist.as_deref())?;
+        let composer_id = self.artist_id(track.composer.as_deref())?;
+        let remixer_id = self.artist_id(track.remixer.as_deref())?;
+        let album_id = self.album_id(track.album.as_deref(), artist_id)?;
+        let genre_id = self.genre_id(track.genre.as_deref())?;
+        let key_id = self.key_id(track.tonality.as_deref())?;
+
+        let file_path = decode_location(&track.location);
+        let filename = file_path.rsplit('/').next().unwrap_or_default().to_string();
+        let rating = (track.rating.unwrap_or_default() / 51).clamp(0, i32::from(Track::MAX_RATING));
+        let int = |value: Option<i32>| i64::from(value.unwrap_or_default());
+
+        Ok(Track {
+            unknown1: 0x24,
+            index_shift: 0,
+            bitmask: 0xc0700,
+            sample_rate: clamped_float(track.samplerate.unwrap_or_default(), u32::MAX),
+            composer_id,
+            file_size: clamped(track.size.unwrap_or_default(), u32::MAX),
+            unknown2: 0,
+            unknown3: 0,
+            unknown4: 0,
+            artwork_id: ArtworkId(0),
+            key_id,
+            orig_artist_id: ArtistId(0),
+            label_id: LabelId(0),
+            remixer_id,
+            bitrate: clamped(int(track.bitrate), u32::MAX),
+            track_number: clamped(int(track.tracknumber), u32::MAX),
+            tempo: clamped_float(track.averagebpm.unwrap_or_default() * 100.0, u32::MAX),
+            genre_id,
+            album_id,
+            artist_id,
+            id: TrackId(clamped(i64::from(track.trackid), u32::MAX)),
+            disc_number: clamped(int(track.discnumber), u16::MAX),
+            play_count: clamped(int(track.playcount), u16::MAX),
+            year: clamped(int(track.year), u16::MAX),
+            sample_depth: 0,
+            duration: clamped_float(track.totaltime.unwrap_or_default(), u16::MAX),
+            unknown5: 0x29,
+            color: ColorIndex::None,
+            rating: clamped(i64::from(rating), Track::MAX_RATING),
+            file_type: file_type(track.kind.as_deref(), &filename),
+            unknown7: 3,
+            isrc: DeviceSQLString::new_isrc(String::new())?,
+            unknown_string1: DeviceSQLString::empty(),
+            unknown_string2: DeviceSQLString::empty(),
+            unknown_string3: DeviceSQLString::empty(),
+            unknown_string4: DeviceSQLString::empty(),
+            message: DeviceSQLString::empty(),
+            kuvo_public: DeviceSQLString::empty(),
+            autoload_hotcues: DeviceSQLString::new("ON".to_string())?,
+            unknown_string5: DeviceSQLString::empty(),
+            unknown_string6: DeviceSQLString::empty(),
+            date_added: DeviceSQLString::new(track.dateadded.clone().unwrap_or_default())?,
+            release_date: DeviceSQLString::empty(),
+            mix_name: DeviceSQLString::new(track.mix.clone().unwrap_or_default())?,
+            unknown_string7: DeviceSQLString::empty(),
+            analyze_path: DeviceSQLString::empty(),
+            analyze_date: DeviceSQLString::empty(),
+            comment: DeviceSQLString::new(track.comments.clone().unwrap_or_default())?,
+            title: DeviceSQLString::new(track.name.clone().unwrap_or_default())?,
+            unknown_string8: DeviceSQLString::empty(),
+            filename: DeviceSQLString::new(filename)?,
+            file_path: DeviceSQLString::new(file_path)?,
+        })
+    }
+
+    /// Convert a PDB track row into an XML track.
+    ///
+    /// Artist, album, genre and key names are resolved using the rows known to this converter
+    /// (see [`TrackConverter::from_database`]). Unknown IDs result in empty names.
+    pub fn to_xml_track(&self, track: &Track) -> RekordcrateResult<xml::Track> {
+        let artist_name = |id: ArtistId| -> Result<String, StringError> {
+            self.artists
+                .iter()
+                .find(|artist| id.0 != 0 && artist.id == id)
+                .map_or_else(|| Ok(String::new()), Artist::name)
+        };
+        let album = self
+            .albums
+            .iter()
+            .find(|album| track.album_id.0 != 0 && album.id == track.album_id)
+            .map_or_else(
+                || Ok(String::new()),
+                |album| album.name.clone().into_string(),
+            )?;
+        let genre = self
+            .genres
+            .iter()
+            .find(|genre| track.genre_id.0 != 0 && genre.id == track.genre_id)
+            .map_or_else(
+                || Ok(String::new()),
+                |genre| genre.name.clone().into_string(),
+            )?;
+        let tonality = self
+            .keys
+            .iter()
+            .find(|key| track.key_id.0 != 0 && key.id == track.key_id)
+            .map_or_else(|| Ok(String::new()), Key::name)?;
+
+        Ok(xml::Track {
+            trackid: i32::try_from(track.id.0).unwrap_or_default(),
+            name: Some(track.title.clone().into_string()?),
+            artist: Some(artist_name(track.artist_id)?),
+            composer: Some(artist_name(track.composer_id)?),
+            album: Some(album),
+            grouping: None,
+            genre: Some(genre),
+            kind: None,
+            size: Some(track.file_size.into()),
+            totaltime: Some(track.duration.into()),
+            discnumber: Some(track.disc_number.into()),
+            tracknumber: Some(i32::try_from(track.track_number).unwrap_or_default()),
+            year: Some(track.year.into()),
+            averagebpm: Some(f64::from(track.tempo) / 100.0),
+            datemodified: None,
+            dateadded: Some(track.date_added.clone().into_string()?),
+            bitrate: Some(i32::try_from(track.bitrate).unwrap_or_default()),
+            samplerate: Some(track.sample_rate.into()),
+            comments: Some(track.comment.clone().into_string()?),
+            playcount: Some(track.play_count.into()),
+            lastplayed: None,
+            rating: Some(i32::from(track.rating) * 51),
+            location: encode_location(&track.file_path.clone().into_string()?),
+            remixer: Some(artist_name(track.remixer_id)?),
+            tonality: Some(tonality),
+            label: None,
+            mix: Some(track.mix_name.clone().into_string()?),
+            colour: None,
+            tempos: vec![],
+            position_marks: vec![],
+        })
+    }
+
+    /// Find or create the artist row with the given name.
+    fn artist_id(&mut self, name: Option<&str>) -> Result<ArtistId, StringError> {
+        let Some(name) = name.filter(|name| !name.is_empty()) else {
+            return Ok(ArtistId(0));
+        };
+        if let Some(artist) = self
+            .artists
+            .iter()
+            .find(|artist| artist.name().is_ok_and(|n| n == name))
+        {
+            return Ok(artist.id);
+        }
+        let id = ArtistId(next_id(self.artists.iter().map(|artist| artist.id.0)));
+        self.artists.push(Artist::new(id, name)?);
+        Ok(id)
+    }
+
+    /// Find or create the album row with the given name and artist.
+    fn album_id(
+        &mut self,
+        name: Option<&str>,
+        artist_id: ArtistId,
+    ) -> Result<AlbumId, StringError> {
+        let Some(name) = name.filter(|name| !name.is_empty()) else {
+            return Ok(AlbumId(0));
+        };
+        if let Some(album) = self.albums.iter().find(|album| {
+            album.artist_id == artist_id
+                && album.name.clone().into_string().is_ok_and(|n| n == name)
+        }) {
+            return Ok(album.id);
+        }
+        let id = AlbumId(next_id(self.albums.iter().map(|album| album.id.0)));
+        self.albums.push(Album {
+            unknown1: 0x80,
+            index_shift: 0,
+            unknown2: 0,
+            artist_id,
+            id,
+            unknown3: 0,
+            unknown4: 3,
+            name: DeviceSQLString::new(name.to_string())?,
+        });
+        Ok(id)
+    }
+
+    /// Find or create the genre row with the given name.
+    fn genre_id(&mut self, name: Option<&str>) -> Result<GenreId, StringError> {
+        let Some(name) = name.filter(|name| !name.is_empty()) else {
+            return Ok(GenreId(0));
+        };
+        if let Some(genre) = self
+            .genres
+            .iter()
+            .find(|genre| genre.name.clone().into_string().is_ok_and(|n| n == name))
+        {
+            return Ok(genre.id);
+        }
+        let id = GenreId(next_id(self.genres.iter().map(|genre| genre.id.0)));
+        self.genres.push(Genre {
+            id,
+            name: DeviceSQLString::new(name.to_string())?,
+        });
+        Ok(id)
+    }
+
+    /// Find or create the key row with the given name.
+    fn key_id(&mut self, name: Option<&str>) -> Result<KeyId, StringError> {
+        let Some(name) = name.filter(|name| !name.is_empty()) else {
+            return Ok(KeyId(0));
+        };
+        if let Some(key) = self
+            .keys
+            .iter()
+            .find(|key| key.name().is_ok_and(|n| n == name))
+        {
+            return Ok(key.id);
+        }
+        let id = KeyId(next_id(self.keys.iter().map(|key| key.id.0)));
+        self.keys.push(Key {
+            id,
+            id2: id.0,
+            name: DeviceSQLString::new(name.to_string())?,
+        });
+        Ok(id)
+    }
+}
+
+/// Convert an integer from an XML track into the type of the corresponding PDB field, clamping it
+/// to the range of that type (negative values become `0`).
+fn clamped<T: TryFrom<i64> + Default>(value: i64, max: T) -> T {
+    T::try_from(value).unwrap_or(if value < 0 { T::default() } else { max })
+}
+
+/// Round a number from an XML track and convert it like [`clamped`].
+fn clamped_float<T: TryFrom<i64> + Default>(value: f64, max: T) -> T {
+    // Float to integer casts saturate (and map NaN to zero), so this only loses the fraction.
+    clamped(value.round() as i64, max)
+}
+
+/// Returns the smallest unused (non-zero) ID that is larger than all given IDs.
+fn next_id(ids: impl Iterator<Item = u32>) -> u32 {
+    ids.max().unwrap_or_default() + 1
+}
+
+/// Determine the file type of a track from the `Kind` attribute of an XML track (e.g. `MP3 File`)
+/// or, if that is missing or unknown, from the file extension.
+fn file_type(kind: Option<&str>, filename: &str) -> FileType {
+    let from_name = |name: &str| match name.to_ascii_lowercase().as_str() {
+        "mp3" => Some(FileType::Mp3),
+        "m4a" => Some(FileType::M4a),
+        "flac" => Some(FileType::Flac),
+        "wav" => Some(FileType::Wav),
+        "aif" | "aiff" => Some(FileType::Aiff),
+        _ => None,
+    };
+    kind.and_then(|kind| kind.strip_suffix(" File"))
+        .and_then(from_name)
+        .or_else(|| {
+            filename
+                .rsplit_once('.')
+                .and_then(|(_, extension)| from_name(extension))
+        })
+        .unwrap_or(FileType::Unknown(0))
+}
+
+/// Convert the `Location` attribute of an XML track (a percent-encoded file URI) into a path.
+fn decode_location(location: &str) -> String {
+    let encoded = location.strip_prefix(LOCATION_PREFIX).unwrap_or(location);
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next(), iter.next()];
+            if let [Some(high), Some(low)] = hex {
+                if let Ok(decoded) =
+                    u8::from_str_radix(std::str::from_utf8(&[high, low]).unwrap_or_default(), 16)
+                {
+                    bytes.push(decoded);
+                    continue;
+                }
+            }
+            bytes.push(byte);
+            bytes.extend(hex.into_iter().flatten());
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Convert a file path into the `Location` attribute of an XML track.
+fn encode_location(path: &str) -> String {
+    let mut location = String::from(LOCATION_PREFIX);
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            location.push(char::from(byte));
+        } else {
+            location.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    location
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn xml_tracks() -> Vec<xml::Track> {
+        let data = include_str!("../../data/xml/database.xml");
+        let document: xml::Document = quick_xml::de::from_str(data).unwrap();
+        document.tracks().to_vec()
+    }
+
+    #[test]
+    fn xml_to_pdb_track() {
+        let mut converter = TrackConverter::new();
+        let tracks = xml_tracks()
+            .iter()
+            .map(|track| converter.to_pdb_track(track))
+            .collect::<RekordcrateResult<Vec<Track>>>()
+            .unwrap();
+
+        let track = tracks.iter().find(|track| track.id == TrackId(5)).unwrap();
+        assert_eq!(track.title.clone().into_string().unwrap(), "Demo Track 1");
+        assert_eq!(track.tempo, 12800);
+        assert_eq!(
+            track.file_path.clone().into_string().unwrap(),
+            "/C:/Music/PioneerDJ/Demo Tracks/Demo Track 1.mp3"
+        );
+        assert_eq!(
+            track.filename.clone().into_string().unwrap(),
+            "Demo Track 1.mp3"
+        );
+
+        // Both demo tracks share the same artist row.
+        let other = tracks.iter().find(|track| track.id == TrackId(6)).unwrap();
+        assert_ne!(track.artist_id, ArtistId(0));
+        assert_eq!(track.artist_id, other.artist_id);
+        assert_eq!(converter.artists().len(), 1);
+        assert_eq!(converter.artists()[0].name().unwrap(), "Loopmasters");
+    }
+
+    #[test]
+    fn pdb_to_xml_track_roundtrip() {
+        let mut converter = TrackConverter::new();
+        for track in xml_tracks() {
+            let pdb_track = converter.to_pdb_track(&track).unwrap();
+            let xml_track = converter.to_xml_track(&pdb_track).unwrap();
+            assert_eq!(xml_track.trackid, track.trackid);
+            assert_eq!(xml_track.name, track.name);
+            assert_eq!(xml_track.artist, track.artist);
+            assert_eq!(xml_track.genre, track.genre);
+            assert_eq!(xml_track.averagebpm, track.averagebpm);
+            assert_eq!(xml_track.rating, track.rating);
+            assert_eq!(xml_track.location, track.location);
+        }
+    }
+
+    #[test]
+    fn pdb_to_xml_track_from_database() {
+        let database =
+            Database::open("data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb")
+                .unwrap();
+        let converter = TrackConverter::from_database(&database).unwrap();
+        let track = database
+            .iter_rows(PageType::Tracks)
+            .find_map(|row| match row.unwrap() {
+                Row::Track(track) if track.id == TrackId(1) => Some(track),
+                _ => None,
+            })
+            .unwrap();
+        let xml_track = converter.to_xml_track(&track).unwrap();
+        assert_eq!(xml_track.name.as_deref(), Some("Demo Track 1"));
+        assert_eq!(xml_track.artist.as_deref(), Some("Loopmasters"));
+        assert_eq!(xml_track.averagebpm, Some(128.0));
+        assert_eq!(
+            xml_track.location,
+            "file://localhost/Contents/Loopmasters/UnknownAlbum/Demo%20Track%201.mp3"
+        );
+    }
+
+    #[test]
+    fn xml_file_type() {
+        assert_eq!(file_type(Some("MP3 File"), "track.wav"), FileType::Mp3);
+        assert_eq!(file_type(Some("WAV File"), "track"), FileType::Wav);
+        assert_eq!(file_type(None, "track.FLAC"), FileType::Flac);
+        assert_eq!(file_type(Some("Unknown File"), "track.aif"), FileType::Aiff);
+        assert_eq!(file_type(None, "track.ogg"), FileType::Unknown(0));
+        assert_eq!(file_type(None, "track"), FileType::Unknown(0));
+
+        let mut converter = TrackConverter::new();
+        let file_types: Vec<FileType> = xml_tracks()
+            .iter()
+            .map(|track| converter.to_pdb_track(track).unwrap().file_type)
+            .collect();
+        assert!(file_types.contains(&FileType::Mp3));
+        assert!(file_types.contains(&FileType::Wav));
+    }
+
+    #[test]
+    fn xml_to_pdb_track_out_of_range() {
+        let mut track = xml_tracks()[0].clone();
+        track.trackid = -1;
+        track.size = Some(i64::MAX);
+        track.totaltime = Some(1e9);
+        track.bitrate = Some(-320);
+        track.samplerate = Some(f64::NAN);
+        track.averagebpm = Some(128.004);
+        track.year = Some(100_000);
+        track.rating = Some(1000);
+        let track = TrackConverter::new().to_pdb_track(&track).unwrap();
+        assert_eq!(track.id, TrackId(0));
+        assert_eq!(track.file_size, u32::MAX);
+        assert_eq!(track.duration, u16::MAX);
+        assert_eq!(track.bitrate, 0);
+        assert_eq!(track.sample_rate, 0);
+        assert_eq!(track.tempo, 12800);
+        assert_eq!(track.year, u16::MAX);
+        assert_eq!(track.rating, Track::MAX_RATING);
+    }
+
+    #[test]
+    fn location_encoding() {
+        let path = "/Contents/Artist/Album (Deluxe)/01 Ünïcode 100%.mp3";
+        let location = encode_location(path);
+        assert_eq!(
+            location,
+            "file://localhost/Contents/Artist/Album%20%28Deluxe%29/01%20%C3%9Cn%C3%AFcode%20100%25.mp3"
+        );
+        assert_eq!(decode_location(&location), path);
+        assert_eq!(decode_location("/invalid%zz"), "/invalid%zz");
+    }
+}
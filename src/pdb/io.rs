@@ -0,0 +1,2683 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! High-level API for accessing PDB files.
+//!
+//! The [`Database`] type reads the file header once and then loads pages lazily on demand, so
+//! that it's not necessary to keep the whole file in memory.
+
+use crate::pdb::{
+    merge::MergedCollection,
+    string::{DeviceSQLString, StringError},
+    Artist, ArtistId, ColumnEntry, DatabaseType, ExtPageType, ExtRow, Genre, Header, Key, Label,
+    Page, PageFlags, PageHeader, PageIndex, PageType, PlaylistEntry, PlaylistTreeNode,
+    PlaylistTreeNodeId, Row, Table, TableRow, TagId, Track, TrackId,
+};
+use crate::util::{search_form, ColorIndex, RekordcrateError, RekordcrateResult};
+use binrw::{
+    io::{BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    BinRead, BinResult, BinWrite, Endian,
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// Combination of [`Read`] and [`Seek`] that can be used as a trait object.
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Summary of the format characteristics of a PDB file, returned by [`Database::info`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DatabaseInfo {
+    /// The (guessed) kind of database, i.e. whether this is an `export.pdb` or `exportExt.pdb`
+    /// file.
+    pub database_type: DatabaseType,
+    /// Byte order of all numeric fields. PDB files are always little-endian.
+    pub endian: Endian,
+    /// Size of a single page in bytes.
+    pub page_size: u32,
+    /// Number of tables in the file header.
+    pub num_tables: usize,
+    /// Sequence number of the database, which is incremented whenever the database is modified
+    /// and can be used as a version number.
+    pub sequence: u32,
+}
+
+/// Anomalies in the entry indices of a playlist, returned by [`Database::check_playlist_entries`].
+///
+/// The entries of a playlist with `n` tracks are expected to have the indices `1` to `n`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PlaylistEntryIssues {
+    /// ID of the playlist.
+    pub playlist_id: PlaylistTreeNodeId,
+    /// Indices that are used by more than one entry, in ascending order.
+    pub duplicate_indices: Vec<u32>,
+    /// Indices from `1` to the number of entries that are not used by any entry, in ascending
+    /// order.
+    pub missing_indices: Vec<u32>,
+}
+
+/// Total duration of the tracks in a playlist, returned by [`Database::playlist_duration`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct PlaylistDuration {
+    /// Sum of the durations of all tracks that were found.
+    pub duration: Duration,
+    /// Number of playlist entries whose track was found.
+    pub num_tracks: usize,
+    /// Number of playlist entries whose track does not exist and that were skipped.
+    pub num_missing_tracks: usize,
+}
+
+/// Page types of the tables in an `export.pdb` file created by Rekordbox, in order.
+const STANDARD_PAGE_TYPES: [PageType; 20] = [
+    PageType::Tracks,
+    PageType::Genres,
+    PageType::Artists,
+    PageType::Albums,
+    PageType::Labels,
+    PageType::Keys,
+    PageType::Colors,
+    PageType::PlaylistTree,
+    PageType::PlaylistEntries,
+    PageType::Unknown(9),
+    PageType::Unknown(10),
+    PageType::HistoryPlaylists,
+    PageType::HistoryEntries,
+    PageType::Artwork,
+    PageType::Unknown(14),
+    PageType::Unknown(15),
+    PageType::Columns,
+    PageType::Unknown(17),
+    PageType::Unknown(18),
+    PageType::History,
+];
+
+/// Size of the index header that follows the page header of an index page.
+const INDEX_HEADER_SIZE: u32 = 0x14;
+
+/// Size of the unused space at the end of an index page.
+const INDEX_FOOTER_SIZE: u32 = 0x14;
+
+/// Offset of the first data page of the table in its index page, which follows the index of the
+/// index page itself at the start of the index header. This is [`NO_PAGE`] if the table does not
+/// have any data pages.
+const FIRST_DATA_PAGE_OFFSET: usize = Page::HEADER_SIZE as usize + 4;
+
+/// Page index that is used in index pages if there is no page.
+const NO_PAGE: u32 = 0x03FF_FFFF;
+
+/// Serialize an index page without any entries (i.e. the first page of an empty table).
+fn empty_index_page(
+    page_index: &PageIndex,
+    page_type: PageType,
+    next_page: &PageIndex,
+    page_size: u32,
+) -> BinResult<Vec<u8>> {
+    const EMPTY_ENTRY: u32 = 0x1FFF_FFF8;
+    let num_entries = (page_size - Page::HEADER_SIZE - INDEX_HEADER_SIZE - INDEX_FOOTER_SIZE) / 4;
+
+    let mut writer = Cursor::new(vec![0; page_size as usize]);
+    // Page header.
+    (0u32, page_index, page_type, next_page, 1u32, 0u32).write_le(&mut writer)?;
+    // Number of rows, unknown fields, flags (index page), free and used size.
+    ([0u8, 0, 0, 0x64], 0u16, 0u16).write_le(&mut writer)?;
+    (0x1FFFu16, 0x1FFFu16, num_entries as u16, 0u16).write_le(&mut writer)?;
+    // Index header: page index, first data page, unknown fields, number of entries and first
+    // empty entry.
+    page_index.write_le(&mut writer)?;
+    debug_assert_eq!(writer.position(), FIRST_DATA_PAGE_OFFSET as u64);
+    (NO_PAGE, NO_PAGE, 0u32, 0u16, 0x1FFFu16).write_le(&mut writer)?;
+    vec![EMPTY_ENTRY; num_entries as usize].write_le(&mut writer)?;
+    Ok(writer.into_inner())
+}
+
+/// A PDB database.
+///
+/// Only the file header is parsed when the database is opened. Pages are read from the underlying
+/// reader when they are accessed.
+///
+/// # Editing
+///
+/// The methods that take `&mut self` (e.g. [`Database::set_track_title`] or
+/// [`Database::create_playlist`]) rewrite the affected pages in an in-memory copy of the file.
+/// The file that the database was opened from is never modified, so use [`Database::write`] to
+/// save the changes.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::pdb::{io::Database, PageType};
+///
+/// let db = Database::open("data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb")?;
+/// for row in db.iter_rows(PageType::Tracks) {
+///     println!("{:?}", row?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Database {
+    /// The file header.
+    header: Header,
+    /// The (guessed) kind of database, which determines how rows are parsed.
+    database_type: DatabaseType,
+    /// The reader that pages are loaded from.
+    reader: RefCell<Box<dyn ReadSeek>>,
+    /// Playlists that each track appears in, built on first use by
+    /// [`Database::playlists_containing`].
+    track_playlists: RefCell<Option<HashMap<TrackId, Vec<PlaylistTreeNodeId>>>>,
+}
+
+impl Database {
+    /// Open the database file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> RekordcrateResult<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::open_non_persistent(BufReader::new(file))
+    }
+
+    /// Open a database that is read from an arbitrary `reader` (e.g. an in-memory buffer).
+    pub fn open_non_persistent<R: Read + Seek + 'static>(mut reader: R) -> RekordcrateResult<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let header = Header::read(&mut reader)?;
+        Ok(Self {
+            database_type: DatabaseType::guess(&header),
+            header,
+            reader: RefCell::new(Box::new(reader)),
+            track_playlists: RefCell::default(),
+        })
+    }
+
+    /// Open the database file at `path` by mapping it into memory.
+    ///
+    /// Pages are then read by copying them from the mapped memory instead of issuing a syscall
+    /// for every seek and read, which can speed up random access to large databases noticeably.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified (or truncated) while the database is open, neither by this
+    /// process nor by any other process. Otherwise, the behavior is undefined (and in practice
+    /// the process may crash with `SIGBUS`). Only use this for files that are not concurrently
+    /// written, e.g. exports on a USB drive that is not being written to by Rekordbox at the same
+    /// time.
+    #[cfg(feature = "mmap")]
+    #[allow(unsafe_code)]
+    pub unsafe fn open_mmap<P: AsRef<Path>>(path: P) -> RekordcrateResult<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: The caller guarantees that the file is not modified while it's mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::open_non_persistent(binrw::io::Cursor::new(mmap))
+    }
+
+    /// Create a new database in memory that contains the standard set of tables of an
+    /// `export.pdb` file, all of which are empty.
+    ///
+    /// Each table consists of a single index page (and a reserved empty page), just like in
+    /// exports created by Rekordbox. Use [`Database::write`] to save the database to a file.
+    ///
+    /// **Note:** Rekordbox usually populates the color and column tables of new exports, but these
+    /// are left empty here.
+    pub fn new_empty(page_size: u32) -> RekordcrateResult<Self> {
+        if page_size < Page::HEADER_SIZE + INDEX_HEADER_SIZE + INDEX_FOOTER_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Page size {} is too small", page_size),
+            )
+            .into());
+        }
+
+        let tables: Vec<Table> = (0u32..)
+            .zip(STANDARD_PAGE_TYPES)
+            .map(|(i, page_type)| Table {
+                page_type,
+                empty_candidate: 2 * i + 2,
+                first_page: PageIndex(2 * i + 1),
+                last_page: PageIndex(2 * i + 1),
+            })
+            .collect();
+        let num_pages = 2 * u32::try_from(tables.len()).unwrap_or(u32::MAX) + 1;
+        let header = Header {
+            page_size,
+            next_unused_page: PageIndex(num_pages),
+            unknown: 5,
+            sequence: 1,
+            tables,
+        };
+
+        let mut writer = Cursor::new(vec![0; page_size as usize * num_pages as usize]);
+        header.write(&mut writer)?;
+        for table in &header.tables {
+            writer.seek(SeekFrom::Start(table.first_page.offset(page_size)))?;
+            writer.write_all(&empty_index_page(
+                &table.first_page,
+                table.page_type,
+                &PageIndex(table.empty_candidate),
+                page_size,
+            )?)?;
+        }
+
+        Self::open_non_persistent(writer)
+    }
+
+    /// Write the complete database file to `writer`.
+    pub fn write<W: Write>(&self, writer: &mut W) -> RekordcrateResult<()> {
+        let mut reader = self.reader.borrow_mut();
+        reader.seek(SeekFrom::Start(0))?;
+        std::io::copy(&mut *reader, writer)?;
+        Ok(())
+    }
+
+    /// The file header, which contains the page size and the list of tables.
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Summary of the format characteristics of this database.
+    #[must_use]
+    pub fn info(&self) -> DatabaseInfo {
+        DatabaseInfo {
+            database_type: self.database_type,
+            endian: Endian::Little,
+            page_size: self.header.page_size,
+            num_tables: self.header.tables.len(),
+            sequence: self.header.sequence,
+        }
+    }
+
+    /// Whether the tables in the header have the page types (and order) of an `export.pdb` file
+    /// written by Rekordbox.
+    pub(crate) fn has_standard_tables(&self) -> bool {
+        self.header
+            .tables
+            .iter()
+            .map(|table| table.page_type)
+            .eq(STANDARD_PAGE_TYPES)
+    }
+
+    /// Read the page with the given index.
+    pub fn read_page(&self, page_index: &PageIndex) -> RekordcrateResult<Page> {
+        let mut reader = self.reader.borrow_mut();
+        let offset = page_index.offset(self.header.page_size);
+        reader.seek(SeekFrom::Start(offset))?;
+        let page = Page::read_options(
+            &mut *reader,
+            Endian::Little,
+            (self.header.page_size, self.database_type),
+        )?;
+        #[cfg(feature = "trace")]
+        log::trace!(
+            "Read page {} at offset {:#x}: {:?} page with {} rows, next page {}",
+            page_index.0,
+            offset,
+            page.page_type,
+            page.num_rows(),
+            page.next_page.0
+        );
+        Ok(page)
+    }
+
+    /// Read only the header of the page with the given index.
+    pub fn read_page_header(&self, page_index: &PageIndex) -> RekordcrateResult<PageHeader> {
+        let mut reader = self.reader.borrow_mut();
+        let offset = page_index.offset(self.header.page_size);
+        reader.seek(SeekFrom::Start(offset))?;
+        let page_header = PageHeader::read(&mut *reader)?;
+        #[cfg(feature = "trace")]
+        log::trace!(
+            "Read header of page {} at offset {:#x}: {:?} page with {} rows, next page {}",
+            page_index.0,
+            offset,
+            page_header.page_type,
+            page_header.num_rows(),
+            page_header.next_page.0
+        );
+        Ok(page_header)
+    }
+
+    /// List the page types of all tables, along with whether the table contains any rows.
+    ///
+    /// The first page of each table is an index page that never contains rows, so a table
+    /// with pages is not necessarily non-empty. This only reads the page headers and row
+    /// presence flags (see [`Database::row_count`]).
+    pub fn table_summary(&self) -> RekordcrateResult<Vec<(PageType, bool)>> {
+        self.header
+            .tables
+            .iter()
+            .map(|table| Ok((table.page_type, self.row_count(table.page_type)? > 0)))
+            .collect()
+    }
+
+    /// Count the present rows of all tables with the given page type.
+    ///
+    /// This only reads the page headers and the row presence flags, so it's a lot cheaper than
+    /// iterating over the rows using [`Database::iter_rows`].
+    pub fn row_count(&self, page_type: PageType) -> RekordcrateResult<usize> {
+        let mut row_count = 0;
+        for table in self
+            .header
+            .tables
+            .iter()
+            .filter(|table| table.page_type == page_type)
+        {
+            let mut visited = HashSet::new();
+            let mut page_index = table.first_page.clone();
+            loop {
+                if !visited.insert(page_index.clone()) {
+                    return Err(Self::cyclic_page_list_error(
+                        &page_index,
+                        self.header.page_size,
+                    ));
+                }
+                let page_header = self.read_page_header(&page_index)?;
+                let mut reader = self.reader.borrow_mut();
+                reader.seek(SeekFrom::Start(page_index.offset(self.header.page_size)))?;
+                row_count +=
+                    page_header.read_num_present_rows(&mut *reader, self.header.page_size)?;
+                if page_header.page_index == table.last_page {
+                    break;
+                }
+                page_index = page_header.next_page;
+            }
+        }
+        Ok(row_count)
+    }
+
+    /// Error that is returned if a page is encountered twice while following a table's pages.
+    fn cyclic_page_list_error(page_index: &PageIndex, page_size: u32) -> RekordcrateError {
+        binrw::Error::AssertFail {
+            pos: page_index.offset(page_size),
+            message: format!(
+                "Page {:?} was already visited (cyclic page list)",
+                page_index
+            ),
+        }
+        .into()
+    }
+
+    /// Iterate over the pages of the given table.
+    ///
+    /// The pages are read lazily, i.e. only when the iterator is advanced.
+    #[must_use]
+    pub fn pages<'a>(&'a self, table: &Table) -> Pages<'a> {
+        Pages {
+            database: self,
+            next_page: Some(table.first_page.clone()),
+            last_page: table.last_page.clone(),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Iterate over the rows of all tables with the given page type.
+    ///
+    /// Only rows that are marked as present in the row presence flags of their row group are
+    /// returned. Deleted rows may still occupy space on the page, but are never returned.
+    ///
+    /// Pages are read lazily, so that at most one page needs to be held in memory at a time. The
+    /// rows are owned by the caller. If the rows only need to be inspected, consider using
+    /// [`Database::load_table`] and [`LoadedTable::iter_rows_ref`] instead.
+    pub fn iter_rows(
+        &self,
+        page_type: PageType,
+    ) -> impl Iterator<Item = RekordcrateResult<Row>> + '_ {
+        self.header
+            .tables
+            .iter()
+            .filter(move |table| table.page_type == page_type)
+            .flat_map(|table| self.pages(table))
+            .flat_map(|page| {
+                let rows: Vec<RekordcrateResult<Row>> = match page {
+                    Ok(page) => page.into_rows().map(Ok).collect(),
+                    Err(err) => vec![Err(err)],
+                };
+                rows
+            })
+    }
+
+    /// Iterate over the rows of type `T`, e.g. all [`Track`] rows.
+    ///
+    /// Like [`Database::iter_rows`], this only returns rows that are marked as present in the row
+    /// presence flags of their row group, in the order in which they are stored in the tables.
+    ///
+    /// ```
+    /// # pub fn main() -> rekordcrate::Result<()> {
+    /// use rekordcrate::pdb::{io::Database, Track};
+    ///
+    /// let db = Database::open("data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb")?;
+    /// for track in db.iter_present_rows::<Track>() {
+    ///     println!("{}", track?.title()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_present_rows<T: TableRow>(
+        &self,
+    ) -> impl Iterator<Item = RekordcrateResult<T>> + '_ {
+        self.iter_rows(T::PAGE_TYPE).filter_map(|row| match row {
+            Ok(row) => T::from_row(row).map(Ok),
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Like [`Database::iter_present_rows`], but also returns the index of the page that each
+    /// row is stored on.
+    ///
+    /// This is mostly useful for debugging the page layout, e.g. to correlate rows with the
+    /// output of the `dump-pdb` command.
+    pub fn iter_rows_with_page<T: TableRow>(
+        &self,
+    ) -> impl Iterator<Item = RekordcrateResult<(PageIndex, T)>> + '_ {
+        let page_type = T::PAGE_TYPE;
+        self.header
+            .tables
+            .iter()
+            .filter(move |table| table.page_type == page_type)
+            .flat_map(|table| self.pages(table))
+            .flat_map(|page| {
+                let rows: Vec<RekordcrateResult<(PageIndex, Row)>> = match page {
+                    Ok(page) => {
+                        let page_index = page.page_index.clone();
+                        page.into_rows()
+                            .map(|row| Ok((page_index.clone(), row)))
+                            .collect()
+                    }
+                    Err(err) => vec![Err(err)],
+                };
+                rows
+            })
+            .filter_map(|row| match row {
+                Ok((page_index, row)) => T::from_row(row).map(|row| Ok((page_index, row))),
+                Err(err) => Some(Err(err)),
+            })
+    }
+
+    /// Load all pages of the tables with the given page type into memory.
+    ///
+    /// The returned [`LoadedTable`] owns the pages and allows iterating over references to its
+    /// rows, so that callers can hold `&Row` without cloning.
+    pub fn load_table(&self, page_type: PageType) -> RekordcrateResult<LoadedTable> {
+        let pages = self
+            .header
+            .tables
+            .iter()
+            .filter(|table| table.page_type == page_type)
+            .flat_map(|table| self.pages(table))
+            .collect::<RekordcrateResult<Vec<Page>>>()?;
+        Ok(LoadedTable { page_type, pages })
+    }
+
+    /// Apply `update` to all rows of the tables with the given page type and rewrite the pages
+    /// that contain modified rows in memory.
+    ///
+    /// `update` returns `true` if it modified the row. The heap of each affected page is rewritten
+    /// (including the page sizes and row offsets). Returns the number of modified rows.
+    fn update_rows(
+        &mut self,
+        page_type: PageType,
+        mut update: impl FnMut(&mut Row) -> RekordcrateResult<bool>,
+    ) -> RekordcrateResult<usize> {
+        let mut modified_pages = vec![];
+        let mut num_modified_rows = 0;
+        for table in &self.header.tables {
+            if table.page_type != page_type {
+                continue;
+            }
+            for page in self.pages(table) {
+                let mut page = page?;
+                let mut page_modified = false;
+                for row in page.rows_mut() {
+                    if update(row)? {
+                        num_modified_rows += 1;
+                        page_modified = true;
+                    }
+                }
+                if page_modified {
+                    modified_pages.push(page);
+                }
+            }
+        }
+        if modified_pages.is_empty() {
+            return Ok(0);
+        }
+
+        let page_size = self.header.page_size;
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        for mut page in modified_pages {
+            let page_offset = usize::try_from(page.page_index.offset(page_size))
+                .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+            let page_data = data
+                .get_mut(page_offset..page_offset + page_size as usize)
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+            page.rewrite_heap(page_size, page_data)?;
+        }
+        self.reader = RefCell::new(Box::new(Cursor::new(data)));
+        self.track_playlists.take();
+        Ok(num_modified_rows)
+    }
+
+    /// Append `row` to the table with the given page type and rewrite the affected pages in
+    /// memory.
+    ///
+    /// The row is added to the last page of the table. If that page is full (or the table has no
+    /// data pages yet), the reserved empty page of the table (`empty_candidate`) is turned into
+    /// a new data page that is appended to the table, and the next unused page of the file is
+    /// reserved instead, like Rekordbox does.
+    ///
+    /// **Note:** The entries of the index page (the first page of the table) are not updated,
+    /// because their meaning is not fully understood. Exports created by Rekordbox also contain
+    /// data pages that are not listed in the index page, so this does not seem to be a problem.
+    fn insert_row(&mut self, page_type: PageType, row: Row) -> RekordcrateResult<()> {
+        let page_size = self.header.page_size;
+        let page_range = |page_index: &PageIndex| -> RekordcrateResult<std::ops::Range<usize>> {
+            let start = usize::try_from(page_index.offset(page_size))
+                .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+            Ok(start..start + page_size as usize)
+        };
+        let table_index = self
+            .header
+            .tables
+            .iter()
+            .position(|table| table.page_type == page_type)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Database does not contain a {:?} table", page_type),
+                )
+            })?;
+        let table = self.header.tables[table_index].clone();
+
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        let mut last_page = self.read_page(&table.last_page)?;
+        let last_page_range = page_range(&table.last_page)?;
+        if last_page.has_data()
+            && last_page.insert_row(row.clone(), page_size, &mut data[last_page_range.clone()])?
+        {
+            self.reader = RefCell::new(Box::new(Cursor::new(data)));
+            self.track_playlists.take();
+            return Ok(());
+        }
+
+        // Turn the reserved empty page into a new data page and reserve the next unused page.
+        let new_page_index = PageIndex(table.empty_candidate);
+        let empty_candidate = self.header.next_unused_page().0;
+        let new_page_range = page_range(&new_page_index)?;
+        if data.len() < new_page_range.end {
+            data.resize(new_page_range.end, 0);
+        }
+        let page_flags = if last_page.has_data() {
+            last_page.page_flags()
+        } else {
+            PageFlags::DATA_PAGE
+        };
+        let mut writer = Cursor::new(&mut data[new_page_range.clone()]);
+        (0u32, &new_page_index, page_type, empty_candidate).write_le(&mut writer)?;
+        (last_page.unknown1, 0u32, [0u8, 0, 0], page_flags).write_le(&mut writer)?;
+        // Free size, used size, unknown fields and number of rows (16-bit version).
+        let free_size = u16::try_from(page_size - Page::HEADER_SIZE)
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+        (free_size, 0u16, 1u16, 0u16, 0u16, 0u16).write_le(&mut writer)?;
+        let mut reader = Cursor::new(&data);
+        reader.seek(SeekFrom::Start(new_page_index.offset(page_size)))?;
+        let mut new_page =
+            Page::read_options(&mut reader, Endian::Little, (page_size, self.database_type))?;
+        if !new_page.insert_row(row, page_size, &mut data[new_page_range])? {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Row does not fit into an empty page",
+            )
+            .into());
+        }
+
+        // Link the new page to the table. Nothing can fail from here on, so the page that is
+        // reserved as the new empty candidate is allocated only now.
+        let allocated_page = self.header.allocate_page();
+        debug_assert_eq!(allocated_page.0, empty_candidate);
+        let last_page_data = &mut data[last_page_range];
+        last_page_data[12..16].copy_from_slice(&new_page_index.0.to_le_bytes());
+        if !last_page.has_data() {
+            let first_data_page = FIRST_DATA_PAGE_OFFSET..FIRST_DATA_PAGE_OFFSET + 4;
+            if last_page_data[first_data_page.clone()] == NO_PAGE.to_le_bytes() {
+                last_page_data[first_data_page].copy_from_slice(&new_page_index.0.to_le_bytes());
+            }
+        }
+        last_page.next_page = new_page_index.clone();
+        let table = &mut self.header.tables[table_index];
+        table.last_page = new_page_index;
+        table.empty_candidate = empty_candidate;
+        self.header.write(&mut Cursor::new(&mut data[..]))?;
+
+        self.reader = RefCell::new(Box::new(Cursor::new(data)));
+        self.track_playlists.take();
+        Ok(())
+    }
+
+    /// Change the user-defined name of the color with the given index.
+    ///
+    /// Returns `false` if the database does not contain a row for the color.
+    pub fn set_color_name(&mut self, color: ColorIndex, name: &str) -> RekordcrateResult<bool> {
+        let num_modified_rows = self.update_rows(PageType::Colors, |row| match row {
+            Row::Color(row) if row.color() == color => {
+                row.set_name(name)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        Ok(num_modified_rows > 0)
+    }
+
+    /// Set the user rating (0 to 5 stars) of the track with the given ID.
+    ///
+    /// Returns `false` if the track does not exist. Like [`Track::set_rating`], this fails with a
+    /// [`RekordcrateError::InvalidRating`] error if the rating is greater than 5.
+    pub fn set_track_rating(&mut self, id: TrackId, rating: u8) -> RekordcrateResult<bool> {
+        self.update_track(id, |track| track.set_rating(rating))
+    }
+
+    /// Assign a color to the track with the given ID.
+    ///
+    /// Returns `false` if the track does not exist.
+    pub fn set_track_color(&mut self, id: TrackId, color: ColorIndex) -> RekordcrateResult<bool> {
+        self.update_track(id, |track| {
+            track.set_color(color.clone());
+            Ok(())
+        })
+    }
+
+    /// Change the title of the track with the given ID.
+    ///
+    /// Since the strings of a track row are stored back to back, the offsets of the strings after
+    /// the title and the row offsets of the subsequent rows on the page are recomputed. Returns
+    /// `false` if the track does not exist, and a [`RekordcrateError::ParseError`] with a
+    /// [`binrw::Error::AssertFail`] if the page has no room for the new title.
+    pub fn set_track_title(&mut self, id: TrackId, title: &str) -> RekordcrateResult<bool> {
+        self.update_track(id, |track| Ok(track.set_title(title)?))
+    }
+
+    /// Set the artist of the track with the given ID to the artist with the given name.
+    ///
+    /// If the database does not contain an artist with that name yet, a new artist row is
+    /// inserted into the artists table. Returns `false` without modifying the database if the track
+    /// does not exist.
+    pub fn set_track_artist(&mut self, id: TrackId, name: &str) -> RekordcrateResult<bool> {
+        let mut track_exists = false;
+        for track in self.iter_present_rows::<Track>() {
+            if track?.id() == id {
+                track_exists = true;
+                break;
+            }
+        }
+        if !track_exists {
+            return Ok(false);
+        }
+
+        let mut artist_id = None;
+        let mut max_id = 0;
+        for artist in self.iter_present_rows::<Artist>() {
+            let artist = artist?;
+            max_id = max_id.max(artist.id().0);
+            if artist_id.is_none() && artist.name()? == name {
+                artist_id = Some(artist.id());
+            }
+        }
+        let artist_id = match artist_id {
+            Some(artist_id) => artist_id,
+            None => {
+                let artist_id = ArtistId(max_id + 1);
+                let artist = Artist::new(artist_id, name)?;
+                self.insert_row(PageType::Artists, Row::Artist(artist))?;
+                artist_id
+            }
+        };
+        self.update_track(id, |track| {
+            track.set_artist_id(artist_id);
+            Ok(())
+        })
+    }
+
+    /// Apply `update` to the track with the given ID and return `true` if it exists.
+    fn update_track(
+        &mut self,
+        id: TrackId,
+        mut update: impl FnMut(&mut Track) -> RekordcrateResult<()>,
+    ) -> RekordcrateResult<bool> {
+        let num_modified_rows = self.update_rows(PageType::Tracks, |row| match row {
+            Row::Track(track) if track.id() == id => {
+                update(track)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        Ok(num_modified_rows > 0)
+    }
+
+    /// Rename the "My Tag" tag or tag category with the given ID.
+    ///
+    /// This is only supported for extended databases (`exportExt.pdb`). Returns `false` if the
+    /// database does not contain the tag.
+    pub fn set_tag_name(&mut self, id: TagId, name: &str) -> RekordcrateResult<bool> {
+        if self.database_type != DatabaseType::Ext {
+            return Ok(false);
+        }
+        let num_modified_rows = self.update_rows(ExtPageType::Tags.into(), |row| match row {
+            Row::Ext(ExtRow::Tag(tag)) if tag.id() == id => {
+                tag.set_name(name)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        Ok(num_modified_rows > 0)
+    }
+
+    /// Returns the children of the playlist folder with the given ID (use `PlaylistTreeNodeId(0)`
+    /// for the root folder), in the order in which they are shown on the device.
+    pub fn playlist_children(
+        &self,
+        parent_id: PlaylistTreeNodeId,
+    ) -> RekordcrateResult<Vec<PlaylistTreeNode>> {
+        let mut children = vec![];
+        for row in self.iter_rows(PageType::PlaylistTree) {
+            if let Row::PlaylistTreeNode(node) = row? {
+                if node.parent_id == parent_id {
+                    children.push(node);
+                }
+            }
+        }
+        children.sort_by_key(PlaylistTreeNode::sort_order);
+        Ok(children)
+    }
+
+    /// Returns the entries of the playlist with the given ID, ordered by their index.
+    ///
+    /// Returns a [`RekordcrateError::DuplicateEntryIndex`] error if the playlist contains more
+    /// than one entry with the same index, because the track order would be ambiguous.
+    pub fn playlist_entries(
+        &self,
+        id: PlaylistTreeNodeId,
+    ) -> RekordcrateResult<Vec<PlaylistEntry>> {
+        let mut entries = vec![];
+        for row in self.iter_rows(PageType::PlaylistEntries) {
+            if let Row::PlaylistEntry(entry) = row? {
+                if entry.playlist_id() == id {
+                    entries.push(entry);
+                }
+            }
+        }
+        sort_playlist_entries(id, &mut entries)?;
+        Ok(entries)
+    }
+
+    /// Total duration of the tracks in the playlist with the given ID (see [`Track::duration`]).
+    ///
+    /// Tracks that appear multiple times in the playlist are counted multiple times. Entries that
+    /// reference a track that doesn't exist are skipped and counted in
+    /// [`PlaylistDuration::num_missing_tracks`]. If `recursive` is `true` and the ID belongs to a
+    /// folder (or is `PlaylistTreeNodeId(0)` for the root folder), the tracks of all playlists
+    /// in that folder and its subfolders are included. Otherwise, only the entries of the node
+    /// itself are included, i.e. the duration of a folder is zero.
+    pub fn playlist_duration(
+        &self,
+        id: PlaylistTreeNodeId,
+        recursive: bool,
+    ) -> RekordcrateResult<PlaylistDuration> {
+        let mut playlist_ids = HashSet::from([id]);
+        if recursive {
+            let mut children: HashMap<PlaylistTreeNodeId, Vec<PlaylistTreeNodeId>> = HashMap::new();
+            for node in self.iter_present_rows::<PlaylistTreeNode>() {
+                let node = node?;
+                children.entry(node.parent_id).or_default().push(node.id);
+            }
+            let mut queue = vec![id];
+            while let Some(parent_id) = queue.pop() {
+                for &child_id in children.get(&parent_id).into_iter().flatten() {
+                    // Skip nodes that have already been visited, in case the tree has cycles.
+                    if playlist_ids.insert(child_id) {
+                        queue.push(child_id);
+                    }
+                }
+            }
+        }
+
+        let mut durations = HashMap::new();
+        for track in self.iter_present_rows::<Track>() {
+            let track = track?;
+            durations.insert(track.id(), track.duration());
+        }
+
+        let mut playlist_duration = PlaylistDuration::default();
+        for entry in self.iter_present_rows::<PlaylistEntry>() {
+            let entry = entry?;
+            if !playlist_ids.contains(&entry.playlist_id()) {
+                continue;
+            }
+            match durations.get(&entry.track_id()) {
+                Some(&duration) => {
+                    playlist_duration.duration += Duration::from_secs(duration.into());
+                    playlist_duration.num_tracks += 1;
+                }
+                None => playlist_duration.num_missing_tracks += 1,
+            }
+        }
+        Ok(playlist_duration)
+    }
+
+    /// Returns the playlists whose entry indices are not contiguous, ordered by playlist ID.
+    ///
+    /// Some exports contain playlists with gaps or duplicates in the entry indices, which causes
+    /// Rekordbox to hide some of their tracks. This only reports the anomalies, see
+    /// [`Database::normalize_playlist_entries`] for repairing them.
+    pub fn check_playlist_entries(&self) -> RekordcrateResult<Vec<PlaylistEntryIssues>> {
+        let mut indices: HashMap<PlaylistTreeNodeId, Vec<u32>> = HashMap::new();
+        for entry in self.iter_present_rows::<PlaylistEntry>() {
+            let entry = entry?;
+            indices
+                .entry(entry.playlist_id())
+                .or_default()
+                .push(entry.entry_index());
+        }
+
+        let mut issues = vec![];
+        for (playlist_id, mut indices) in indices {
+            indices.sort_unstable();
+            let mut duplicate_indices: Vec<u32> = indices
+                .windows(2)
+                .filter(|pair| pair[0] == pair[1])
+                .map(|pair| pair[0])
+                .collect();
+            duplicate_indices.dedup();
+            let missing_indices: Vec<u32> = (1..)
+                .take(indices.len())
+                .filter(|index| indices.binary_search(index).is_err())
+                .collect();
+            if !duplicate_indices.is_empty() || !missing_indices.is_empty() {
+                issues.push(PlaylistEntryIssues {
+                    playlist_id,
+                    duplicate_indices,
+                    missing_indices,
+                });
+            }
+        }
+        issues.sort_by_key(|issues| issues.playlist_id.0);
+        Ok(issues)
+    }
+
+    /// Renumber the entries of the playlist with the given ID to `1`, `2`, `3`, etc., removing
+    /// any gaps and duplicates in their indices.
+    ///
+    /// The entries keep their relative order; entries that share an index are kept in the order
+    /// in which they are stored. Returns the number of modified entries.
+    pub fn normalize_playlist_entries(
+        &mut self,
+        id: PlaylistTreeNodeId,
+    ) -> RekordcrateResult<usize> {
+        let mut entries = vec![];
+        for entry in self.iter_present_rows::<PlaylistEntry>() {
+            let entry = entry?;
+            if entry.playlist_id() == id {
+                entries.push(entry.entry_index());
+            }
+        }
+        let mut positions: Vec<usize> = (0..entries.len()).collect();
+        positions.sort_by_key(|&position| entries[position]);
+        let mut new_indices = vec![0; entries.len()];
+        for (new_index, position) in (1..).zip(positions) {
+            new_indices[position] = new_index;
+        }
+
+        let mut new_indices = new_indices.into_iter();
+        self.update_rows(PageType::PlaylistEntries, |row| match row {
+            Row::PlaylistEntry(entry) if entry.playlist_id == id => {
+                let new_index = new_indices.next().ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Entries of playlist {} changed while renumbering", id.0),
+                    )
+                })?;
+                let modified = entry.entry_index != new_index;
+                entry.entry_index = new_index;
+                Ok(modified)
+            }
+            _ => Ok(false),
+        })
+    }
+
+    /// Returns the tracks of all playlists, grouped by playlist and ordered by entry index.
+    ///
+    /// Each item consists of the [full path](Self::playlist_full_path) of the playlist, the index
+    /// of the entry and the track. The playlists are visited depth-first in the order in which
+    /// they are shown on the device (see [`Database::playlist_children`]); nodes that are not
+    /// reachable from the root folder are skipped. Entries that reference tracks that don't exist
+    /// in the database are skipped with a warning. Like [`Database::playlist_entries`], this fails
+    /// if a playlist contains more than one entry with the same index.
+    pub fn all_playlist_tracks_ordered(&self) -> RekordcrateResult<Vec<(String, u32, Track)>> {
+        let mut tracks = HashMap::new();
+        for track in self.iter_present_rows::<Track>() {
+            let track = track?;
+            tracks.insert(track.id(), track);
+        }
+        let mut entries: HashMap<PlaylistTreeNodeId, Vec<PlaylistEntry>> = HashMap::new();
+        for entry in self.iter_present_rows::<PlaylistEntry>() {
+            let entry = entry?;
+            entries.entry(entry.playlist_id()).or_default().push(entry);
+        }
+        let table = self.load_table(PageType::PlaylistTree)?;
+        let mut children: HashMap<PlaylistTreeNodeId, Vec<&PlaylistTreeNode>> = HashMap::new();
+        for row in table.iter_rows_ref() {
+            if let Row::PlaylistTreeNode(node) = row {
+                children.entry(node.parent_id).or_default().push(node);
+            }
+        }
+        for nodes in children.values_mut() {
+            nodes.sort_by_key(|node| node.sort_order());
+        }
+
+        let mut items = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = vec![(PlaylistTreeNodeId(0), String::new())];
+        while let Some((id, path)) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(mut playlist_entries) = entries.remove(&id) {
+                sort_playlist_entries(id, &mut playlist_entries)?;
+                for entry in playlist_entries {
+                    let Some(track) = tracks.get(&entry.track_id()) else {
+                        log::warn!(
+                            "Skipping entry {} of playlist {}: track {} does not exist",
+                            entry.entry_index(),
+                            id.0,
+                            entry.track_id().0
+                        );
+                        continue;
+                    };
+                    items.push((path.clone(), entry.entry_index(), track.clone()));
+                }
+            }
+            for node in children.get(&id).into_iter().flatten().rev() {
+                let name = node.name.clone().into_string()?;
+                let child_path = if path.is_empty() {
+                    name
+                } else {
+                    format!("{path}/{name}")
+                };
+                stack.push((node.id, child_path));
+            }
+        }
+        Ok(items)
+    }
+
+    /// Change the order in which the children of the playlist folder with the given ID are shown
+    /// on the device.
+    ///
+    /// `new_order` must contain the IDs of all children of the folder exactly once. Their
+    /// `sort_order` values are rewritten to `0`, `1`, `2`, etc. (like Rekordbox numbers them)
+    /// according to their position in `new_order`.
+    pub fn reorder_playlists(
+        &mut self,
+        parent_id: PlaylistTreeNodeId,
+        new_order: &[PlaylistTreeNodeId],
+    ) -> RekordcrateResult<()> {
+        let children: HashSet<PlaylistTreeNodeId> = self
+            .playlist_children(parent_id)?
+            .iter()
+            .map(|node| node.id)
+            .collect();
+        let new_positions: HashMap<PlaylistTreeNodeId, u32> =
+            new_order.iter().copied().zip(0..).collect();
+        if new_positions.len() != new_order.len()
+            || children.len() != new_order.len()
+            || !new_order.iter().all(|id| children.contains(id))
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "New order must contain each of the {} children of playlist folder {} exactly once",
+                    children.len(),
+                    parent_id.0
+                ),
+            )
+            .into());
+        }
+
+        self.update_rows(PageType::PlaylistTree, |row| match row {
+            Row::PlaylistTreeNode(node) if node.parent_id == parent_id => {
+                let sort_order = new_positions[&node.id];
+                let modified = node.sort_order() != sort_order;
+                node.set_sort_order(sort_order);
+                Ok(modified)
+            }
+            _ => Ok(false),
+        })?;
+        Ok(())
+    }
+
+    /// Create a new playlist (or folder, if `is_folder` is `true`) with the given name in the
+    /// playlist folder with the given ID, and return the ID of the new node.
+    ///
+    /// Use `PlaylistTreeNodeId(0)` as `parent_id` to create the node in the root folder. The new
+    /// node gets the next free ID and is placed after the existing children of the folder. The
+    /// row is appended to the last page of the playlist tree table (or to a new page if that one
+    /// is full).
+    pub fn create_playlist(
+        &mut self,
+        parent_id: PlaylistTreeNodeId,
+        name: &str,
+        is_folder: bool,
+    ) -> RekordcrateResult<PlaylistTreeNodeId> {
+        let mut max_id = 0;
+        let mut parent_is_folder = parent_id == PlaylistTreeNodeId(0);
+        let mut sort_order = 0;
+        for node in self.iter_present_rows::<PlaylistTreeNode>() {
+            let node = node?;
+            max_id = max_id.max(node.id.0);
+            if node.id == parent_id {
+                parent_is_folder = node.is_folder();
+            }
+            if node.parent_id == parent_id {
+                sort_order = sort_order.max(node.sort_order() + 1);
+            }
+        }
+        if !parent_is_folder {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Playlist tree node {} is not a folder", parent_id.0),
+            )
+            .into());
+        }
+
+        let id = PlaylistTreeNodeId(max_id + 1);
+        let node = PlaylistTreeNode {
+            parent_id,
+            unknown: 0,
+            sort_order,
+            id,
+            node_is_folder: u32::from(is_folder),
+            name: DeviceSQLString::new(name.to_string())?,
+        };
+        self.insert_row(PageType::PlaylistTree, Row::PlaylistTreeNode(node))?;
+        Ok(id)
+    }
+
+    /// Copy the track with the given ID and the rows it references (artists, album, artwork,
+    /// genre, key and label) from `source` into this database.
+    ///
+    /// Rows are matched like in [`MergedCollection::copy_track_from`]: rows that already exist in
+    /// this database are reused, all other rows are appended with a new ID if their ID is already
+    /// taken. Either all rows are added or, if one of them can't be inserted, none of them.
+    /// Returns the ID of the track in this database, or `None` if the track does not exist in
+    /// `source`.
+    pub fn copy_track_from(
+        &mut self,
+        source: &Database,
+        id: TrackId,
+    ) -> RekordcrateResult<Option<TrackId>> {
+        let mut collection = MergedCollection {
+            tracks: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            artists: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            albums: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            artwork: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            genres: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            keys: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            labels: self.iter_present_rows().collect::<RekordcrateResult<_>>()?,
+            ..MergedCollection::default()
+        };
+        let num_tracks = collection.tracks.len();
+        let num_artists = collection.artists.len();
+        let num_albums = collection.albums.len();
+        let num_artwork = collection.artwork.len();
+        let num_genres = collection.genres.len();
+        let num_keys = collection.keys.len();
+        let num_labels = collection.labels.len();
+        let Some(track_id) = collection.copy_track_from(source, id)? else {
+            return Ok(None);
+        };
+
+        // The referenced rows are inserted before the track, so that the track never references
+        // rows that don't exist.
+        let new_rows: Vec<(PageType, Row)> = collection
+            .artists
+            .split_off(num_artists)
+            .into_iter()
+            .map(|artist| (PageType::Artists, Row::Artist(artist)))
+            .chain(
+                collection
+                    .albums
+                    .split_off(num_albums)
+                    .into_iter()
+                    .map(|album| (PageType::Albums, Row::Album(album))),
+            )
+            .chain(
+                collection
+                    .artwork
+                    .split_off(num_artwork)
+                    .into_iter()
+                    .map(|artwork| (PageType::Artwork, Row::Artwork(artwork))),
+            )
+            .chain(
+                collection
+                    .genres
+                    .split_off(num_genres)
+                    .into_iter()
+                    .map(|genre| (PageType::Genres, Row::Genre(genre))),
+            )
+            .chain(
+                collection
+                    .keys
+                    .split_off(num_keys)
+                    .into_iter()
+                    .map(|key| (PageType::Keys, Row::Key(key))),
+            )
+            .chain(
+                collection
+                    .labels
+                    .split_off(num_labels)
+                    .into_iter()
+                    .map(|label| (PageType::Labels, Row::Label(label))),
+            )
+            .chain(
+                collection
+                    .tracks
+                    .split_off(num_tracks)
+                    .into_iter()
+                    .map(|track| (PageType::Tracks, Row::Track(track))),
+            )
+            .collect();
+
+        let header = self.header.clone();
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        for (page_type, row) in new_rows {
+            if let Err(error) = self.insert_row(page_type, row) {
+                self.header = header;
+                self.reader = RefCell::new(Box::new(Cursor::new(data)));
+                self.track_playlists.take();
+                return Err(error);
+            }
+        }
+        Ok(Some(track_id))
+    }
+
+    /// Full path of the playlist or folder with the given ID, consisting of the names of all
+    /// parent folders and the node itself, separated by `/` (e.g. `Folder/Subfolder/Playlist`).
+    ///
+    /// Slashes inside of names are not escaped. Returns `None` if there is no node with that ID,
+    /// if one of its parents is missing, or if the parent links contain a cycle.
+    pub fn playlist_full_path(&self, id: PlaylistTreeNodeId) -> RekordcrateResult<Option<String>> {
+        let table = self.load_table(PageType::PlaylistTree)?;
+        let nodes: HashMap<PlaylistTreeNodeId, &PlaylistTreeNode> = table
+            .iter_rows_ref()
+            .filter_map(|row| match row {
+                Row::PlaylistTreeNode(node) => Some((node.id, node)),
+                _ => None,
+            })
+            .collect();
+        Ok(playlist_path(&nodes, id)?)
+    }
+
+    /// Returns the playlists that contain the track with the given ID, along with their
+    /// [full paths](Self::playlist_full_path), ordered by path.
+    ///
+    /// Each playlist is only returned once, even if it contains the track multiple times.
+    /// Playlists whose path can't be determined (e.g. because a parent folder is missing) are
+    /// skipped. The index of which tracks appear in which playlists is built on the first call
+    /// and reused afterwards, so that looking up many tracks only scans the playlist entries
+    /// once.
+    pub fn playlists_containing(
+        &self,
+        id: TrackId,
+    ) -> RekordcrateResult<Vec<(PlaylistTreeNodeId, String)>> {
+        if self.track_playlists.borrow().is_none() {
+            let mut track_playlists: HashMap<TrackId, Vec<PlaylistTreeNodeId>> = HashMap::new();
+            for row in self.iter_rows(PageType::PlaylistEntries) {
+                if let Row::PlaylistEntry(entry) = row? {
+                    let playlists = track_playlists.entry(entry.track_id()).or_default();
+                    if !playlists.contains(&entry.playlist_id()) {
+                        playlists.push(entry.playlist_id());
+                    }
+                }
+            }
+            *self.track_playlists.borrow_mut() = Some(track_playlists);
+        }
+        let playlist_ids = self
+            .track_playlists
+            .borrow()
+            .as_ref()
+            .and_then(|track_playlists| track_playlists.get(&id).cloned())
+            .unwrap_or_default();
+        if playlist_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let table = self.load_table(PageType::PlaylistTree)?;
+        let nodes: HashMap<PlaylistTreeNodeId, &PlaylistTreeNode> = table
+            .iter_rows_ref()
+            .filter_map(|row| match row {
+                Row::PlaylistTreeNode(node) => Some((node.id, node)),
+                _ => None,
+            })
+            .collect();
+        let mut playlists = vec![];
+        for playlist_id in playlist_ids {
+            if let Some(path) = playlist_path(&nodes, playlist_id)? {
+                playlists.push((playlist_id, path));
+            }
+        }
+        playlists.sort_by(|(_, a), (_, b)| a.cmp(b));
+        Ok(playlists)
+    }
+
+    /// Returns the keys whose second ID field does not match their row ID (see [`Key::id2`]).
+    ///
+    /// The second ID field is assumed to be a copy of the row ID, which holds for all known
+    /// exports (i.e. this returns an empty list). If an export contradicts the assumption, the
+    /// returned rows contain both values.
+    pub fn key_id_mismatches(&self) -> RekordcrateResult<Vec<Key>> {
+        let mut keys = vec![];
+        for key in self.iter_present_rows::<Key>() {
+            let key = key?;
+            if !key.has_matching_ids() {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Iterate over the tracks whose tempo lies between `min` and `max` BPM (inclusive), in the
+    /// order in which they are stored in the tracks table.
+    ///
+    /// Tracks without a valid tempo (see [`Track::bpm`]), e.g. because they have not been
+    /// analyzed yet, are never returned.
+    ///
+    /// ```
+    /// # pub fn main() -> rekordcrate::Result<()> {
+    /// use rekordcrate::pdb::io::Database;
+    ///
+    /// let db = Database::open("data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb")?;
+    /// for track in db.tracks_in_bpm_range(125.0, 130.0) {
+    ///     println!("{}", track?.title()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tracks_in_bpm_range(
+        &self,
+        min: f64,
+        max: f64,
+    ) -> impl Iterator<Item = RekordcrateResult<Track>> + '_ {
+        self.iter_present_rows::<Track>()
+            .filter(move |track| match track {
+                Ok(track) => track.bpm().is_ok_and(|bpm| (min..=max).contains(&bpm.0)),
+                Err(_) => true,
+            })
+    }
+
+    /// Returns all genres, in the order in which they are stored in the database or, if `sorted`
+    /// is `true`, sorted by name (ignoring case and accents).
+    ///
+    /// The genres table is small, so the rows are collected right away (e.g. to populate a
+    /// filter menu).
+    pub fn genres(&self, sorted: bool) -> RekordcrateResult<Vec<Genre>> {
+        self.collect_rows(sorted, Genre::name)
+    }
+
+    /// Returns all musical keys, in the order in which they are stored in the database or, if
+    /// `sorted` is `true`, sorted by name (see [`Database::genres`]).
+    pub fn keys(&self, sorted: bool) -> RekordcrateResult<Vec<Key>> {
+        self.collect_rows(sorted, Key::name)
+    }
+
+    /// Returns all labels, in the order in which they are stored in the database or, if `sorted`
+    /// is `true`, sorted by name (see [`Database::genres`]).
+    pub fn labels(&self, sorted: bool) -> RekordcrateResult<Vec<Label>> {
+        self.collect_rows(sorted, Label::name)
+    }
+
+    /// Collect the present rows of type `T`, optionally sorted by the normalized result of `name`
+    /// (see [`search_form`]).
+    fn collect_rows<T: TableRow>(
+        &self,
+        sorted: bool,
+        name: impl Fn(&T) -> Result<String, StringError>,
+    ) -> RekordcrateResult<Vec<T>> {
+        let rows = self
+            .iter_present_rows::<T>()
+            .collect::<RekordcrateResult<Vec<T>>>()?;
+        if !sorted {
+            return Ok(rows);
+        }
+        let mut rows = rows
+            .into_iter()
+            .map(|row| Ok((search_form(&name(&row)?), row)))
+            .collect::<RekordcrateResult<Vec<(String, T)>>>()?;
+        rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    /// Returns the browse categories (e.g. `GENRE` or `ARTIST`) from the columns table, in the
+    /// order in which they appear in the browse menu.
+    ///
+    /// The categories are ordered by ID. Each row also contains a field that might encode
+    /// visibility or a custom sort order, but in all known exports it just contains the ID with
+    /// bit 7 set (`0x80` for `GENRE`, `0x81` for `ARTIST`, ...), so it is not decoded (and
+    /// preserved as-is when writing rows).
+    pub fn browse_categories(&self) -> RekordcrateResult<Vec<ColumnEntry>> {
+        let mut categories = self
+            .iter_rows(PageType::Columns)
+            .filter_map(|row| match row {
+                Ok(Row::ColumnEntry(entry)) => Some(Ok(entry)),
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            })
+            .collect::<RekordcrateResult<Vec<_>>>()?;
+        categories.sort_by_key(ColumnEntry::id);
+        Ok(categories)
+    }
+}
+
+/// Sort the entries of the playlist with the given ID by their index and check that no index is
+/// used more than once.
+fn sort_playlist_entries(
+    id: PlaylistTreeNodeId,
+    entries: &mut [PlaylistEntry],
+) -> RekordcrateResult<()> {
+    entries.sort_by_key(PlaylistEntry::entry_index);
+    match entries
+        .windows(2)
+        .find(|entries| entries[0].entry_index() == entries[1].entry_index())
+    {
+        Some(entries) => Err(RekordcrateError::DuplicateEntryIndex {
+            playlist_id: id,
+            entry_index: entries[0].entry_index(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Build the path of a playlist tree node by following the `parent_id` links.
+///
+/// Returns `None` if a node is missing or if the links contain a cycle.
+fn playlist_path(
+    nodes: &HashMap<PlaylistTreeNodeId, &PlaylistTreeNode>,
+    id: PlaylistTreeNodeId,
+) -> Result<Option<String>, StringError> {
+    let mut names = vec![];
+    let mut visited = HashSet::new();
+    let mut id = id;
+    while id.0 != 0 {
+        if !visited.insert(id) {
+            return Ok(None);
+        }
+        let Some(node) = nodes.get(&id) else {
+            return Ok(None);
+        };
+        names.push(node.name.clone().into_string()?);
+        id = node.parent_id;
+    }
+    names.reverse();
+    Ok(Some(names.join("/")))
+}
+
+/// The pages of a table that have been loaded into memory, returned by [`Database::load_table`].
+///
+/// The rows can be borrowed for as long as the `LoadedTable` is alive, which avoids cloning them
+/// for read-only access.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::pdb::{io::Database, PageType, Row};
+///
+/// let db = Database::open("data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb")?;
+/// let table = db.load_table(PageType::Artists)?;
+/// let names: Vec<String> = table
+///     .iter_rows_ref()
+///     .filter_map(|row| match row {
+///         Row::Artist(artist) => artist.name().ok(),
+///         _ => None,
+///     })
+///     .collect();
+/// assert_eq!(names, vec!["Loopmasters"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct LoadedTable {
+    /// The page type of the table.
+    page_type: PageType,
+    /// The pages of the table, in order.
+    pages: Vec<Page>,
+}
+
+impl LoadedTable {
+    /// The page type of the table.
+    #[must_use]
+    pub fn page_type(&self) -> PageType {
+        self.page_type
+    }
+
+    /// The pages of the table, in order.
+    #[must_use]
+    pub fn pages(&self) -> &[Page] {
+        &self.pages
+    }
+
+    /// Iterate over references to the present rows of all pages, in order.
+    ///
+    /// The references are valid for as long as the `LoadedTable` is borrowed.
+    pub fn iter_rows_ref(&self) -> impl Iterator<Item = &Row> + '_ {
+        self.pages.iter().flat_map(Page::rows)
+    }
+
+    /// Consume the table and iterate over the owned rows, in order.
+    pub fn into_rows(self) -> impl Iterator<Item = Row> {
+        self.pages.into_iter().flat_map(Page::into_rows)
+    }
+}
+
+impl fmt::Debug for Database {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Database")
+            .field("header", &self.header)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Iterator over the pages of a table, returned by [`Database::pages`].
+#[derive(Debug)]
+pub struct Pages<'a> {
+    /// The database to read the pages from.
+    database: &'a Database,
+    /// Index of the next page to read, or `None` if the iterator is exhausted.
+    next_page: Option<PageIndex>,
+    /// Index of the last page of the table.
+    last_page: PageIndex,
+    /// Pages that have already been read, to detect cycles in the linked list of pages.
+    visited: HashSet<PageIndex>,
+}
+
+impl Iterator for Pages<'_> {
+    type Item = RekordcrateResult<Page>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let page_index = self.next_page.take()?;
+        if !self.visited.insert(page_index.clone()) {
+            return Some(Err(Database::cyclic_page_list_error(
+                &page_index,
+                self.database.header.page_size,
+            )));
+        }
+        let page = match self.database.read_page(&page_index) {
+            Ok(page) => page,
+            Err(err) => return Some(Err(err)),
+        };
+        if page.page_index != self.last_page {
+            self.next_page = Some(page.next_page.clone());
+        }
+        Some(Ok(page))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pdb::{
+        string::DeviceSQLString, AudioProperties, Color, FileType, InvalidTempoError, Tag,
+    };
+
+    const DEMO_TRACKS_PDB: &str = "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb";
+    const DEMO_TRACKS_EXT_PDB: &str =
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/exportExt.pdb";
+    const NUM_ROWS_PDB: &str = "data/pdb/num_rows/export.pdb";
+    const EMPTY_PDB: &str = "data/complete_export/empty/PIONEER/rekordbox/export.pdb";
+
+    #[test]
+    fn open_demo_tracks() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert_eq!(db.header().page_size, 4096);
+        assert_eq!(db.header().tables.len(), 20);
+
+        let tracks: Vec<Row> = db
+            .iter_rows(PageType::Tracks)
+            .collect::<RekordcrateResult<_>>()
+            .unwrap();
+        assert!(!tracks.is_empty());
+        assert!(tracks.iter().all(|row| matches!(row, Row::Track(_))));
+    }
+
+    #[test]
+    fn info() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert_eq!(
+            db.info(),
+            DatabaseInfo {
+                database_type: DatabaseType::Plain,
+                endian: Endian::Little,
+                page_size: 4096,
+                num_tables: 20,
+                sequence: 34,
+            }
+        );
+
+        let db = Database::open(DEMO_TRACKS_EXT_PDB).unwrap();
+        assert_eq!(
+            db.info(),
+            DatabaseInfo {
+                database_type: DatabaseType::Ext,
+                endian: Endian::Little,
+                page_size: 4096,
+                num_tables: 9,
+                sequence: 12,
+            }
+        );
+    }
+
+    #[test]
+    fn key_id_mismatches() {
+        for path in [DEMO_TRACKS_PDB, NUM_ROWS_PDB] {
+            let db = Database::open(path).unwrap();
+            assert!(db.row_count(PageType::Keys).unwrap() > 0);
+            assert_eq!(db.key_id_mismatches().unwrap(), vec![]);
+        }
+    }
+
+    #[test]
+    fn tracks_in_bpm_range() {
+        fn track_ids(db: &Database, min: f64, max: f64) -> Vec<u32> {
+            let mut ids = db
+                .tracks_in_bpm_range(min, max)
+                .map(|track| track.map(|track| track.id().0))
+                .collect::<RekordcrateResult<Vec<_>>>()
+                .unwrap();
+            ids.sort_unstable();
+            ids
+        }
+
+        // The demo tracks have tempos of 128 and 120 BPM.
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert_eq!(track_ids(&db, 125.0, 130.0), vec![1]);
+        assert_eq!(track_ids(&db, 120.0, 128.0), vec![1, 2]);
+        assert_eq!(track_ids(&db, 120.01, 127.99), vec![]);
+
+        // Unanalyzed tracks with a tempo of 0 are excluded, even if the range includes 0.
+        let mut db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        db.update_track(TrackId(2), |track| {
+            track.tempo = 0;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(track_ids(&db, 0.0, 200.0), vec![1]);
+    }
+
+    #[test]
+    fn genres_keys_and_labels() {
+        for path in [DEMO_TRACKS_PDB, NUM_ROWS_PDB] {
+            let db = Database::open(path).unwrap();
+            let genres = db.genres(false).unwrap();
+            let keys = db.keys(false).unwrap();
+            let labels = db.labels(false).unwrap();
+            assert_eq!(genres.len(), db.row_count(PageType::Genres).unwrap());
+            assert_eq!(keys.len(), db.row_count(PageType::Keys).unwrap());
+            assert_eq!(labels.len(), db.row_count(PageType::Labels).unwrap());
+
+            let names = |names: Vec<Result<String, StringError>>| -> Vec<String> {
+                names.into_iter().map(Result::unwrap).collect()
+            };
+            let sorted_genres = names(db.genres(true).unwrap().iter().map(Genre::name).collect());
+            let sorted_keys = names(db.keys(true).unwrap().iter().map(Key::name).collect());
+            let sorted_labels = names(db.labels(true).unwrap().iter().map(Label::name).collect());
+            for (sorted, unsorted) in [
+                (
+                    &sorted_genres,
+                    names(genres.iter().map(Genre::name).collect()),
+                ),
+                (&sorted_keys, names(keys.iter().map(Key::name).collect())),
+                (
+                    &sorted_labels,
+                    names(labels.iter().map(Label::name).collect()),
+                ),
+            ] {
+                let mut expected = unsorted.clone();
+                expected.sort_by_key(|name| search_form(name));
+                assert_eq!(sorted, &expected);
+            }
+        }
+    }
+
+    #[test]
+    fn browse_categories() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let names = db
+            .browse_categories()
+            .unwrap()
+            .iter()
+            .map(ColumnEntry::name)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(names.len(), 27);
+        assert_eq!(names[..5], ["GENRE", "ARTIST", "ALBUM", "TRACK", "BPM"]);
+        assert_eq!(names.last().unwrap(), "MATCHING");
+    }
+
+    #[test]
+    fn table_summary() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let summary = db.table_summary().unwrap();
+        assert_eq!(summary.len(), 20);
+        assert!(summary.contains(&(PageType::Tracks, true)));
+        assert!(summary.contains(&(PageType::Genres, false)));
+        assert!(summary.contains(&(PageType::PlaylistTree, false)));
+
+        let db = Database::new_empty(4096).unwrap();
+        assert!(db
+            .table_summary()
+            .unwrap()
+            .iter()
+            .all(|(_, non_empty)| !non_empty));
+    }
+
+    #[test]
+    fn row_count_matches_iteration() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        for table in &db.header().tables {
+            let expected = db.iter_rows(table.page_type).count();
+            assert_eq!(
+                db.row_count(table.page_type).unwrap(),
+                expected,
+                "wrong row count for page type {:?}",
+                table.page_type
+            );
+        }
+    }
+
+    #[test]
+    fn borrowed_rows_match_owned_rows() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        for table in &db.header().tables {
+            let owned: Vec<Row> = db
+                .iter_rows(table.page_type)
+                .collect::<RekordcrateResult<_>>()
+                .unwrap();
+            let loaded = db.load_table(table.page_type).unwrap();
+            let borrowed: Vec<&Row> = loaded.iter_rows_ref().collect();
+            assert_eq!(borrowed, owned.iter().collect::<Vec<_>>());
+            assert_eq!(loaded.into_rows().collect::<Vec<_>>(), owned);
+        }
+    }
+
+    #[test]
+    fn open_non_persistent_matches_open() {
+        let data = std::fs::read(DEMO_TRACKS_PDB).unwrap();
+        let in_memory = Database::open_non_persistent(binrw::io::Cursor::new(data)).unwrap();
+        let from_file = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert_eq!(in_memory.header(), from_file.header());
+        for table in &from_file.header().tables {
+            let expected: Vec<Page> = from_file.pages(table).map(Result::unwrap).collect();
+            let actual: Vec<Page> = in_memory.pages(table).map(Result::unwrap).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn page_flags() {
+        for path in [DEMO_TRACKS_PDB, DEMO_TRACKS_EXT_PDB, NUM_ROWS_PDB] {
+            let db = Database::open(path).unwrap();
+            for table in &db.header().tables {
+                let flags = db.read_page_header(&table.first_page).unwrap().page_flags();
+                assert!(flags.is_index_page());
+                assert_eq!(flags.bits(), 0x64);
+                for page in db.pages(table).skip(1) {
+                    let flags = page.unwrap().page_flags();
+                    assert!(flags.page_has_data());
+                    assert!([0x24, 0x34].contains(&flags.bits()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn new_empty_roundtrip() {
+        let db = Database::new_empty(4096).unwrap();
+        let mut data = vec![];
+        db.write(&mut data).unwrap();
+        assert_eq!(data.len(), 41 * 4096);
+
+        let reopened = Database::open_non_persistent(Cursor::new(data.clone())).unwrap();
+        assert_eq!(reopened.header(), db.header());
+        assert_eq!(reopened.info().database_type, DatabaseType::Plain);
+        for table in &reopened.header().tables {
+            let pages: Vec<Page> = reopened.pages(table).map(Result::unwrap).collect();
+            assert_eq!(pages.len(), 1);
+            assert!(!pages[0].has_data());
+            assert_eq!(reopened.row_count(table.page_type).unwrap(), 0);
+        }
+
+        // The index pages of empty tables are identical to the ones created by Rekordbox.
+        let rekordbox = std::fs::read(EMPTY_PDB).unwrap();
+        let tracks_index_page = 4096..2 * 4096;
+        assert_eq!(
+            data[tracks_index_page.clone()],
+            rekordbox[tracks_index_page]
+        );
+    }
+
+    #[test]
+    fn new_empty_invalid_page_size() {
+        assert!(Database::new_empty(64).is_err());
+    }
+
+    #[test]
+    fn playlist_full_path() {
+        let db = Database::open(NUM_ROWS_PDB).unwrap();
+        let table = db.load_table(PageType::PlaylistTree).unwrap();
+        let id = table
+            .iter_rows_ref()
+            .find_map(|row| match row {
+                Row::PlaylistTreeNode(node)
+                    if node.name.clone().into_string().unwrap()
+                        == "Macadam house set 23 playlist" =>
+                {
+                    Some(node.id)
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            db.playlist_full_path(id).unwrap().as_deref(),
+            Some("PBAR CURRENT/HOUSE NYC /HOUSE PLAYLISTS/Macadam house set 23 playlist")
+        );
+        assert_eq!(
+            db.playlist_full_path(PlaylistTreeNodeId(u32::MAX)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn playlist_entries() {
+        let mut db = Database::open(NUM_ROWS_PDB).unwrap();
+        let playlist_id = PlaylistTreeNodeId(93);
+        let entries = db.playlist_entries(playlist_id).unwrap();
+        let indices: Vec<u32> = entries.iter().map(PlaylistEntry::entry_index).collect();
+        assert_eq!(indices, vec![1, 2, 3]);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.playlist_id() == playlist_id));
+
+        // Move the first entry to index 3, so that there are two entries at that index.
+        let num_modified_rows = db
+            .update_rows(PageType::PlaylistEntries, |row| match row {
+                Row::PlaylistEntry(entry)
+                    if entry.playlist_id == playlist_id && entry.entry_index == 1 =>
+                {
+                    entry.entry_index = 3;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            })
+            .unwrap();
+        assert_eq!(num_modified_rows, 1);
+        assert!(matches!(
+            db.playlist_entries(playlist_id),
+            Err(RekordcrateError::DuplicateEntryIndex {
+                playlist_id: PlaylistTreeNodeId(93),
+                entry_index: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn playlist_duration() {
+        let mut db = Database::open(NUM_ROWS_PDB).unwrap();
+        let durations: HashMap<TrackId, u64> = db
+            .iter_present_rows::<Track>()
+            .map(|track| {
+                let track = track.unwrap();
+                (track.id(), u64::from(track.duration()))
+            })
+            .collect();
+        let expected_duration = |db: &Database, id: PlaylistTreeNodeId| -> u64 {
+            db.playlist_entries(id)
+                .unwrap()
+                .iter()
+                .map(|entry| durations[&entry.track_id()])
+                .sum()
+        };
+
+        let playlist_id = PlaylistTreeNodeId(54);
+        let duration = db.playlist_duration(playlist_id, false).unwrap();
+        assert_eq!(duration.num_tracks, 6);
+        assert_eq!(duration.num_missing_tracks, 0);
+        assert_eq!(
+            duration.duration,
+            Duration::from_secs(expected_duration(&db, playlist_id))
+        );
+        assert_eq!(db.playlist_duration(playlist_id, true).unwrap(), duration);
+
+        // Folders only have a duration if their children are included.
+        let folder_id = db
+            .iter_present_rows::<PlaylistTreeNode>()
+            .map(Result::unwrap)
+            .find(|node| node.id == playlist_id)
+            .unwrap()
+            .parent_id;
+        assert_eq!(
+            db.playlist_duration(folder_id, false).unwrap(),
+            PlaylistDuration::default()
+        );
+        let children = db.playlist_children(folder_id).unwrap();
+        assert!(children.iter().all(|node| !node.is_folder()));
+        let playlist_ids: Vec<PlaylistTreeNodeId> = children.iter().map(|node| node.id).collect();
+        let duration = db.playlist_duration(folder_id, true).unwrap();
+        assert_eq!(
+            duration.duration,
+            Duration::from_secs(
+                playlist_ids
+                    .iter()
+                    .map(|&id| expected_duration(&db, id))
+                    .sum()
+            )
+        );
+        let root_duration = db.playlist_duration(PlaylistTreeNodeId(0), true).unwrap();
+        assert!(root_duration.duration >= duration.duration);
+        assert_eq!(
+            root_duration.num_tracks,
+            db.row_count(PageType::PlaylistEntries).unwrap()
+        );
+
+        // Entries of missing tracks are skipped.
+        let mut modified = false;
+        db.update_rows(PageType::PlaylistEntries, |row| match row {
+            Row::PlaylistEntry(entry) if entry.playlist_id == playlist_id && !modified => {
+                entry.track_id = TrackId(u32::MAX);
+                modified = true;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })
+        .unwrap();
+        let duration = db.playlist_duration(playlist_id, false).unwrap();
+        assert_eq!(duration.num_tracks, 5);
+        assert_eq!(duration.num_missing_tracks, 1);
+    }
+
+    #[test]
+    fn normalize_playlist_entries() {
+        let tracks = |db: &Database, id: PlaylistTreeNodeId| -> Vec<TrackId> {
+            let mut entries: Vec<PlaylistEntry> = db
+                .iter_present_rows::<PlaylistEntry>()
+                .map(Result::unwrap)
+                .filter(|entry| entry.playlist_id() == id)
+                .collect();
+            entries.sort_by_key(PlaylistEntry::entry_index);
+            entries.iter().map(PlaylistEntry::track_id).collect()
+        };
+
+        let issues = |db: &Database, id: PlaylistTreeNodeId| -> Option<PlaylistEntryIssues> {
+            db.check_playlist_entries()
+                .unwrap()
+                .into_iter()
+                .find(|issues| issues.playlist_id == id)
+        };
+
+        let mut db = Database::open(NUM_ROWS_PDB).unwrap();
+        // Some playlists in this export have gaps in their indices, e.g. playlist 54 only
+        // contains the indices 82 to 87.
+        let playlist_id = PlaylistTreeNodeId(54);
+        assert_eq!(
+            issues(&db, playlist_id),
+            Some(PlaylistEntryIssues {
+                playlist_id,
+                duplicate_indices: vec![],
+                missing_indices: vec![1, 2, 3, 4, 5, 6],
+            })
+        );
+        let original_tracks = tracks(&db, playlist_id);
+        assert_eq!(db.normalize_playlist_entries(playlist_id).unwrap(), 6);
+        assert_eq!(issues(&db, playlist_id), None);
+        assert_eq!(tracks(&db, playlist_id), original_tracks);
+
+        let playlist_id = PlaylistTreeNodeId(113);
+        assert_eq!(issues(&db, playlist_id), None);
+        let original_tracks = tracks(&db, playlist_id);
+        assert_eq!(original_tracks.len(), 6);
+        assert_eq!(db.normalize_playlist_entries(playlist_id).unwrap(), 0);
+
+        // Move the third entry to index 6, so that the indices are [1, 2, 4, 5, 6, 6].
+        db.update_rows(PageType::PlaylistEntries, |row| match row {
+            Row::PlaylistEntry(entry)
+                if entry.playlist_id == playlist_id && entry.entry_index == 3 =>
+            {
+                entry.entry_index = 6;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })
+        .unwrap();
+        assert_eq!(
+            issues(&db, playlist_id),
+            Some(PlaylistEntryIssues {
+                playlist_id,
+                duplicate_indices: vec![6],
+                missing_indices: vec![3],
+            })
+        );
+
+        assert_eq!(db.normalize_playlist_entries(playlist_id).unwrap(), 3);
+        assert_eq!(issues(&db, playlist_id), None);
+        let indices: Vec<u32> = db
+            .playlist_entries(playlist_id)
+            .unwrap()
+            .iter()
+            .map(PlaylistEntry::entry_index)
+            .collect();
+        assert_eq!(indices, vec![1, 2, 3, 4, 5, 6]);
+        let mut new_tracks = tracks(&db, playlist_id);
+        assert_eq!(
+            new_tracks[..4],
+            [
+                original_tracks[0],
+                original_tracks[1],
+                original_tracks[3],
+                original_tracks[4]
+            ]
+        );
+        new_tracks[4..].sort_unstable_by_key(|id| id.0);
+        let mut moved_tracks = [original_tracks[2], original_tracks[5]];
+        moved_tracks.sort_unstable_by_key(|id| id.0);
+        assert_eq!(new_tracks[4..], moved_tracks);
+    }
+
+    #[test]
+    fn playlists_containing() {
+        let db = Database::open(NUM_ROWS_PDB).unwrap();
+        let playlists = db.playlists_containing(TrackId(244)).unwrap();
+        let mut ids: Vec<u32> = playlists.iter().map(|(id, _)| id.0).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![11, 12, 23, 44]);
+        for (id, path) in &playlists {
+            assert_eq!(db.playlist_full_path(*id).unwrap().as_ref(), Some(path));
+        }
+        assert!(playlists.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+        // The cached index is reused for further lookups.
+        assert!(db.track_playlists.borrow().is_some());
+        assert_eq!(db.playlists_containing(TrackId(244)).unwrap(), playlists);
+        assert!(db.playlists_containing(TrackId(0)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn all_playlist_tracks_ordered() {
+        fn expected_playlists(
+            db: &Database,
+            parent_id: PlaylistTreeNodeId,
+            playlists: &mut Vec<(String, Vec<(u32, TrackId)>)>,
+        ) {
+            for node in db.playlist_children(parent_id).unwrap() {
+                if node.is_folder() {
+                    expected_playlists(db, node.id, playlists);
+                    continue;
+                }
+                let entries: Vec<(u32, TrackId)> = db
+                    .playlist_entries(node.id)
+                    .unwrap()
+                    .iter()
+                    .map(|entry| (entry.entry_index(), entry.track_id()))
+                    .collect();
+                if !entries.is_empty() {
+                    let path = db.playlist_full_path(node.id).unwrap().unwrap();
+                    playlists.push((path, entries));
+                }
+            }
+        }
+
+        let db = Database::open(NUM_ROWS_PDB).unwrap();
+        let mut expected = vec![];
+        expected_playlists(&db, PlaylistTreeNodeId(0), &mut expected);
+        assert!(expected.iter().any(|(path, _)| path.contains('/')));
+
+        let mut playlists: Vec<(String, Vec<(u32, TrackId)>)> = vec![];
+        for (path, entry_index, track) in db.all_playlist_tracks_ordered().unwrap() {
+            match playlists.last_mut() {
+                Some((last_path, entries)) if *last_path == path => {
+                    entries.push((entry_index, track.id()));
+                }
+                _ => playlists.push((path, vec![(entry_index, track.id())])),
+            }
+        }
+        assert_eq!(playlists, expected);
+    }
+
+    #[test]
+    fn reorder_playlists() {
+        let mut db = Database::open(NUM_ROWS_PDB).unwrap();
+        let parent_id = PlaylistTreeNodeId(3);
+        let ids = |db: &Database| -> Vec<PlaylistTreeNodeId> {
+            db.playlist_children(parent_id)
+                .unwrap()
+                .iter()
+                .map(|node| node.id)
+                .collect()
+        };
+        let mut new_order = ids(&db);
+        assert!(new_order.len() > 2);
+        new_order.reverse();
+        db.reorder_playlists(parent_id, &new_order).unwrap();
+        assert_eq!(ids(&db), new_order);
+
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+        assert_eq!(ids(&db), new_order);
+        let sort_orders: Vec<u32> = db
+            .playlist_children(parent_id)
+            .unwrap()
+            .iter()
+            .map(PlaylistTreeNode::sort_order)
+            .collect();
+        assert_eq!(sort_orders, (0..new_order.len() as u32).collect::<Vec<_>>());
+
+        let mut db = db;
+        assert!(db.reorder_playlists(parent_id, &new_order[1..]).is_err());
+        assert!(db
+            .reorder_playlists(parent_id, &[new_order[0], new_order[0]])
+            .is_err());
+    }
+
+    #[test]
+    fn allocate_page() {
+        let mut db = Database::open(NUM_ROWS_PDB).unwrap();
+        let next_unused_page = db.header().next_unused_page().clone();
+        assert_eq!(db.header.allocate_page(), next_unused_page);
+        assert_eq!(
+            db.header().next_unused_page(),
+            &PageIndex(next_unused_page.0 + 1)
+        );
+    }
+
+    #[test]
+    fn copy_track_from() {
+        let demo = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let track = demo
+            .iter_present_rows::<Track>()
+            .map(Result::unwrap)
+            .find(|track| track.artist_id.0 != 0)
+            .unwrap();
+        let artist = demo
+            .iter_present_rows::<Artist>()
+            .map(Result::unwrap)
+            .find(|artist| artist.id == track.artist_id)
+            .unwrap();
+
+        // The source only contains the track and its artist, so all other references dangle.
+        let mut source = Database::new_empty(4096).unwrap();
+        source
+            .insert_row(PageType::Artists, Row::Artist(artist.clone()))
+            .unwrap();
+        source
+            .insert_row(PageType::Tracks, Row::Track(track.clone()))
+            .unwrap();
+        // The target already contains a different artist with the same ID.
+        let mut other_artist = artist.clone();
+        other_artist.name = DeviceSQLString::new("Other Artist".to_string()).unwrap();
+        let mut target = Database::new_empty(4096).unwrap();
+        target
+            .insert_row(PageType::Artists, Row::Artist(other_artist.clone()))
+            .unwrap();
+
+        assert_eq!(
+            target.copy_track_from(&source, track.id).unwrap(),
+            Some(track.id)
+        );
+        let mut copied_artists: Vec<Artist> = target
+            .iter_present_rows()
+            .collect::<RekordcrateResult<_>>()
+            .unwrap();
+        copied_artists.sort_by_key(|artist| artist.id.0);
+        let mut copied_artist = artist.clone();
+        copied_artist.id = ArtistId(artist.id.0 + 1);
+        assert_eq!(copied_artists, vec![other_artist, copied_artist]);
+        let copied_tracks: Vec<Track> = target
+            .iter_present_rows()
+            .collect::<RekordcrateResult<_>>()
+            .unwrap();
+        assert_eq!(copied_tracks.len(), 1);
+        assert_eq!(copied_tracks[0].artist_id, ArtistId(artist.id.0 + 1));
+        assert_eq!(copied_tracks[0].album_id.0, 0);
+        assert_eq!(copied_tracks[0].genre_id.0, 0);
+
+        // Copying the same track again reuses the existing rows.
+        let header = target.header().clone();
+        assert_eq!(
+            target.copy_track_from(&source, track.id).unwrap(),
+            Some(track.id)
+        );
+        assert_eq!(target.header(), &header);
+        assert_eq!(
+            target.copy_track_from(&source, TrackId(9999)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn failed_insert_keeps_header() {
+        // The last page of the playlist tree table is full, and the name is too long to fit into
+        // an empty page.
+        let mut db = Database::open(NUM_ROWS_PDB).unwrap();
+        let header = db.header().clone();
+        assert!(db
+            .create_playlist(PlaylistTreeNodeId(0), &"x".repeat(5000), false)
+            .is_err());
+        assert_eq!(db.header(), &header);
+
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        assert_eq!(std::fs::read(NUM_ROWS_PDB).unwrap(), data);
+    }
+
+    #[test]
+    fn create_playlist() {
+        // The last page of the playlist tree table in this export is full, and the table in the
+        // demo export does not have any data pages yet, so both need a new page.
+        for path in [NUM_ROWS_PDB, DEMO_TRACKS_PDB] {
+            let mut db = Database::open(path).unwrap();
+            let old_nodes: Vec<PlaylistTreeNode> = db
+                .iter_present_rows::<PlaylistTreeNode>()
+                .collect::<RekordcrateResult<_>>()
+                .unwrap();
+            let table = |db: &Database| {
+                db.header()
+                    .tables
+                    .iter()
+                    .find(|table| table.page_type == PageType::PlaylistTree)
+                    .unwrap()
+                    .clone()
+            };
+            let old_table = table(&db);
+            let old_next_unused_page = db.header().next_unused_page().clone();
+
+            let folder_id = db
+                .create_playlist(PlaylistTreeNodeId(0), "New Folder", true)
+                .unwrap();
+            let playlist_id = db
+                .create_playlist(folder_id, "New Playlist", false)
+                .unwrap();
+            assert_ne!(folder_id, playlist_id);
+            assert!(db.create_playlist(playlist_id, "Invalid", false).is_err());
+
+            let mut data = Vec::new();
+            db.write(&mut data).unwrap();
+            let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+            // The reserved empty page is used as the new last page, and the next unused page is
+            // reserved instead.
+            let new_table = table(&db);
+            assert_eq!(new_table.last_page.0, old_table.empty_candidate);
+            assert_eq!(PageIndex(new_table.empty_candidate), old_next_unused_page);
+            assert_eq!(
+                db.header().next_unused_page(),
+                &PageIndex(old_next_unused_page.0 + 1)
+            );
+            assert_eq!(
+                db.row_count(PageType::PlaylistTree).unwrap(),
+                old_nodes.len() + 2
+            );
+            let nodes: Vec<PlaylistTreeNode> = db
+                .iter_present_rows::<PlaylistTreeNode>()
+                .collect::<RekordcrateResult<_>>()
+                .unwrap();
+            assert_eq!(nodes[..old_nodes.len()], old_nodes[..]);
+
+            let folder = db
+                .playlist_children(PlaylistTreeNodeId(0))
+                .unwrap()
+                .into_iter()
+                .find(|node| node.id == folder_id)
+                .unwrap();
+            assert!(folder.is_folder());
+            assert_eq!(folder.name.clone().into_string().unwrap(), "New Folder");
+            let children = db.playlist_children(folder_id).unwrap();
+            assert_eq!(children.len(), 1);
+            assert_eq!(children[0].id, playlist_id);
+            assert!(!children[0].is_folder());
+            assert_eq!(
+                children[0].name.clone().into_string().unwrap(),
+                "New Playlist"
+            );
+        }
+    }
+
+    #[test]
+    fn playlist_path_cycle() {
+        let node = |id, parent_id, name: &str| PlaylistTreeNode {
+            parent_id: PlaylistTreeNodeId(parent_id),
+            unknown: 0,
+            sort_order: 0,
+            id: PlaylistTreeNodeId(id),
+            node_is_folder: 1,
+            name: DeviceSQLString::new(name.to_string()).unwrap(),
+        };
+        let (a, b, c) = (node(1, 2, "a"), node(2, 1, "b"), node(3, 0, "c"));
+        let nodes = HashMap::from([(a.id, &a), (b.id, &b), (c.id, &c)]);
+        assert_eq!(playlist_path(&nodes, a.id).unwrap(), None);
+        assert_eq!(playlist_path(&nodes, c.id).unwrap().as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn iter_rows_with_page() {
+        let db = Database::open(NUM_ROWS_PDB).unwrap();
+        let rows: Vec<(PageIndex, Track)> = db
+            .iter_rows_with_page::<Track>()
+            .collect::<RekordcrateResult<_>>()
+            .unwrap();
+        let tracks: Vec<Track> = db
+            .iter_present_rows::<Track>()
+            .collect::<RekordcrateResult<_>>()
+            .unwrap();
+        assert_eq!(
+            rows.iter().map(|(_, track)| track).collect::<Vec<_>>(),
+            tracks.iter().collect::<Vec<_>>()
+        );
+
+        let page_indices: HashSet<PageIndex> = rows
+            .iter()
+            .map(|(page_index, _)| page_index.clone())
+            .collect();
+        assert!(page_indices.len() > 1);
+        let table = db
+            .header()
+            .tables
+            .iter()
+            .find(|table| table.page_type == PageType::Tracks)
+            .unwrap();
+        for page in db.pages(table) {
+            let page = page.unwrap();
+            let num_rows = rows
+                .iter()
+                .filter(|(page_index, _)| *page_index == page.page_index)
+                .count();
+            assert_eq!(num_rows, page.rows().count());
+        }
+    }
+
+    #[test]
+    fn iter_present_rows_skips_absent_rows() {
+        let mut data = std::fs::read(DEMO_TRACKS_PDB).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data.clone())).unwrap();
+        let colors: Vec<Color> = db.iter_present_rows().map(Result::unwrap).collect();
+        assert_eq!(colors.len(), 8);
+        let table = db.load_table(PageType::Colors).unwrap();
+        let page = table
+            .pages()
+            .iter()
+            .find(|page| page.num_rows() > 0)
+            .unwrap();
+
+        // Clear the presence flags of the second and fifth slot of the (only) row group, as if
+        // these rows had been deleted.
+        let page_size = db.header().page_size;
+        let page_end =
+            usize::try_from(page.page_index.offset(page_size) + u64::from(page_size)).unwrap();
+        let flags = u16::from_le_bytes([data[page_end - 4], data[page_end - 3]]);
+        assert_eq!(flags, 0x00ff);
+        data[page_end - 4..page_end - 2].copy_from_slice(&0x00edu16.to_le_bytes());
+
+        let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+        let present_colors: Vec<Color> = db.iter_present_rows().map(Result::unwrap).collect();
+        // Rows are returned starting with the highest slot of the row group, so the rows in the
+        // second and fifth slot are the seventh and fourth row.
+        let expected: Vec<Color> = colors
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| ![6, 3].contains(i))
+            .map(|(_, color)| color)
+            .collect();
+        assert_eq!(present_colors, expected);
+    }
+
+    #[test]
+    fn tag_rows_roundtrip() {
+        let data = std::fs::read(DEMO_TRACKS_EXT_PDB).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data.clone())).unwrap();
+        let page_size = db.header().page_size;
+        let table = db.load_table(ExtPageType::Tags.into()).unwrap();
+        let mut num_rows = 0;
+        for page in table.pages().iter().filter(|page| page.num_rows() > 0) {
+            let heap_offset = usize::try_from(page.page_index.offset(page_size)).unwrap()
+                + Page::HEADER_SIZE as usize;
+            for row_offset in page
+                .row_groups
+                .iter()
+                .flat_map(|row_group| row_group.rows.iter().flatten())
+            {
+                let mut writer = Cursor::new(vec![]);
+                row_offset.value.write_le(&mut writer).unwrap();
+                let row_data = writer.into_inner();
+                let start = heap_offset + usize::from(row_offset.ptr);
+                assert_eq!(row_data, data[start..start + row_data.len()]);
+                num_rows += 1;
+            }
+            assert_eq!(
+                page.compute_sizes(page_size).unwrap(),
+                (page.used_size, page.free_size)
+            );
+        }
+        assert_eq!(num_rows, 28);
+    }
+
+    #[test]
+    fn set_tag_name() {
+        let original = std::fs::read(DEMO_TRACKS_EXT_PDB).unwrap();
+        let tags = |db: &Database| -> Vec<Tag> {
+            db.iter_rows(ExtPageType::Tags.into())
+                .map(|row| match row.unwrap() {
+                    Row::Ext(ExtRow::Tag(tag)) => tag,
+                    row => panic!("unexpected row {:?}", row),
+                })
+                .collect()
+        };
+
+        let mut db = Database::open_non_persistent(Cursor::new(original.clone())).unwrap();
+        let mut expected = tags(&db);
+        let tag = expected.iter_mut().find(|tag| !tag.is_category()).unwrap();
+        let id = tag.id();
+        let name = tag.name().unwrap();
+        tag.set_name("A much longer name for this tag").unwrap();
+        // The offset of the string that follows the name has to fit into a single byte.
+        let mut unchanged = tag.clone();
+        assert!(matches!(
+            unchanged.set_name(&"x".repeat(300)),
+            Err(StringError::TooLong)
+        ));
+        assert_eq!(&unchanged, tag);
+        assert!(db
+            .set_tag_name(id, "A much longer name for this tag")
+            .unwrap());
+
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+        assert_eq!(tags(&db), expected);
+        let table = db.load_table(ExtPageType::Tags.into()).unwrap();
+        for page in table.pages().iter().filter(|page| page.num_rows() > 0) {
+            assert_eq!(
+                page.compute_sizes(db.header().page_size).unwrap(),
+                (page.used_size, page.free_size)
+            );
+        }
+
+        // Renaming the tag back restores the original rows.
+        let original_tags = tags(&Database::open_non_persistent(Cursor::new(original)).unwrap());
+        let mut db = db;
+        assert!(db.set_tag_name(id, &name).unwrap());
+        assert_eq!(tags(&db), original_tags);
+
+        let mut db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert!(!db.set_tag_name(id, "Tag").unwrap());
+    }
+
+    #[test]
+    fn set_color_name() {
+        let original = std::fs::read(DEMO_TRACKS_PDB).unwrap();
+        let color_names = |db: &Database| -> Vec<(ColorIndex, String)> {
+            db.iter_rows(PageType::Colors)
+                .map(|row| match row.unwrap() {
+                    Row::Color(color) => (color.color(), color.name().unwrap()),
+                    row => panic!("unexpected row {:?}", row),
+                })
+                .collect()
+        };
+
+        let mut db = Database::open_non_persistent(Cursor::new(original.clone())).unwrap();
+        let mut expected = color_names(&db);
+        assert!(db.set_color_name(ColorIndex::Pink, "Hot Pink").unwrap());
+        let pink = expected
+            .iter_mut()
+            .find(|(color, _)| *color == ColorIndex::Pink)
+            .unwrap();
+        pink.1 = "Hot Pink".to_string();
+
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+        assert_eq!(color_names(&db), expected);
+        let table = db.load_table(PageType::Colors).unwrap();
+        let page = table
+            .pages()
+            .iter()
+            .find(|page| page.num_rows() > 0)
+            .unwrap();
+        assert_eq!(
+            page.compute_sizes(db.header().page_size).unwrap(),
+            (page.used_size, page.free_size)
+        );
+
+        // Renaming the color back restores the original file.
+        let mut db = db;
+        assert!(db.set_color_name(ColorIndex::Pink, "Pink").unwrap());
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        assert_eq!(data, original);
+
+        let mut db = Database::new_empty(4096).unwrap();
+        assert!(!db.set_color_name(ColorIndex::Pink, "Pink").unwrap());
+    }
+
+    #[test]
+    fn set_track_rating_and_color() {
+        let track = |db: &Database, id: TrackId| -> Track {
+            db.iter_present_rows::<Track>()
+                .map(Result::unwrap)
+                .find(|track| track.id() == id)
+                .unwrap()
+        };
+
+        let mut db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert_eq!(track(&db, TrackId(1)).rating(), 0);
+        assert!(db.set_track_rating(TrackId(1), 4).unwrap());
+        assert!(db.set_track_color(TrackId(1), ColorIndex::Blue).unwrap());
+        assert!(matches!(
+            db.set_track_rating(TrackId(1), 6),
+            Err(RekordcrateError::InvalidRating(6))
+        ));
+        assert!(!db.set_track_rating(TrackId(12345), 1).unwrap());
+        assert!(!db.set_track_color(TrackId(12345), ColorIndex::Red).unwrap());
+
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+        let modified = track(&db, TrackId(1));
+        assert_eq!(modified.rating(), 4);
+        assert_eq!(modified.color(), ColorIndex::Blue);
+        let unmodified = track(&db, TrackId(2));
+        assert_eq!(unmodified.rating(), 0);
+        assert_eq!(unmodified.color(), ColorIndex::None);
+    }
+
+    #[test]
+    fn set_track_title_and_artist() {
+        let tracks = |db: &Database| -> Vec<Track> {
+            let mut tracks: Vec<Track> = db
+                .iter_present_rows::<Track>()
+                .collect::<RekordcrateResult<_>>()
+                .unwrap();
+            tracks.sort_by_key(|track| track.id().0);
+            tracks
+        };
+        let artist_name = |db: &Database, id: ArtistId| -> String {
+            db.iter_present_rows::<Artist>()
+                .map(Result::unwrap)
+                .find(|artist| artist.id() == id)
+                .unwrap()
+                .name()
+                .unwrap()
+        };
+
+        let mut db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let old_tracks = tracks(&db);
+        let num_artists = db.row_count(PageType::Artists).unwrap();
+        let existing_artist = artist_name(&db, old_tracks[1].artist_id());
+        let title = "A considerably longer title than the original one";
+        assert!(db.set_track_title(TrackId(1), title).unwrap());
+        assert!(db.set_track_artist(TrackId(1), "New Artist").unwrap());
+        assert!(db.set_track_artist(TrackId(2), &existing_artist).unwrap());
+        assert!(db.set_track_title(TrackId(2), "").unwrap());
+        assert!(!db.set_track_title(TrackId(12345), title).unwrap());
+        assert!(!db.set_track_artist(TrackId(12345), "Other").unwrap());
+
+        let mut data = Vec::new();
+        db.write(&mut data).unwrap();
+        let db = Database::open_non_persistent(Cursor::new(data)).unwrap();
+        assert_eq!(db.row_count(PageType::Artists).unwrap(), num_artists + 1);
+        for page_type in [PageType::Tracks, PageType::Artists] {
+            let table = db.load_table(page_type).unwrap();
+            for page in table.pages().iter().filter(|page| page.num_rows() > 0) {
+                assert_eq!(
+                    page.compute_sizes(db.header().page_size).unwrap(),
+                    (page.used_size, page.free_size),
+                );
+            }
+        }
+
+        let new_tracks = tracks(&db);
+        assert_eq!(new_tracks.len(), old_tracks.len());
+        assert_eq!(new_tracks[0].title().unwrap(), title);
+        assert_eq!(artist_name(&db, new_tracks[0].artist_id()), "New Artist");
+        assert_eq!(new_tracks[1].title().unwrap(), "");
+        assert_eq!(new_tracks[1].artist_id(), old_tracks[1].artist_id());
+        for (new, old) in new_tracks.iter().zip(&old_tracks).skip(2) {
+            assert_eq!(new, old);
+        }
+        for (new, old) in new_tracks.iter().zip(&old_tracks).take(2) {
+            assert_eq!(new.file_path().unwrap(), old.file_path().unwrap());
+            assert_eq!(new.artwork_id(), old.artwork_id());
+        }
+    }
+
+    #[test]
+    fn track_mix_names() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        for track in db.iter_present_rows::<Track>() {
+            let track = track.unwrap();
+            assert_eq!(track.mix_name().unwrap(), "");
+            assert_eq!(track.message().unwrap(), "");
+        }
+
+        let db = Database::open(NUM_ROWS_PDB).unwrap();
+        let mix_name = |id: TrackId| {
+            db.iter_present_rows::<Track>()
+                .map(Result::unwrap)
+                .find(|track| track.id() == id)
+                .unwrap()
+                .mix_name()
+                .unwrap()
+        };
+        assert_eq!(mix_name(TrackId(2796)), "Conforce Remix");
+        assert_eq!(mix_name(TrackId(2834)), "Original Mix");
+    }
+
+    #[test]
+    fn page_compute_sizes() {
+        let db = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let page_size = db.header().page_size;
+        for page_type in [PageType::Keys, PageType::Colors, PageType::Labels] {
+            let table = db.load_table(page_type).unwrap();
+            let page = table
+                .pages()
+                .iter()
+                .find(|page| page.num_rows() > 0)
+                .unwrap();
+            assert_eq!(
+                page.compute_sizes(page_size).unwrap(),
+                (page.used_size, page.free_size),
+                "{:?}",
+                page_type
+            );
+        }
+    }
+
+    /// Find the track with the given `filename` in the database at `path`.
+    fn find_track(path: &str, filename: &str) -> Track {
+        Database::open(path)
+            .unwrap()
+            .iter_rows(PageType::Tracks)
+            .map(Result::unwrap)
+            .find_map(|row| match row {
+                Row::Track(track) if track.filename.clone().into_string().unwrap() == filename => {
+                    Some(track)
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn track_file_type() {
+        let mp3 = find_track(DEMO_TRACKS_PDB, "Demo Track 1.mp3").file_type();
+        assert_eq!(mp3, FileType::Mp3);
+        assert!(!mp3.is_lossless());
+        let wav = find_track(NUM_ROWS_PDB, "Radar - DWL  [Master].wav").file_type();
+        assert_eq!(wav, FileType::Wav);
+        assert!(wav.is_lossless());
+    }
+
+    #[test]
+    fn track_bpm() {
+        assert_eq!(
+            find_track(DEMO_TRACKS_PDB, "Demo Track 1.mp3")
+                .bpm()
+                .unwrap()
+                .to_string(),
+            "128.00"
+        );
+        assert_eq!(
+            find_track(NUM_ROWS_PDB, "outer limits [2024-03-28 173417].mp3").bpm(),
+            Err(InvalidTempoError(0))
+        );
+    }
+
+    #[test]
+    fn track_audio_properties() {
+        assert_eq!(
+            find_track(DEMO_TRACKS_PDB, "Demo Track 1.mp3").audio_properties(),
+            AudioProperties {
+                sample_rate_hz: 44100,
+                bitrate_kbps: 320,
+                sample_depth_bits: 16,
+            }
+        );
+        assert_eq!(
+            find_track(NUM_ROWS_PDB, "Radar - DWL  [Master].wav").audio_properties(),
+            AudioProperties {
+                sample_rate_hz: 44100,
+                bitrate_kbps: 1411,
+                sample_depth_bits: 16,
+            }
+        );
+        assert_eq!(
+            find_track(NUM_ROWS_PDB, "A1_frak_wobbler_ROB_MA.wav").audio_properties(),
+            AudioProperties {
+                sample_rate_hz: 44100,
+                bitrate_kbps: 2116,
+                sample_depth_bits: 24,
+            }
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    #[allow(unsafe_code)]
+    fn open_mmap_matches_open() {
+        // SAFETY: The fixture is not modified while the test runs.
+        let mapped = unsafe { Database::open_mmap(DEMO_TRACKS_PDB) }.unwrap();
+        let from_file = Database::open(DEMO_TRACKS_PDB).unwrap();
+        assert_eq!(mapped.header(), from_file.header());
+        for table in &from_file.header().tables {
+            let expected: Vec<Page> = from_file.pages(table).map(Result::unwrap).collect();
+            let actual: Vec<Page> = mapped.pages(table).map(Result::unwrap).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,822 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Combine the collections of two PDB files.
+//!
+//! [`merge_collections`] reads the tracks, their metadata and the playlist tree of two databases
+//! and produces a single [`MergedCollection`] in memory. Rows from the second database are
+//! assigned new IDs if they collide with rows from the first one, and all rows that reference them
+//! are rewritten accordingly.
+//!
+//! Entities are considered identical (and hence *merged* into a single row) if:
+//!
+//! - artists, artwork, genres, keys and labels have the same name (or path),
+//! - albums have the same name and artist,
+//! - tracks have the same file path,
+//! - playlist tree nodes have the same name, parent and node type (folder or playlist).
+//!
+//! The color and column tables are fixed and taken from the first database. History playlists are
+//! device-specific and are not merged.
+//!
+//! Single tracks can be added to a collection using [`MergedCollection::copy_track_from`], or
+//! directly to a database using [`Database::copy_track_from`].
+//!
+//! **Note:** Apart from single tracks, this currently only produces the merged rows. Writing them
+//! back into a PDB file is not supported yet.
+
+use crate::pdb::{
+    io::Database, Album, Artist, Artwork, Genre, Key, Label, PageType, PlaylistEntry,
+    PlaylistTreeNode, Row, Track, TrackId,
+};
+use crate::util::RekordcrateResult;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// The kind of entity whose ID was changed during a merge.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum EntityKind {
+    /// A row in the [`PageType::Tracks`] table.
+    Track,
+    /// A row in the [`PageType::Artists`] table.
+    Artist,
+    /// A row in the [`PageType::Albums`] table.
+    Album,
+    /// A row in the [`PageType::Artwork`] table.
+    Artwork,
+    /// A row in the [`PageType::Genres`] table.
+    Genre,
+    /// A row in the [`PageType::Keys`] table.
+    Key,
+    /// A row in the [`PageType::Labels`] table.
+    Label,
+    /// A row in the [`PageType::PlaylistTree`] table.
+    PlaylistTreeNode,
+}
+
+/// An entity of the second database whose ID is different in the merged collection.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IdChange {
+    /// The kind of entity.
+    pub kind: EntityKind,
+    /// The ID in the second database.
+    pub old_id: u32,
+    /// The ID in the merged collection.
+    pub new_id: u32,
+}
+
+/// Summary of the changes made by [`merge_collections`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MergeReport {
+    /// Entities of the second database that are identical to an entity of the first database and
+    /// have been merged into it. `new_id` is the ID of the entity from the first database.
+    pub merged: Vec<IdChange>,
+    /// Entities of the second database that have been added with a new ID, because their
+    /// original ID was already used in the first database.
+    pub renamed: Vec<IdChange>,
+}
+
+/// The combined rows of two databases, returned by [`merge_collections`].
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct MergedCollection {
+    /// All tracks.
+    pub tracks: Vec<Track>,
+    /// All artists.
+    pub artists: Vec<Artist>,
+    /// All albums.
+    pub albums: Vec<Album>,
+    /// All artwork items.
+    pub artwork: Vec<Artwork>,
+    /// All genres.
+    pub genres: Vec<Genre>,
+    /// All keys.
+    pub keys: Vec<Key>,
+    /// All labels.
+    pub labels: Vec<Label>,
+    /// All playlist tree nodes (folders and playlists).
+    pub playlist_tree: Vec<PlaylistTreeNode>,
+    /// All playlist entries.
+    pub playlist_entries: Vec<PlaylistEntry>,
+}
+
+impl MergedCollection {
+    /// Copy the track with the given ID and the rows it references (artists, album, artwork,
+    /// genre, key and label) from `source` into this collection.
+    ///
+    /// Rows that already exist in this collection (using the same criteria as
+    /// [`merge_collections`]) are reused, all other rows are added with a new ID if their ID is
+    /// already taken. References to rows that don't exist in `source` are set to `0` (i.e. "none").
+    /// Returns the ID of the track in this collection, or `None` if the track does not exist in
+    /// `source`.
+    ///
+    /// Use [`Database::copy_track_from`] to copy a track into a database instead.
+    ///
+    /// ```
+    /// # pub fn main() -> rekordcrate::Result<()> {
+    /// use rekordcrate::pdb::{io::Database, merge::MergedCollection, TrackId};
+    ///
+    /// let path = "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb";
+    /// let source = Database::open(path)?;
+    /// let mut collection = MergedCollection::default();
+    /// let id = collection.copy_track_from(&source, TrackId(1))?;
+    /// assert_eq!(id, Some(TrackId(1)));
+    /// assert_eq!(collection.tracks.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn copy_track_from(
+        &mut self,
+        source: &Database,
+        id: TrackId,
+    ) -> RekordcrateResult<Option<TrackId>> {
+        let Some(mut track) = source
+            .iter_rows(PageType::Tracks)
+            .find_map(|row| match row {
+                Ok(Row::Track(track)) if track.id == id => Some(Ok(track)),
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            })
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+
+        let artists = read_rows(source, PageType::Artists, |row| match row {
+            Row::Artist(artist) => Some(artist),
+            _ => None,
+        })?;
+        let mut copy_artist = |id: u32| {
+            copy_row(
+                &mut self.artists,
+                artists.iter().find(|artist| artist.id.0 == id),
+                |artist| artist.id.0,
+                |artist, id| artist.id.0 = id,
+                |artist| artist.name().ok(),
+            )
+            .unwrap_or(0)
+        };
+        track.artist_id.0 = copy_artist(track.artist_id.0);
+        track.composer_id.0 = copy_artist(track.composer_id.0);
+        track.orig_artist_id.0 = copy_artist(track.orig_artist_id.0);
+        track.remixer_id.0 = copy_artist(track.remixer_id.0);
+
+        let mut album = read_rows(source, PageType::Albums, |row| match row {
+            Row::Album(album) if album.id == track.album_id => Some(album),
+            _ => None,
+        })?
+        .pop();
+        if let Some(album) = &mut album {
+            album.artist_id.0 = copy_artist(album.artist_id.0);
+        }
+        track.album_id.0 = copy_row(
+            &mut self.albums,
+            album.as_ref(),
+            |album| album.id.0,
+            |album, id| album.id.0 = id,
+            |album| {
+                let name = album.name.clone().into_string().ok()?;
+                Some((name, album.artist_id.0))
+            },
+        )
+        .unwrap_or(0);
+
+        track.artwork_id.0 = copy_row(
+            &mut self.artwork,
+            read_rows(source, PageType::Artwork, |row| match row {
+                Row::Artwork(artwork) if artwork.id == track.artwork_id => Some(artwork),
+                _ => None,
+            })?
+            .first(),
+            |artwork| artwork.id.0,
+            |artwork, id| artwork.id.0 = id,
+            |artwork| artwork.path.clone().into_string().ok(),
+        )
+        .unwrap_or(0);
+
+        track.genre_id.0 = copy_row(
+            &mut self.genres,
+            read_rows(source, PageType::Genres, |row| match row {
+                Row::Genre(genre) if genre.id == track.genre_id => Some(genre),
+                _ => None,
+            })?
+            .first(),
+            |genre| genre.id.0,
+            |genre, id| genre.id.0 = id,
+            |genre| genre.name.clone().into_string().ok(),
+        )
+        .unwrap_or(0);
+
+        track.key_id.0 = copy_row(
+            &mut self.keys,
+            read_rows(source, PageType::Keys, |row| match row {
+                Row::Key(key) if key.id == track.key_id => Some(key),
+                _ => None,
+            })?
+            .first(),
+            |key| key.id.0,
+            |key, id| {
+                key.id.0 = id;
+                key.id2 = id;
+            },
+            |key| key.name().ok(),
+        )
+        .unwrap_or(0);
+
+        track.label_id.0 = copy_row(
+            &mut self.labels,
+            read_rows(source, PageType::Labels, |row| match row {
+                Row::Label(label) if label.id == track.label_id => Some(label),
+                _ => None,
+            })?
+            .first(),
+            |label| label.id.0,
+            |label, id| label.id.0 = id,
+            |label| label.name.clone().into_string().ok(),
+        )
+        .unwrap_or(0);
+
+        let track_id = copy_row(
+            &mut self.tracks,
+            Some(&track),
+            |track| track.id.0,
+            |track, id| track.id.0 = id,
+            |track| track.file_path.clone().into_string().ok(),
+        );
+        Ok(track_id.map(TrackId))
+    }
+}
+
+/// Merge the collection of `other` into the collection of `base`.
+///
+/// All rows of `base` are kept unchanged. Rows of `other` are either merged into an identical row
+/// of `base` or added to the collection, with a new ID if necessary. The returned [`MergeReport`]
+/// lists all rows of `other` whose ID has changed.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::pdb::{io::Database, merge::merge_collections};
+///
+/// let path = "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb";
+/// let (base, other) = (Database::open(path)?, Database::open(path)?);
+/// let (collection, report) = merge_collections(&base, &other)?;
+/// assert!(report.renamed.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+pub fn merge_collections(
+    base: &Database,
+    other: &Database,
+) -> RekordcrateResult<(MergedCollection, MergeReport)> {
+    let mut report = MergeReport::default();
+
+    let (artists, artist_ids) = merge_rows(
+        EntityKind::Artist,
+        read_rows(base, PageType::Artists, |row| match row {
+            Row::Artist(artist) => Some(artist),
+            _ => None,
+        })?,
+        read_rows(other, PageType::Artists, |row| match row {
+            Row::Artist(artist) => Some(artist),
+            _ => None,
+        })?,
+        |artist| artist.id.0,
+        |artist, id| artist.id.0 = id,
+        |artist| artist.name().ok(),
+        &mut report,
+    );
+
+    let mut other_albums = read_rows(other, PageType::Albums, |row| match row {
+        Row::Album(album) => Some(album),
+        _ => None,
+    })?;
+    for album in &mut other_albums {
+        album.artist_id.0 = artist_ids.remap(album.artist_id.0);
+    }
+    let (albums, album_ids) = merge_rows(
+        EntityKind::Album,
+        read_rows(base, PageType::Albums, |row| match row {
+            Row::Album(album) => Some(album),
+            _ => None,
+        })?,
+        other_albums,
+        |album| album.id.0,
+        |album, id| album.id.0 = id,
+        |album| {
+            let name = album.name.clone().into_string().ok()?;
+            Some((name, album.artist_id.0))
+        },
+        &mut report,
+    );
+
+    let (artwork, artwork_ids) = merge_rows(
+        EntityKind::Artwork,
+        read_rows(base, PageType::Artwork, |row| match row {
+            Row::Artwork(artwork) => Some(artwork),
+            _ => None,
+        })?,
+        read_rows(other, PageType::Artwork, |row| match row {
+            Row::Artwork(artwork) => Some(artwork),
+            _ => None,
+        })?,
+        |artwork| artwork.id.0,
+        |artwork, id| artwork.id.0 = id,
+        |artwork| artwork.path.clone().into_string().ok(),
+        &mut report,
+    );
+
+    let (genres, genre_ids) = merge_rows(
+        EntityKind::Genre,
+        read_rows(base, PageType::Genres, |row| match row {
+            Row::Genre(genre) => Some(genre),
+            _ => None,
+        })?,
+        read_rows(other, PageType::Genres, |row| match row {
+            Row::Genre(genre) => Some(genre),
+            _ => None,
+        })?,
+        |genre| genre.id.0,
+        |genre, id| genre.id.0 = id,
+        |genre| genre.name.clone().into_string().ok(),
+        &mut report,
+    );
+
+    let (keys, key_ids) = merge_rows(
+        EntityKind::Key,
+        read_rows(base, PageType::Keys, |row| match row {
+            Row::Key(key) => Some(key),
+            _ => None,
+        })?,
+        read_rows(other, PageType::Keys, |row| match row {
+            Row::Key(key) => Some(key),
+            _ => None,
+        })?,
+        |key| key.id.0,
+        |key, id| {
+            key.id.0 = id;
+            key.id2 = id;
+        },
+        |key| key.name().ok(),
+        &mut report,
+    );
+
+    let (labels, label_ids) = merge_rows(
+        EntityKind::Label,
+        read_rows(base, PageType::Labels, |row| match row {
+            Row::Label(label) => Some(label),
+            _ => None,
+        })?,
+        read_rows(other, PageType::Labels, |row| match row {
+            Row::Label(label) => Some(label),
+            _ => None,
+        })?,
+        |label| label.id.0,
+        |label, id| label.id.0 = id,
+        |label| label.name.clone().into_string().ok(),
+        &mut report,
+    );
+
+    let mut other_tracks = read_rows(other, PageType::Tracks, |row| match row {
+        Row::Track(track) => Some(track),
+        _ => None,
+    })?;
+    for track in &mut other_tracks {
+        track.artist_id.0 = artist_ids.remap(track.artist_id.0);
+        track.composer_id.0 = artist_ids.remap(track.composer_id.0);
+        track.orig_artist_id.0 = artist_ids.remap(track.orig_artist_id.0);
+        track.remixer_id.0 = artist_ids.remap(track.remixer_id.0);
+        track.album_id.0 = album_ids.remap(track.album_id.0);
+        track.artwork_id.0 = artwork_ids.remap(track.artwork_id.0);
+        track.genre_id.0 = genre_ids.remap(track.genre_id.0);
+        track.key_id.0 = key_ids.remap(track.key_id.0);
+        track.label_id.0 = label_ids.remap(track.label_id.0);
+    }
+    let (tracks, track_ids) = merge_rows(
+        EntityKind::Track,
+        read_rows(base, PageType::Tracks, |row| match row {
+            Row::Track(track) => Some(track),
+            _ => None,
+        })?,
+        other_tracks,
+        |track| track.id.0,
+        |track, id| track.id.0 = id,
+        |track| track.file_path.clone().into_string().ok(),
+        &mut report,
+    );
+
+    let (playlist_tree, playlist_ids, merged_playlists) = merge_playlist_tree(
+        read_rows(base, PageType::PlaylistTree, |row| match row {
+            Row::PlaylistTreeNode(node) => Some(node),
+            _ => None,
+        })?,
+        read_rows(other, PageType::PlaylistTree, |row| match row {
+            Row::PlaylistTreeNode(node) => Some(node),
+            _ => None,
+        })?,
+        &mut report,
+    );
+
+    let mut playlist_entries = read_rows(base, PageType::PlaylistEntries, |row| match row {
+        Row::PlaylistEntry(entry) => Some(entry),
+        _ => None,
+    })?;
+    let mut existing_entries: HashSet<(u32, u32)> = playlist_entries
+        .iter()
+        .map(|entry| (entry.playlist_id.0, entry.track_id.0))
+        .collect();
+    let mut next_entry_index: HashMap<u32, u32> = HashMap::new();
+    for entry in &playlist_entries {
+        let next = next_entry_index.entry(entry.playlist_id.0).or_default();
+        *next = (*next).max(entry.entry_index + 1);
+    }
+    let mut other_entries = read_rows(other, PageType::PlaylistEntries, |row| match row {
+        Row::PlaylistEntry(entry) => Some(entry),
+        _ => None,
+    })?;
+    other_entries.sort_by_key(|entry| (entry.playlist_id.0, entry.entry_index));
+    for mut entry in other_entries {
+        entry.playlist_id.0 = playlist_ids.remap(entry.playlist_id.0);
+        entry.track_id.0 = track_ids.remap(entry.track_id.0);
+        // Playlists that exist in both databases only receive the tracks that they don't
+        // contain yet.
+        if merged_playlists.contains(&entry.playlist_id.0)
+            && !existing_entries.insert((entry.playlist_id.0, entry.track_id.0))
+        {
+            continue;
+        }
+        let next = next_entry_index.entry(entry.playlist_id.0).or_insert(1);
+        entry.entry_index = *next;
+        *next += 1;
+        playlist_entries.push(entry);
+    }
+
+    let collection = MergedCollection {
+        tracks,
+        artists,
+        albums,
+        artwork,
+        genres,
+        keys,
+        labels,
+        playlist_tree,
+        playlist_entries,
+    };
+    Ok((collection, report))
+}
+
+/// Mapping of the IDs of the second database to the IDs in the merged collection.
+#[derive(Debug, Default)]
+struct IdMap(HashMap<u32, u32>);
+
+impl IdMap {
+    /// Translate an ID of the second database.
+    ///
+    /// The ID `0` (which means "not set") and IDs of rows that don't exist (dangling references)
+    /// are returned unchanged.
+    fn remap(&self, id: u32) -> u32 {
+        if id == 0 {
+            return id;
+        }
+        self.0.get(&id).copied().unwrap_or(id)
+    }
+}
+
+/// Read all rows of the given page type and convert them using `extract`.
+fn read_rows<T>(
+    database: &Database,
+    page_type: PageType,
+    extract: impl Fn(Row) -> Option<T>,
+) -> RekordcrateResult<Vec<T>> {
+    database
+        .iter_rows(page_type)
+        .filter_map(|row| row.map(&extract).transpose())
+        .collect()
+}
+
+/// Merge the rows of one table, deduplicating identical rows by their `key`.
+///
+/// Rows without a key (e.g. because the name could not be decoded) are never merged.
+#[allow(clippy::too_many_arguments)]
+fn merge_rows<T, K: Hash + Eq>(
+    kind: EntityKind,
+    base: Vec<T>,
+    other: Vec<T>,
+    id: impl Fn(&T) -> u32,
+    set_id: impl Fn(&mut T, u32),
+    key: impl Fn(&T) -> Option<K>,
+    report: &mut MergeReport,
+) -> (Vec<T>, IdMap) {
+    let base_ids: HashSet<u32> = base.iter().map(&id).collect();
+    let mut next_id = base.iter().chain(other.iter()).map(&id).max().unwrap_or(0) + 1;
+    let mut keys: HashMap<K, u32> = base
+        .iter()
+        .filter_map(|row| key(row).map(|key| (key, id(row))))
+        .collect();
+
+    let mut rows = base;
+    let mut ids = IdMap::default();
+    for mut row in other {
+        let old_id = id(&row);
+        let row_key = key(&row);
+        if let Some(&new_id) = row_key.as_ref().and_then(|row_key| keys.get(row_key)) {
+            ids.0.insert(old_id, new_id);
+            report.merged.push(IdChange {
+                kind,
+                old_id,
+                new_id,
+            });
+            continue;
+        }
+
+        let new_id = if base_ids.contains(&old_id) {
+            let new_id = next_id;
+            next_id += 1;
+            set_id(&mut row, new_id);
+            report.renamed.push(IdChange {
+                kind,
+                old_id,
+                new_id,
+            });
+            new_id
+        } else {
+            old_id
+        };
+        ids.0.insert(old_id, new_id);
+        if let Some(row_key) = row_key {
+            keys.insert(row_key, new_id);
+        }
+        rows.push(row);
+    }
+    (rows, ids)
+}
+
+/// Add a copy of `row` to `rows`, unless an identical row (with the same `key`) already exists.
+///
+/// The copy gets a new ID if its ID is already used. Returns the ID of the row in `rows`, or
+/// `None` if `row` is `None` (e.g. because of a dangling reference).
+fn copy_row<T: Clone, K: Eq>(
+    rows: &mut Vec<T>,
+    row: Option<&T>,
+    id: impl Fn(&T) -> u32,
+    set_id: impl Fn(&mut T, u32),
+    key: impl Fn(&T) -> Option<K>,
+) -> Option<u32> {
+    let row = row?;
+    if let Some(row_key) = key(row) {
+        if let Some(existing) = rows
+            .iter()
+            .find(|other| key(other).as_ref() == Some(&row_key))
+        {
+            return Some(id(existing));
+        }
+    }
+
+    let mut row = row.clone();
+    if rows.iter().any(|other| id(other) == id(&row)) {
+        let new_id = rows.iter().map(&id).max().unwrap_or(0) + 1;
+        set_id(&mut row, new_id);
+    }
+    let row_id = id(&row);
+    rows.push(row);
+    Some(row_id)
+}
+
+/// Merge the playlist trees.
+///
+/// Returns the merged nodes, the ID mapping and the IDs of playlists that exist in both
+/// databases.
+fn merge_playlist_tree(
+    base: Vec<PlaylistTreeNode>,
+    other: Vec<PlaylistTreeNode>,
+    report: &mut MergeReport,
+) -> (Vec<PlaylistTreeNode>, IdMap, HashSet<u32>) {
+    // Parents need to be merged before their children, because the key of a node depends on the
+    // (remapped) ID of its parent.
+    let mut pending = other;
+    let mut ordered = Vec::with_capacity(pending.len());
+    let mut known: HashSet<u32> = HashSet::from([0]);
+    while !pending.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .partition(|node: &PlaylistTreeNode| known.contains(&node.parent_id.0));
+        if ready.is_empty() {
+            // The remaining nodes are orphans (or part of a cycle), add them as they are.
+            ordered.extend(rest);
+            break;
+        }
+        known.extend(ready.iter().map(|node| node.id.0));
+        ordered.extend(ready);
+        pending = rest;
+    }
+
+    let base_ids: HashSet<u32> = base.iter().map(|node| node.id.0).collect();
+    let mut next_id = base
+        .iter()
+        .chain(ordered.iter())
+        .map(|node| node.id.0)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let node_key = |node: &PlaylistTreeNode| {
+        let name = node.name.clone().into_string().ok()?;
+        Some((node.parent_id.0, name, node.is_folder()))
+    };
+    let mut keys: HashMap<_, u32> = base
+        .iter()
+        .filter_map(|node| node_key(node).map(|key| (key, node.id.0)))
+        .collect();
+
+    let mut nodes = base;
+    let mut ids = IdMap::default();
+    let mut merged = HashSet::new();
+    for mut node in ordered {
+        node.parent_id.0 = ids.remap(node.parent_id.0);
+        let old_id = node.id.0;
+        let key = node_key(&node);
+        if let Some(&new_id) = key.as_ref().and_then(|key| keys.get(key)) {
+            ids.0.insert(old_id, new_id);
+            merged.insert(new_id);
+            report.merged.push(IdChange {
+                kind: EntityKind::PlaylistTreeNode,
+                old_id,
+                new_id,
+            });
+            continue;
+        }
+
+        if base_ids.contains(&old_id) {
+            node.id.0 = next_id;
+            next_id += 1;
+            report.renamed.push(IdChange {
+                kind: EntityKind::PlaylistTreeNode,
+                old_id,
+                new_id: node.id.0,
+            });
+        }
+        ids.0.insert(old_id, node.id.0);
+        if let Some(key) = key {
+            keys.insert(key, node.id.0);
+        }
+        nodes.push(node);
+    }
+    (nodes, ids, merged)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DEMO_TRACKS_PDB: &str = "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb";
+    const NUM_ROWS_PDB: &str = "data/pdb/num_rows/export.pdb";
+
+    #[test]
+    fn merge_with_itself() {
+        let base = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let other = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let (collection, report) = merge_collections(&base, &other).unwrap();
+
+        assert!(report.renamed.is_empty());
+        assert!(report
+            .merged
+            .iter()
+            .all(|change| change.old_id == change.new_id));
+        assert_eq!(
+            collection.tracks.len(),
+            base.row_count(PageType::Tracks).unwrap()
+        );
+        assert_eq!(
+            collection.playlist_entries.len(),
+            base.row_count(PageType::PlaylistEntries).unwrap()
+        );
+    }
+
+    #[test]
+    fn merge_remaps_colliding_ids() {
+        let base = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let other = Database::open(NUM_ROWS_PDB).unwrap();
+        let (collection, report) = merge_collections(&base, &other).unwrap();
+
+        assert!(report
+            .renamed
+            .iter()
+            .any(|change| change.kind == EntityKind::Track));
+        assert_eq!(
+            collection.tracks.len() + report_count(&report, EntityKind::Track),
+            base.row_count(PageType::Tracks).unwrap() + other.row_count(PageType::Tracks).unwrap()
+        );
+
+        let track_ids: HashSet<u32> = collection.tracks.iter().map(|track| track.id.0).collect();
+        assert_eq!(track_ids.len(), collection.tracks.len());
+        let artist_ids: HashSet<u32> = collection.artists.iter().map(|a| a.id.0).collect();
+        assert_eq!(artist_ids.len(), collection.artists.len());
+        let playlist_ids: HashSet<u32> = collection
+            .playlist_tree
+            .iter()
+            .map(|node| node.id.0)
+            .collect();
+        assert_eq!(playlist_ids.len(), collection.playlist_tree.len());
+
+        // All references must point to rows of the merged collection.
+        for track in &collection.tracks {
+            assert!(track.artist_id.0 == 0 || artist_ids.contains(&track.artist_id.0));
+        }
+        for entry in &collection.playlist_entries {
+            assert!(track_ids.contains(&entry.track_id.0));
+            assert!(playlist_ids.contains(&entry.playlist_id.0));
+        }
+        for node in &collection.playlist_tree {
+            assert!(node.parent_id.0 == 0 || playlist_ids.contains(&node.parent_id.0));
+        }
+    }
+
+    #[test]
+    fn copy_track_into_empty_collection() {
+        let source = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let mut collection = MergedCollection::default();
+        let id = collection
+            .copy_track_from(&source, TrackId(1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(id, TrackId(1));
+        assert_eq!(collection.tracks.len(), 1);
+        let track = &collection.tracks[0];
+        let artist = collection
+            .artists
+            .iter()
+            .find(|artist| artist.id == track.artist_id)
+            .unwrap();
+        assert_eq!(artist.name().unwrap(), "Loopmasters");
+
+        // Copying the same track again reuses the existing rows.
+        let artists = collection.artists.len();
+        assert_eq!(
+            collection.copy_track_from(&source, TrackId(1)).unwrap(),
+            Some(id)
+        );
+        assert_eq!(collection.tracks.len(), 1);
+        assert_eq!(collection.artists.len(), artists);
+
+        // The second demo track has the same artist.
+        collection.copy_track_from(&source, TrackId(2)).unwrap();
+        assert_eq!(collection.tracks.len(), 2);
+        assert_eq!(collection.artists.len(), artists);
+
+        assert_eq!(
+            collection.copy_track_from(&source, TrackId(12345)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn copy_track_remaps_colliding_ids() {
+        let base = Database::open(NUM_ROWS_PDB).unwrap();
+        let (mut collection, _) = merge_collections(&base, &base).unwrap();
+        let tracks = collection.tracks.len();
+
+        let source = Database::open(DEMO_TRACKS_PDB).unwrap();
+        let id = collection
+            .copy_track_from(&source, TrackId(1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(collection.tracks.len(), tracks + 1);
+        assert_ne!(id, TrackId(1));
+
+        let track = collection
+            .tracks
+            .iter()
+            .find(|track| track.id == id)
+            .unwrap();
+        assert_eq!(track.title.clone().into_string().unwrap(), "Demo Track 1");
+        let artist = collection
+            .artists
+            .iter()
+            .find(|artist| artist.id == track.artist_id)
+            .unwrap();
+        assert_eq!(artist.name().unwrap(), "Loopmasters");
+        let key = collection
+            .keys
+            .iter()
+            .find(|key| key.id == track.key_id)
+            .unwrap();
+        assert_eq!(key.id.0, key.id2);
+        for ids in [
+            collection.tracks.iter().map(|t| t.id.0).collect::<Vec<_>>(),
+            collection.artists.iter().map(|a| a.id.0).collect(),
+            collection.keys.iter().map(|k| k.id.0).collect(),
+            collection.labels.iter().map(|l| l.id.0).collect(),
+        ] {
+            assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+        }
+    }
+
+    fn report_count(report: &MergeReport, kind: EntityKind) -> usize {
+        report
+            .merged
+            .iter()
+            .filter(|change| change.kind == kind)
+            .count()
+    }
+}
@@ -18,16 +18,22 @@
 //! - <https://github.com/henrybetts/Rekordbox-Decoding>
 //! - <https://github.com/flesniak/python-prodj-link/tree/master/prodj/pdblib>
 
+pub mod convert;
+pub mod io;
+pub mod merge;
 pub mod string;
 
-use crate::pdb::string::DeviceSQLString;
-use crate::util::ColorIndex;
+use crate::pdb::string::{DeviceSQLString, StringError};
+use crate::util::{
+    normalize_path, search_form, ColorIndex, MusicalKey, RekordcrateError, RekordcrateResult,
+};
 use binrw::{
     binread, binrw,
     file_ptr::FilePtrArgs,
-    io::{Read, Seek, SeekFrom, Write},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
     BinRead, BinResult, BinWrite, Endian, FilePtr16, FilePtr8,
 };
+use thiserror::Error;
 
 /// Do not read anything, but the return the current stream position of `reader`.
 fn current_offset<R: Read + Seek>(reader: &mut R, _: Endian, _: ()) -> BinResult<u64> {
@@ -87,10 +93,122 @@ pub enum PageType {
     Unknown(u32),
 }
 
+/// The kind of PDB database.
+///
+/// Device exports contain two databases: `export.pdb` (the "plain" database with tracks,
+/// playlists, etc.) and `exportExt.pdb` (the "extended" database, which contains additional data
+/// such as "My Tag" assignments). Both use the same file structure, but the page types have a
+/// different meaning.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum DatabaseType {
+    /// Plain database (`export.pdb`).
+    #[default]
+    Plain,
+    /// Extended database (`exportExt.pdb`).
+    Ext,
+}
+
+impl DatabaseType {
+    /// Number of tables in extended databases.
+    const EXT_TABLE_COUNT: usize = 9;
+
+    /// Guess the database type from the list of tables in the header.
+    ///
+    /// Extended databases only contain tables with page types up to 8, while plain databases
+    /// contain additional tables (e.g. [`PageType::Artwork`] or [`PageType::Columns`]).
+    #[must_use]
+    pub fn guess(header: &Header) -> Self {
+        let only_low_page_types = header.tables.iter().all(|table| {
+            matches!(
+                table.page_type,
+                PageType::Tracks
+                    | PageType::Genres
+                    | PageType::Artists
+                    | PageType::Albums
+                    | PageType::Labels
+                    | PageType::Keys
+                    | PageType::Colors
+                    | PageType::PlaylistTree
+                    | PageType::PlaylistEntries
+            )
+        });
+        let database_type = if only_low_page_types && header.tables.len() == Self::EXT_TABLE_COUNT {
+            Self::Ext
+        } else {
+            Self::Plain
+        };
+        log::debug!(
+            "Guessed database type {:?} from {} tables",
+            database_type,
+            header.tables.len()
+        );
+        database_type
+    }
+}
+
+/// The type of pages found inside a `Table` of an extended database (`exportExt.pdb`).
+///
+/// Extended databases use the same page type numbers as plain databases, but with a different
+/// meaning. Use `ExtPageType::from` to interpret the [`PageType`] of a table accordingly, and
+/// `PageType::from` to look up the tables of a given `ExtPageType`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExtPageType {
+    /// Holds rows of "My Tag" tags and tag categories.
+    Tags,
+    /// Holds rows that link tracks to "My Tag" tags.
+    TrackTags,
+    /// Unknown Page type.
+    Unknown(u32),
+}
+
+impl From<PageType> for ExtPageType {
+    fn from(page_type: PageType) -> Self {
+        match page_type {
+            PageType::Albums => Self::Tags,
+            PageType::Labels => Self::TrackTags,
+            PageType::Tracks => Self::Unknown(0),
+            PageType::Genres => Self::Unknown(1),
+            PageType::Artists => Self::Unknown(2),
+            PageType::Keys => Self::Unknown(5),
+            PageType::Colors => Self::Unknown(6),
+            PageType::PlaylistTree => Self::Unknown(7),
+            PageType::PlaylistEntries => Self::Unknown(8),
+            PageType::HistoryPlaylists => Self::Unknown(11),
+            PageType::HistoryEntries => Self::Unknown(12),
+            PageType::Artwork => Self::Unknown(13),
+            PageType::Columns => Self::Unknown(16),
+            PageType::History => Self::Unknown(19),
+            PageType::Unknown(value) => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<ExtPageType> for PageType {
+    fn from(page_type: ExtPageType) -> Self {
+        match page_type {
+            ExtPageType::Tags => Self::Albums,
+            ExtPageType::TrackTags => Self::Labels,
+            ExtPageType::Unknown(0) => Self::Tracks,
+            ExtPageType::Unknown(1) => Self::Genres,
+            ExtPageType::Unknown(2) => Self::Artists,
+            ExtPageType::Unknown(5) => Self::Keys,
+            ExtPageType::Unknown(6) => Self::Colors,
+            ExtPageType::Unknown(7) => Self::PlaylistTree,
+            ExtPageType::Unknown(8) => Self::PlaylistEntries,
+            ExtPageType::Unknown(11) => Self::HistoryPlaylists,
+            ExtPageType::Unknown(12) => Self::HistoryEntries,
+            ExtPageType::Unknown(13) => Self::Artwork,
+            ExtPageType::Unknown(16) => Self::Columns,
+            ExtPageType::Unknown(19) => Self::History,
+            ExtPageType::Unknown(value) => Self::Unknown(value),
+        }
+    }
+}
+
 /// Points to a table page and can be used to calculate the page's file offset by multiplying it
 /// with the page size (found in the file header).
 #[binrw]
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Hash)]
 #[brw(little)]
 pub struct PageIndex(u32);
 
@@ -140,8 +258,9 @@ pub struct Header {
     #[br(temp)]
     #[bw(calc = tables.len().try_into().expect("too many tables"))]
     num_tables: u32,
-    /// Unknown field, not used as any `empty_candidate`, points past end of file.
-    #[allow(dead_code)]
+    /// Index of the next page that is not used by any table (not even as `empty_candidate`).
+    ///
+    /// This points past the end of the file and is where new pages are allocated.
     next_unused_page: PageIndex,
     /// Unknown field.
     #[allow(dead_code)]
@@ -158,6 +277,23 @@ pub struct Header {
 }
 
 impl Header {
+    /// Index of the next page that is not used by any table.
+    ///
+    /// Pages with this index or higher are not part of any table, so this is the index of the
+    /// next page that can be allocated when adding pages to the database.
+    #[must_use]
+    pub fn next_unused_page(&self) -> &PageIndex {
+        &self.next_unused_page
+    }
+
+    /// Allocate a new page and return its index, advancing [`Header::next_unused_page`].
+    #[must_use]
+    pub(crate) fn allocate_page(&mut self) -> PageIndex {
+        let page_index = self.next_unused_page.clone();
+        self.next_unused_page = PageIndex(page_index.0 + 1);
+        page_index
+    }
+
     /// Returns pages for the given Table.
     pub fn read_pages<R: Read + Seek>(
         &self,
@@ -167,13 +303,14 @@ impl Header {
     ) -> BinResult<Vec<Page>> {
         let endian = Endian::Little;
         let (first_page, last_page) = args;
+        let database_type = DatabaseType::guess(self);
 
         let mut pages = vec![];
         let mut page_index = first_page.clone();
         loop {
             let page_offset = SeekFrom::Start(page_index.offset(self.page_size));
             reader.seek(page_offset).map_err(binrw::Error::Io)?;
-            let page = Page::read_options(reader, endian, (self.page_size,))?;
+            let page = Page::read_options(reader, endian, (self.page_size, database_type))?;
             let is_last_page = &page.page_index == last_page;
             page_index = page.next_page.clone();
             pages.push(page);
@@ -186,14 +323,158 @@ impl Header {
     }
 }
 
+/// Flags of a table page.
+///
+/// The values seen in Rekordbox exports are `0x24` and `0x34` for data pages, `0x64` for index
+/// pages (the first page of each table) and `0x00` for unused pages. According to
+/// [@flesniak](https://github.com/flesniak), `0x44` also occurs; pages with that value and with
+/// `0x64` were described as "strange" pages. The only bit with a known meaning is `0x40`, which
+/// is set for these pages and indicates that they don't contain rows. All other bits are kept
+/// as-is, so they are preserved when the page is written.
 #[binrw]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct PageFlags(u8);
+#[brw(little)]
+pub struct PageFlags(u8);
 
 impl PageFlags {
+    /// Bit that is set for index pages (the "strange" pages), which don't contain rows.
+    pub const INDEX_PAGE: u8 = 0x40;
+
+    /// Flags that Rekordbox uses for regular data pages.
+    pub const DATA_PAGE: Self = Self(0x24);
+
+    /// Create page flags from the raw value.
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// The raw value of the flags, including unknown bits.
+    #[must_use]
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if this is an index (or "strange") page, i.e. if the bit `0x40` is set.
+    #[must_use]
+    pub const fn is_index_page(&self) -> bool {
+        (self.0 & Self::INDEX_PAGE) != 0
+    }
+
+    /// Returns `true` if the page may contain rows, i.e. if it's not an index page.
+    #[must_use]
+    pub const fn page_has_data(&self) -> bool {
+        !self.is_index_page()
+    }
+
+    /// The bits whose meaning is unknown (i.e. all bits except [`PageFlags::INDEX_PAGE`]).
+    #[must_use]
+    pub const fn unknown_bits(&self) -> u8 {
+        self.0 & !Self::INDEX_PAGE
+    }
+}
+
+/// The header of a table page, without any row data.
+///
+/// This can be used to inspect pages cheaply (e.g. for counting rows), because the rows
+/// themselves are not parsed. See [`Page`] for a description of the fields.
+#[binread]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[br(little, magic = 0u32)]
+pub struct PageHeader {
+    /// Index of the page.
+    pub page_index: PageIndex,
+    /// Type of information that the rows of this page contain.
+    pub page_type: PageType,
+    /// Index of the next page with the same page type.
+    pub next_page: PageIndex,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown1: u32,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown2: u32,
+    /// Number of rows in this table (8-bit version).
+    pub num_rows_small: u8,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown3: u8,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown4: u8,
+    /// Page flags.
+    page_flags: PageFlags,
+    /// Free space in bytes in the data section of the page (excluding the row offsets in the page footer).
+    pub free_size: u16,
+    /// Used space in bytes in the data section of the page.
+    pub used_size: u16,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown5: u16,
+    /// Number of rows in this table (16-bit version).
+    pub num_rows_large: u16,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown6: u16,
+    /// Unknown field.
+    #[allow(dead_code)]
+    unknown7: u16,
+}
+
+impl PageHeader {
+    #[must_use]
+    /// Returns `true` if the page actually contains row data.
+    pub fn has_data(&self) -> bool {
+        self.page_flags.page_has_data()
+    }
+
+    #[must_use]
+    /// Flags of this page.
+    pub fn page_flags(&self) -> PageFlags {
+        self.page_flags
+    }
+
     #[must_use]
-    pub fn page_has_data(&self) -> bool {
-        (self.0 & 0x40) == 0
+    /// Number of rows on this page.
+    ///
+    /// Note that this number includes rows that have been flagged as missing by the row group.
+    pub fn num_rows(&self) -> u16 {
+        if self.num_rows_large > self.num_rows_small.into() && self.num_rows_large != 0x1fff {
+            self.num_rows_large
+        } else {
+            self.num_rows_small.into()
+        }
+    }
+
+    /// Count the rows on this page that are actually present, without parsing them.
+    ///
+    /// The reader needs to be positioned at the start of the page.
+    pub fn read_num_present_rows<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        page_size: u32,
+    ) -> BinResult<usize> {
+        if self.num_rows() == 0 || !self.has_data() {
+            return Ok(0);
+        }
+
+        let page_end = reader.stream_position()? + u64::from(page_size);
+        let num_row_groups = usize::from(self.num_rows()).div_ceil(RowGroup::MAX_ROW_COUNT);
+        let mut num_present_rows: u32 = 0;
+        for i in 0..num_row_groups {
+            let flags_offset = u64::try_from(i)
+                .ok()
+                .and_then(|index| index.checked_mul(RowGroup::SIZE))
+                .and_then(|x| page_end.checked_sub(x + 4))
+                .ok_or_else(|| binrw::Error::AssertFail {
+                    pos: page_end,
+                    message: format!("Failed to calculate seek position for row group {}", i),
+                })?;
+            reader.seek(SeekFrom::Start(flags_offset))?;
+            let row_presence_flags = u16::read_options(reader, Endian::Little, ())?;
+            num_present_rows += row_presence_flags.count_ones();
+        }
+        Ok(num_present_rows as usize)
     }
 }
 
@@ -208,7 +489,7 @@ impl PageFlags {
 #[binread]
 #[derive(Debug, PartialEq)]
 #[br(little, magic = 0u32)]
-#[br(import(page_size: u32))]
+#[br(import(page_size: u32, database_type: DatabaseType))]
 pub struct Page {
     /// Index of the page.
     ///
@@ -289,7 +570,7 @@ pub struct Page {
     page_heap_offset: u64,
     /// Row groups belonging to this page.
     #[br(seek_before(SeekFrom::Current(i64::from(page_size) - i64::from(Self::HEADER_SIZE))), restore_position)]
-    #[br(parse_with = Self::parse_row_groups, args(page_type, page_heap_offset, num_rows, page_flags))]
+    #[br(parse_with = Self::parse_row_groups, args(page_type, database_type, page_heap_offset, num_rows, page_flags))]
     pub row_groups: Vec<RowGroup>,
 }
 
@@ -301,12 +582,20 @@ impl Page {
     fn parse_row_groups<R: Read + Seek>(
         reader: &mut R,
         _: Endian,
-        args: (PageType, u64, u16, PageFlags),
+        args: (PageType, DatabaseType, u64, u16, PageFlags),
     ) -> BinResult<Vec<RowGroup>> {
         let endian = Endian::Little;
 
-        let (page_type, page_heap_offset, num_rows, page_flags) = args;
+        let (page_type, database_type, page_heap_offset, num_rows, page_flags) = args;
         if num_rows == 0 || !page_flags.page_has_data() {
+            if num_rows > 0 {
+                log::trace!(
+                    "Skipping {} rows on {:?} page without data (flags: {:#04x})",
+                    num_rows,
+                    page_type,
+                    page_flags.0
+                );
+            }
             return Ok(vec![]);
         }
 
@@ -320,7 +609,7 @@ impl Page {
             reader.seek(
                 u64::try_from(row_groups.len())
                     .ok()
-                    .and_then(|index| index.checked_mul(36))
+                    .and_then(|index| index.checked_mul(RowGroup::SIZE))
                     .and_then(|x| stream_position.checked_sub(x))
                     .map(SeekFrom::Start)
                     .ok_or_else(|| binrw::Error::AssertFail {
@@ -328,10 +617,27 @@ impl Page {
                         message: format!("Failed to calculate seek position for row group {}", i),
                     })?,
             )?;
-            let row_group = RowGroup::read_options(reader, endian, (page_type, page_heap_offset))?;
+            let row_group = RowGroup::read_options(
+                reader,
+                endian,
+                (page_type, database_type, page_heap_offset),
+            )?;
             row_groups.insert(0, row_group);
         }
 
+        let unknown_rows = row_groups
+            .iter()
+            .flat_map(RowGroup::present_rows_ref)
+            .filter(|row| matches!(row, Row::Unknown | Row::Ext(ExtRow::Unknown)))
+            .count();
+        if unknown_rows > 0 {
+            log::debug!(
+                "Skipped {} rows of {:?} page with unknown row format",
+                unknown_rows,
+                page_type
+            );
+        }
+
         Ok(row_groups)
     }
 
@@ -341,6 +647,12 @@ impl Page {
         self.page_flags.page_has_data()
     }
 
+    #[must_use]
+    /// Flags of this page.
+    pub fn page_flags(&self) -> PageFlags {
+        self.page_flags
+    }
+
     #[must_use]
     /// Number of rows on this page.
     ///
@@ -352,6 +664,211 @@ impl Page {
             self.num_rows_small.into()
         }
     }
+
+    /// Compute the used and free space of the data section of this page (i.e. the values of
+    /// `used_size` and `free_size`) from the row data and the row groups.
+    ///
+    /// The used space extends up to the end of the last row on the heap (when written), rounded up
+    /// to a multiple of 4 bytes. The free space is the rest of the page, excluding the header and
+    /// the row groups in the page footer, which hold 2 bytes per row offset and 4 bytes for the
+    /// presence flags of each row group.
+    ///
+    /// **Note:** Rows that have been deleted (i.e. flagged as missing by the row group) may still
+    /// occupy space on the heap, but their size is unknown. Hence, the result only matches the
+    /// stored values for pages without deleted rows at the end of the heap.
+    pub fn compute_sizes(&self, page_size: u32) -> BinResult<(u16, u16)> {
+        let mut heap_end: u64 = 0;
+        for row_offset in self
+            .row_groups
+            .iter()
+            .flat_map(|row_group| row_group.rows.iter().flatten())
+        {
+            let mut writer = Cursor::new(Vec::new());
+            row_offset
+                .value
+                .write_options(&mut writer, Endian::Little, ())?;
+            heap_end = heap_end.max(u64::from(row_offset.ptr) + writer.position());
+        }
+        let used_size = heap_end.next_multiple_of(4);
+
+        let footer_size = 4 * u64::try_from(self.row_groups.len()).unwrap_or(u64::MAX)
+            + 2 * u64::from(self.num_rows());
+        let free_size = u64::from(page_size)
+            .checked_sub(u64::from(Self::HEADER_SIZE) + used_size + footer_size)
+            .ok_or_else(|| binrw::Error::AssertFail {
+                pos: 0,
+                message: format!(
+                    "Rows ({} bytes) and row groups ({} bytes) exceed the page size",
+                    used_size, footer_size
+                ),
+            })?;
+
+        let to_u16 = |size: u64| {
+            u16::try_from(size).map_err(|_| binrw::Error::AssertFail {
+                pos: 0,
+                message: format!("Size of {} bytes does not fit into 16 bits", size),
+            })
+        };
+        Ok((to_u16(used_size)?, to_u16(free_size)?))
+    }
+
+    /// Iterate over mutable references to the rows on this page that are actually present.
+    pub(crate) fn rows_mut(&mut self) -> impl Iterator<Item = &mut Row> + '_ {
+        self.row_groups
+            .iter_mut()
+            .flat_map(|row_group| row_group.rows.iter_mut().rev().flatten())
+            .map(|row_offset| &mut row_offset.value)
+    }
+
+    /// Write the present rows of this page to the heap of `data` (the raw bytes of this page)
+    /// and update the row offsets, `used_size` and `free_size` in both `self` and `data`.
+    ///
+    /// The rows keep their order on the heap and are aligned to 4 bytes, like in Rekordbox
+    /// exports. The heap space of deleted rows is reclaimed.
+    pub(crate) fn rewrite_heap(&mut self, page_size: u32, data: &mut [u8]) -> BinResult<()> {
+        let mut row_offsets: Vec<&mut FilePtr16<Row>> = self
+            .row_groups
+            .iter_mut()
+            .flat_map(|row_group| row_group.rows.iter_mut().flatten())
+            .collect();
+        row_offsets.sort_by_key(|row_offset| row_offset.ptr);
+        let mut heap = Cursor::new(Vec::new());
+        for row_offset in row_offsets {
+            let position = heap.position().next_multiple_of(4);
+            heap.set_position(position);
+            row_offset.ptr = u16::try_from(position).map_err(|_| binrw::Error::AssertFail {
+                pos: position,
+                message: "Row offset does not fit into 16 bits".to_string(),
+            })?;
+            row_offset
+                .value
+                .write_options(&mut heap, Endian::Little, ())?;
+            // Some rows (e.g. artists) restore the stream position after writing their strings, so
+            // the next row has to start after the end of the written data.
+            heap.seek(SeekFrom::End(0))?;
+        }
+
+        let (used_size, free_size) = self.compute_sizes(page_size)?;
+        let heap_size = self.used_size.max(used_size);
+        if u32::from(heap_size) > page_size.saturating_sub(Self::HEADER_SIZE) {
+            return Err(binrw::Error::AssertFail {
+                pos: u64::from(Self::HEADER_SIZE),
+                message: format!("Heap ({heap_size} bytes) exceeds the page size"),
+            });
+        }
+        let heap_start = Self::HEADER_SIZE as usize;
+        let heap_end = heap_start + usize::from(heap_size);
+        data[heap_start..heap_end].fill(0);
+        let heap = heap.into_inner();
+        data[heap_start..heap_start + heap.len()].copy_from_slice(&heap);
+        self.used_size = used_size;
+        self.free_size = free_size;
+        data[28..30].copy_from_slice(&free_size.to_le_bytes());
+        data[30..32].copy_from_slice(&used_size.to_le_bytes());
+
+        // Row groups are stored backwards from the end of the page, so the last one is located at
+        // the very end.
+        let mut row_group_end = page_size as usize;
+        for row_group in self.row_groups.iter().rev() {
+            for (i, row_offset) in row_group.rows.iter().enumerate() {
+                if let Some(row_offset) = row_offset {
+                    let position = row_group_end - 4 - 2 * (i + 1);
+                    data[position..position + 2].copy_from_slice(&row_offset.ptr.to_le_bytes());
+                }
+            }
+            row_group_end -= RowGroup::SIZE as usize;
+        }
+        Ok(())
+    }
+
+    /// Append `row` to this page and write the changes to `data` (the raw bytes of this page).
+    ///
+    /// The row gets the next free row offset (adding a row group if necessary) and the heap is
+    /// rewritten (see [`Page::rewrite_heap`]). The number of row offsets and the number of rows
+    /// are packed into the three bytes at offset `0x18` of the page header (13 and 11 bits), which
+    /// are updated accordingly. Once the number of row offsets no longer fits into
+    /// `num_rows_small`, `num_rows_large` is updated as well. Returns `false` without modifying the
+    /// page if the row does not fit.
+    pub(crate) fn insert_row(
+        &mut self,
+        row: Row,
+        page_size: u32,
+        data: &mut [u8],
+    ) -> BinResult<bool> {
+        let row_counts = u32::from_le_bytes([self.num_rows_small, self.unknown3, self.unknown4, 0]);
+        let num_row_offsets = row_counts & 0x1FFF;
+        let num_rows = row_counts >> 13;
+        let row_index = usize::try_from(num_row_offsets).unwrap_or(usize::MAX);
+        let (group_index, slot) = (
+            row_index / RowGroup::MAX_ROW_COUNT,
+            row_index % RowGroup::MAX_ROW_COUNT,
+        );
+
+        let mut row_data = Cursor::new(Vec::new());
+        row.write_options(&mut row_data, Endian::Little, ())?;
+        let (_, free_size) = self.compute_sizes(page_size)?;
+        let new_group_size = if slot == 0 { 4 } else { 0 };
+        let required_size = row_data.into_inner().len().next_multiple_of(4) + 2 + new_group_size;
+        // `0x1FFF` has a special meaning for `num_rows_large`, so it can't be used as a row count.
+        if num_row_offsets + 1 >= 0x1FFF || usize::from(free_size) < required_size {
+            return Ok(false);
+        }
+
+        // The row groups are stored in reverse order, i.e. the first row group is the last one.
+        if group_index == self.row_groups.len() {
+            self.row_groups.insert(
+                0,
+                RowGroup {
+                    rows: std::array::from_fn(|_| None),
+                    row_presence_flags: 0,
+                    unknown: 0,
+                },
+            );
+        }
+        let row_group_position = self.row_groups.len() - 1 - group_index;
+        let row_group = &mut self.row_groups[row_group_position];
+        if row_group.rows[slot].is_some() {
+            return Err(binrw::Error::AssertFail {
+                pos: 0,
+                message: format!("Row offset {} is already in use", row_index),
+            });
+        }
+        row_group.rows[slot] = Some(FilePtr16 {
+            ptr: u16::MAX,
+            value: row,
+        });
+        row_group.row_presence_flags |= 1 << slot;
+        let row_presence_flags = row_group.row_presence_flags;
+
+        let [num_rows_small, unknown3, unknown4, _] =
+            ((num_row_offsets + 1) | ((num_rows + 1) << 13)).to_le_bytes();
+        self.num_rows_small = num_rows_small;
+        self.unknown3 = unknown3;
+        self.unknown4 = unknown4;
+        data[24..27].copy_from_slice(&[num_rows_small, unknown3, unknown4]);
+        let num_rows_large = u16::try_from(num_row_offsets + 1).unwrap_or(u16::MAX);
+        if num_rows_large > u16::from(u8::MAX) || self.num_rows_large > u16::from(num_rows_small) {
+            self.num_rows_large = num_rows_large;
+            data[34..36].copy_from_slice(&num_rows_large.to_le_bytes());
+        }
+        let flags_position = page_size as usize - RowGroup::SIZE as usize * group_index - 4;
+        data[flags_position..flags_position + 2].copy_from_slice(&row_presence_flags.to_le_bytes());
+
+        self.rewrite_heap(page_size, data)?;
+        Ok(true)
+    }
+
+    /// Iterate over references to the rows on this page that are actually present, in order.
+    pub fn rows(&self) -> impl Iterator<Item = &Row> + '_ {
+        self.row_groups.iter().flat_map(RowGroup::present_rows_ref)
+    }
+
+    /// Consume the page and return the rows that are actually present, in order.
+    pub fn into_rows(self) -> impl Iterator<Item = Row> {
+        self.row_groups
+            .into_iter()
+            .flat_map(RowGroup::into_present_rows)
+    }
 }
 
 /// A group of row indices, which are built backwards from the end of the page. Holds up to sixteen
@@ -372,18 +889,36 @@ pub struct RowGroup {
 
 impl RowGroup {
     const MAX_ROW_COUNT: usize = 16;
+    /// Size of a row group in bytes (16 row offsets, the presence flags and an unknown field).
+    const SIZE: u64 = 36;
 
     /// Return the ordered list of row offsets that are actually present.
     pub fn present_rows(&self) -> impl Iterator<Item = Row> + '_ {
+        self.present_rows_ref().cloned()
+    }
+
+    /// Return references to the rows that are actually present, in order.
+    ///
+    /// In contrast to [`RowGroup::present_rows`], this does not clone the rows.
+    pub fn present_rows_ref(&self) -> impl Iterator<Item = &Row> + '_ {
         self.rows
             .iter()
             .rev()
-            .filter_map(|row_offset| row_offset.as_ref().map(|r| r.value.clone()))
+            .filter_map(|row_offset| row_offset.as_ref().map(|r| &r.value))
+    }
+
+    /// Consume the row group and return the rows that are actually present, in order.
+    pub fn into_present_rows(self) -> impl Iterator<Item = Row> {
+        self.rows
+            .into_iter()
+            .rev()
+            .flatten()
+            .map(FilePtr16::into_inner)
     }
 }
 
 impl BinRead for RowGroup {
-    type Args<'a> = (PageType, u64);
+    type Args<'a> = (PageType, DatabaseType, u64);
 
     /// Read a row group from the reader.
     ///
@@ -392,7 +927,7 @@ impl BinRead for RowGroup {
     fn read_options<R: Read + Seek>(
         reader: &mut R,
         endian: Endian,
-        (page_type, page_heap_offset): Self::Args<'_>,
+        (page_type, database_type, page_heap_offset): Self::Args<'_>,
     ) -> BinResult<Self> {
         let row_group_end_position = reader.stream_position()?;
         reader.seek(SeekFrom::Current(-4))?;
@@ -431,7 +966,7 @@ impl BinRead for RowGroup {
                     endian,
                     FilePtrArgs {
                         offset: page_heap_offset,
-                        inner: (page_type,),
+                        inner: (page_type, database_type),
                     },
                 )?;
                 rows[i] = Some(row);
@@ -561,6 +1096,49 @@ pub struct Artist {
 }
 
 impl Artist {
+    /// Create an artist row with the given ID and name.
+    ///
+    /// The row uses the layout with the 8-bit name offset (subtype `0x60`), like the artist rows
+    /// created by Rekordbox.
+    pub fn new(id: ArtistId, name: &str) -> Result<Self, StringError> {
+        Ok(Self {
+            subtype: 0x60,
+            index_shift: 0,
+            id,
+            unknown1: 3,
+            ofs_name_near: 10,
+            ofs_name_far: None,
+            name: DeviceSQLString::new(name.to_string())?,
+        })
+    }
+
+    /// ID of this row.
+    #[must_use]
+    pub fn id(&self) -> ArtistId {
+        self.id
+    }
+
+    /// Name of this artist.
+    pub fn name(&self) -> Result<String, StringError> {
+        self.name.clone().into_string()
+    }
+
+    /// Name of this artist, normalized for case- and accent-insensitive searching (see
+    /// [`Genre::search_name`]).
+    pub fn search_name(&self) -> Result<String, StringError> {
+        self.name().map(|name| search_form(&name))
+    }
+
+    /// Offset of the name string relative to the start of the row.
+    ///
+    /// Depending on the row subtype, this is either an 8-bit (`0x60`) or a 16-bit (`0x64`)
+    /// offset. This is only useful for researching the row layout.
+    #[must_use]
+    pub fn name_offset(&self) -> u16 {
+        self.ofs_name_far
+            .unwrap_or_else(|| self.ofs_name_near.into())
+    }
+
     fn calculate_name_seek(ofs_near: u8, ofs_far: &Option<u16>) -> SeekFrom {
         let offset: u16 = ofs_far.map_or_else(|| ofs_near.into(), |v| v - 2) - 10;
         SeekFrom::Current(offset.into())
@@ -578,9 +1156,23 @@ pub struct Artwork {
     path: DeviceSQLString,
 }
 
+impl Artwork {
+    /// ID of this row.
+    #[must_use]
+    pub fn id(&self) -> ArtworkId {
+        self.id
+    }
+
+    /// Path of the image file relative to the root of the export (e.g.
+    /// `/PIONEER/Artwork/00001/a1.jpg`).
+    pub fn path(&self) -> Result<String, StringError> {
+        self.path.clone().into_string()
+    }
+}
+
 /// Contains numeric color ID
 #[binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[brw(little)]
 pub struct Color {
     /// Unknown field.
@@ -595,9 +1187,31 @@ pub struct Color {
     name: DeviceSQLString,
 }
 
+impl Color {
+    /// Numeric color ID.
+    #[must_use]
+    pub fn color(&self) -> ColorIndex {
+        self.color.clone()
+    }
+
+    /// User-defined name of the color.
+    pub fn name(&self) -> Result<String, StringError> {
+        self.name.clone().into_string()
+    }
+
+    /// Change the user-defined name of the color.
+    ///
+    /// Use [`Database::set_color_name`](io::Database::set_color_name) to rename a color in a
+    /// database file.
+    pub fn set_name(&mut self, name: &str) -> Result<(), StringError> {
+        self.name = DeviceSQLString::new(name.to_string())?;
+        Ok(())
+    }
+}
+
 /// Represents a musical genre.
 #[binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[brw(little)]
 pub struct Genre {
     /// ID of this row.
@@ -606,6 +1220,28 @@ pub struct Genre {
     name: DeviceSQLString,
 }
 
+impl Genre {
+    /// ID of this row.
+    #[must_use]
+    pub fn id(&self) -> GenreId {
+        self.id
+    }
+
+    /// Name of the genre.
+    pub fn name(&self) -> Result<String, StringError> {
+        self.name.clone().into_string()
+    }
+
+    /// Name of the genre, normalized for searching.
+    ///
+    /// The database does not contain separate search strings, so the name is converted to
+    /// lowercase and diacritics are removed (e.g. `Électro` becomes `electro`), which allows
+    /// case- and accent-insensitive matching like the search function of the players.
+    pub fn search_name(&self) -> Result<String, StringError> {
+        self.name().map(|name| search_form(&name))
+    }
+}
+
 /// Represents a history playlist.
 #[binrw]
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -630,28 +1266,129 @@ pub struct HistoryEntry {
     entry_index: u32,
 }
 
+impl HistoryEntry {
+    /// ID of the track played at this position in the playlist.
+    #[must_use]
+    pub fn track_id(&self) -> TrackId {
+        self.track_id
+    }
+
+    /// ID of the history playlist.
+    #[must_use]
+    pub fn playlist_id(&self) -> HistoryPlaylistId {
+        self.playlist_id
+    }
+
+    /// Position within the playlist.
+    #[must_use]
+    pub fn entry_index(&self) -> u32 {
+        self.entry_index
+    }
+}
+
 /// Represents a musical key.
 #[binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[brw(little)]
 pub struct Key {
     /// ID of this row.
     id: KeyId,
     /// Apparently a second copy of the row ID.
+    ///
+    /// This is equal to `id` in all known exports (see [`Database::key_id_mismatches`]).
+    ///
+    /// [`Database::key_id_mismatches`]: crate::pdb::io::Database::key_id_mismatches
     id2: u32,
     /// Name of the key.
     name: DeviceSQLString,
 }
 
-/// Represents a record label.
-#[binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
-#[brw(little)]
-pub struct Label {
+impl Key {
     /// ID of this row.
-    id: LabelId,
-    /// Name of the record label.
-    name: DeviceSQLString,
+    #[must_use]
+    pub fn id(&self) -> KeyId {
+        self.id
+    }
+
+    /// Second ID field of this row, which apparently is a copy of the [row ID](Self::id).
+    #[must_use]
+    pub fn id2(&self) -> u32 {
+        self.id2
+    }
+
+    /// Returns `true` if the second ID field matches the row ID.
+    #[must_use]
+    pub fn has_matching_ids(&self) -> bool {
+        self.id.0 == self.id2
+    }
+
+    /// Name of the key as stored in the database (usually in classical notation, e.g. `Dm`).
+    pub fn name(&self) -> Result<String, StringError> {
+        self.name.clone().into_string()
+    }
+
+    /// Name of the key, normalized for case- and accent-insensitive searching (see
+    /// [`Genre::search_name`]).
+    pub fn search_name(&self) -> Result<String, StringError> {
+        self.name().map(|name| search_form(&name))
+    }
+
+    /// Interpret the key name as [`MusicalKey`].
+    ///
+    /// Returns `None` if the name cannot be decoded or is not a recognized key name (e.g. a
+    /// free-text value).
+    #[must_use]
+    pub fn musical_key(&self) -> Option<MusicalKey> {
+        self.name().ok().and_then(|name| MusicalKey::parse(&name))
+    }
+
+    /// Key name in classical notation (e.g. `Dm`), if the key is recognized.
+    #[must_use]
+    pub fn classical(&self) -> Option<&'static str> {
+        self.musical_key().map(|key| key.classical())
+    }
+
+    /// Key name in Camelot notation (e.g. `7A`), if the key is recognized.
+    #[must_use]
+    pub fn camelot(&self) -> Option<String> {
+        self.musical_key().map(|key| key.camelot())
+    }
+
+    /// Key name in Open Key notation (e.g. `12m`), if the key is recognized.
+    #[must_use]
+    pub fn open_key(&self) -> Option<String> {
+        self.musical_key().map(|key| key.open_key())
+    }
+}
+
+/// Represents a record label.
+#[binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[brw(little)]
+pub struct Label {
+    /// ID of this row.
+    id: LabelId,
+    /// Name of the record label.
+    name: DeviceSQLString,
+}
+
+impl Label {
+    /// ID of this row.
+    #[must_use]
+    pub fn id(&self) -> LabelId {
+        self.id
+    }
+
+    /// Name of the record label.
+    pub fn name(&self) -> Result<String, StringError> {
+        self.name.clone().into_string()
+    }
+
+    /// Name of the record label, normalized for case- and accent-insensitive searching (see
+    /// [`Genre::search_name`]).
+    pub fn search_name(&self) -> Result<String, StringError> {
+        self.name().map(|name| search_form(&name))
+    }
 }
 
 /// Represents a node in the playlist tree (either a folder or a playlist).
@@ -663,7 +1400,7 @@ pub struct PlaylistTreeNode {
     pub parent_id: PlaylistTreeNodeId,
     /// Unknown field.
     unknown: u32,
-    /// Sort order indicastor.
+    /// Position of this node among the children of its parent folder (starting at 1).
     sort_order: u32,
     /// ID of this row.
     pub id: PlaylistTreeNodeId,
@@ -679,6 +1416,21 @@ impl PlaylistTreeNode {
     pub fn is_folder(&self) -> bool {
         self.node_is_folder > 0
     }
+
+    /// Position of this node among the children of its parent folder, as shown on the device,
+    /// starting at `0`.
+    #[must_use]
+    pub fn sort_order(&self) -> u32 {
+        self.sort_order
+    }
+
+    /// Change the position of this node among the children of its parent folder.
+    ///
+    /// Use [`Database::reorder_playlists`](io::Database::reorder_playlists) to reorder the
+    /// playlists in a database file.
+    pub fn set_sort_order(&mut self, sort_order: u32) {
+        self.sort_order = sort_order;
+    }
 }
 
 /// Represents a track entry in a playlist.
@@ -694,6 +1446,26 @@ pub struct PlaylistEntry {
     playlist_id: PlaylistTreeNodeId,
 }
 
+impl PlaylistEntry {
+    /// Position within the playlist.
+    #[must_use]
+    pub fn entry_index(&self) -> u32 {
+        self.entry_index
+    }
+
+    /// ID of the track played at this position in the playlist.
+    #[must_use]
+    pub fn track_id(&self) -> TrackId {
+        self.track_id
+    }
+
+    /// ID of the playlist.
+    #[must_use]
+    pub fn playlist_id(&self) -> PlaylistTreeNodeId {
+        self.playlist_id
+    }
+}
+
 /// Contains the kinds of Metadata Categories tracks can be browsed by
 /// on CDJs.
 #[binrw]
@@ -717,6 +1489,63 @@ pub struct ColumnEntry {
     pub column_name: DeviceSQLString,
 }
 
+impl ColumnEntry {
+    /// ID of this row.
+    ///
+    /// The IDs are assigned in the order in which the categories appear in the browse menu
+    /// (starting with `GENRE`, `ARTIST`, `ALBUM`, `TRACK`, `BPM`, ...).
+    #[must_use]
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Name of the browse category (e.g. `GENRE`), without the annotation characters that
+    /// surround it in the database.
+    pub fn name(&self) -> Result<String, StringError> {
+        let name = self.column_name.clone().into_string()?;
+        Ok(name
+            .trim_start_matches('\u{fffa}')
+            .trim_end_matches('\u{fffb}')
+            .to_string())
+    }
+}
+
+/// Format of the audio file of a track.
+#[binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[brw(little)]
+pub enum FileType {
+    /// MPEG-1 Audio Layer III (`.mp3`).
+    #[brw(magic = 1u16)]
+    Mp3,
+    /// AAC in an MPEG-4 container (`.m4a`).
+    #[brw(magic = 4u16)]
+    M4a,
+    /// Free Lossless Audio Codec (`.flac`).
+    #[brw(magic = 5u16)]
+    Flac,
+    /// Waveform Audio File Format (`.wav`).
+    #[brw(magic = 11u16)]
+    Wav,
+    /// Audio Interchange File Format (`.aif`, `.aiff`).
+    #[brw(magic = 12u16)]
+    Aiff,
+    /// Unknown file type (e.g. `0` in some exports).
+    Unknown(u16),
+}
+
+impl FileType {
+    /// Returns `true` if the file type uses lossless compression (FLAC) or is uncompressed (WAV,
+    /// AIFF), and `false` for lossy formats (MP3, AAC) and unknown file types.
+    #[must_use]
+    pub fn is_lossless(&self) -> bool {
+        match self {
+            Self::Flac | Self::Wav | Self::Aiff => true,
+            Self::Mp3 | Self::M4a | Self::Unknown(_) => false,
+        }
+    }
+}
+
 /// Contains the album name, along with an ID of the corresponding artist.
 #[binread]
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -785,8 +1614,8 @@ pub struct Track {
     color: ColorIndex,
     /// User rating of this track (0 to 5 starts).
     rating: u8,
-    /// Unknown field, apparently always "1".
-    unknown6: u16,
+    /// Format of the audio file.
+    file_type: FileType,
     /// Unknown field (alternating "2" and "3"?).
     unknown7: u16,
     /// International Standard Recording Code (ISRC), in mangled format.
@@ -807,7 +1636,8 @@ pub struct Track {
     /// Unknown string field (named by [@flesniak](https://github.com/flesniak)).
     #[br(offset = base_offset, parse_with = FilePtr16::parse)]
     message: DeviceSQLString,
-    /// Probably describes whether the track is public on kuvo.com (?). Value is either "ON" or empty string.
+    /// Whether information about this track is published on KUVO while it is played (see
+    /// [`RemoteInfo`]). Value is either "ON" or empty string.
     #[br(offset = base_offset, parse_with = FilePtr16::parse)]
     kuvo_public: DeviceSQLString,
     /// Determines if hotcues should be autoloaded. Value is either "ON" or empty string.
@@ -854,6 +1684,410 @@ pub struct Track {
     file_path: DeviceSQLString,
 }
 
+/// Audio properties of a track, returned by [`Track::audio_properties`].
+///
+/// The number of channels is not stored in the database, so it's not included here.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AudioProperties {
+    /// Sample rate in Hz (e.g. `44100`).
+    pub sample_rate_hz: u32,
+    /// Bitrate in kbit/s (e.g. `320`). For uncompressed files, this is the bitrate of the PCM
+    /// stream (e.g. `1411` for 16-bit stereo audio at 44.1 kHz).
+    pub bitrate_kbps: u32,
+    /// Number of bits per sample (e.g. `16`).
+    pub sample_depth_bits: u16,
+}
+
+/// Track information that is shared with Pioneer's online services, returned by
+/// [`Track::remote_info`].
+///
+/// When "Publish track information" is enabled in Rekordbox, the players send the title and
+/// artist of the track that is played to KUVO (Pioneer's former online service for sharing DJ
+/// sets and club playlists). The KUVO service has been discontinued, but
+/// the fields are still written to exports.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RemoteInfo {
+    /// Whether information about this track is published while it is played.
+    pub publish_track_information: bool,
+    /// Message string of the track (usually empty). Its purpose is not known for sure, but since it
+    /// is stored next to the publish flag, it is probably published along with the track
+    /// information.
+    pub message: String,
+}
+
+/// Information about the analysis of a track, returned by [`Track::analysis_info`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AnalysisInfo {
+    /// Date on which the track was analyzed, or `None` if the track has not been analyzed (or the
+    /// date is malformed).
+    pub analyze_date: Option<chrono::NaiveDate>,
+    /// Path of the `.DAT` analysis file relative to the root of the export (see
+    /// [`Track::normalized_file_path`]), or `None` if the track has not been analyzed.
+    pub analyze_path: Option<std::path::PathBuf>,
+}
+
+/// Tempo of a track in beats per minute, returned by [`Track::bpm`].
+///
+/// The [`Display`](std::fmt::Display) implementation always uses two decimal places (e.g.
+/// `128.00`), which matches the precision stored in the database.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Bpm(pub f64);
+
+impl Bpm {
+    /// Highest tempo in centi-BPM that is considered valid (655.35 BPM).
+    pub const MAX_CENTI_BPM: u32 = 65535;
+
+    /// Create a tempo from a value in centi-BPM (= 1/100 BPM), as stored in the database.
+    ///
+    /// Returns an error for `0` (which is used for tracks that have not been analyzed) and values
+    /// above [`Bpm::MAX_CENTI_BPM`], which only occur in corrupted rows.
+    pub fn from_centi_bpm(centi_bpm: u32) -> Result<Self, InvalidTempoError> {
+        if centi_bpm == 0 || centi_bpm > Self::MAX_CENTI_BPM {
+            return Err(InvalidTempoError(centi_bpm));
+        }
+        Ok(Self(f64::from(centi_bpm) / 100.0))
+    }
+}
+
+impl std::fmt::Display for Bpm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2}", self.0)
+    }
+}
+
+/// Error returned by [`Bpm::from_centi_bpm`] for tempo values outside of the valid range.
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[error("invalid tempo of {0} centi-BPM (expected 1 to 65535)")]
+pub struct InvalidTempoError(pub u32);
+
+impl Track {
+    /// Number of strings in a track row.
+    const STRING_COUNT: usize = 21;
+    /// Position of the string offset table relative to the start of the row.
+    const STRING_OFFSETS_POSITION: u64 = 0x5e;
+    /// Highest possible user rating (in stars).
+    pub const MAX_RATING: u8 = 5;
+
+    /// ID of this row.
+    #[must_use]
+    pub fn id(&self) -> TrackId {
+        self.id
+    }
+
+    /// Artist row ID for this track (`ArtistId(0)` if unset).
+    #[must_use]
+    pub fn artist_id(&self) -> ArtistId {
+        self.artist_id
+    }
+
+    /// Composer of this track as artist row ID (`ArtistId(0)` if unset).
+    #[must_use]
+    pub fn composer_id(&self) -> ArtistId {
+        self.composer_id
+    }
+
+    /// Artist row ID of the remixer (`ArtistId(0)` if unset).
+    #[must_use]
+    pub fn remixer_id(&self) -> ArtistId {
+        self.remixer_id
+    }
+
+    /// Artist row ID of the original performer (`ArtistId(0)` if unset).
+    #[must_use]
+    pub fn original_artist_id(&self) -> ArtistId {
+        self.orig_artist_id
+    }
+
+    /// ID of the genre of this track (`GenreId(0)` if the track has no genre).
+    #[must_use]
+    pub fn genre_id(&self) -> GenreId {
+        self.genre_id
+    }
+
+    /// Title of this track.
+    pub fn title(&self) -> Result<String, StringError> {
+        self.title.clone().into_string()
+    }
+
+    /// Change the title of this track.
+    ///
+    /// The string offsets are computed when the row is written, so a title with a different
+    /// length shifts the offsets of the subsequent strings accordingly. Use
+    /// [`Database::set_track_title`](io::Database::set_track_title) to rename a track in a
+    /// database file.
+    pub fn set_title(&mut self, title: &str) -> Result<(), StringError> {
+        self.title = DeviceSQLString::new(title.to_string())?;
+        Ok(())
+    }
+
+    /// Set the artist of this track to the artist row with the given ID.
+    pub fn set_artist_id(&mut self, artist_id: ArtistId) {
+        self.artist_id = artist_id;
+    }
+
+    /// Name of the mix of this track (e.g. `Extended Mix`), or an empty string if not set.
+    pub fn mix_name(&self) -> Result<String, StringError> {
+        self.mix_name.clone().into_string()
+    }
+
+    /// Change the name of the mix of this track (use an empty string to remove it).
+    pub fn set_mix_name(&mut self, mix_name: &str) -> Result<(), StringError> {
+        self.mix_name = DeviceSQLString::new(mix_name.to_string())?;
+        Ok(())
+    }
+
+    /// Path of the audio file relative to the root of the export (e.g.
+    /// `/Contents/Loopmasters/UnknownAlbum/Demo Track 1.mp3`).
+    pub fn file_path(&self) -> Result<String, StringError> {
+        self.file_path.clone().into_string()
+    }
+
+    /// Path of the audio file relative to the root of the export, with the platform's path
+    /// separators.
+    ///
+    /// Unlike [`Track::file_path`], this also handles paths with backslashes as separators (which
+    /// can be found in some exports created on Windows), so the result can be joined to the
+    /// export root directly.
+    pub fn normalized_file_path(&self) -> Result<std::path::PathBuf, StringError> {
+        self.file_path().map(|path| normalize_path(&path))
+    }
+
+    /// ID of the artwork of this track (`ArtworkId(0)` if the track has no artwork).
+    #[must_use]
+    pub fn artwork_id(&self) -> ArtworkId {
+        self.artwork_id
+    }
+
+    /// Color of this track ([`ColorIndex::None`] if no color is assigned).
+    #[must_use]
+    pub fn color(&self) -> ColorIndex {
+        self.color.clone()
+    }
+
+    /// Assign a color to this track.
+    pub fn set_color(&mut self, color: ColorIndex) {
+        self.color = color;
+    }
+
+    /// User rating of this track (0 to 5 stars).
+    #[must_use]
+    pub fn rating(&self) -> u8 {
+        self.rating
+    }
+
+    /// Set the user rating of this track.
+    ///
+    /// Returns a [`RekordcrateError::InvalidRating`] error if the rating is greater than
+    /// [`Track::MAX_RATING`].
+    pub fn set_rating(&mut self, rating: u8) -> RekordcrateResult<()> {
+        if rating > Self::MAX_RATING {
+            return Err(RekordcrateError::InvalidRating(rating));
+        }
+        self.rating = rating;
+        Ok(())
+    }
+
+    /// Number of this track on its album, or `None` if not set.
+    #[must_use]
+    pub fn track_number(&self) -> Option<u32> {
+        (self.track_number != 0).then_some(self.track_number)
+    }
+
+    /// Number of the disc that this track is on, or `None` if not set.
+    #[must_use]
+    pub fn disc_number(&self) -> Option<u16> {
+        (self.disc_number != 0).then_some(self.disc_number)
+    }
+
+    /// Playback duration of this track in seconds (at normal speed).
+    #[must_use]
+    pub fn duration(&self) -> u16 {
+        self.duration
+    }
+
+    /// Whether the hot cues of this track are loaded automatically when the track is loaded.
+    #[must_use]
+    pub fn autoload_hotcues(&self) -> bool {
+        self.autoload_hotcues.is_flag_set()
+    }
+
+    /// Set whether the hot cues of this track are loaded automatically when the track is loaded.
+    pub fn set_autoload_hotcues(&mut self, value: bool) {
+        self.autoload_hotcues = DeviceSQLString::from_flag(value);
+    }
+
+    /// Whether information about this track is published while it is played (e.g. on KUVO).
+    #[must_use]
+    pub fn publish_track_information(&self) -> bool {
+        self.kuvo_public.is_flag_set()
+    }
+
+    /// Set whether information about this track is published while it is played (e.g. on KUVO).
+    pub fn set_publish_track_information(&mut self, value: bool) {
+        self.kuvo_public = DeviceSQLString::from_flag(value);
+    }
+
+    /// Message of this track, or an empty string if not set (see [`RemoteInfo::message`]).
+    pub fn message(&self) -> Result<String, StringError> {
+        self.message.clone().into_string()
+    }
+
+    /// Change the message of this track (use an empty string to remove it).
+    pub fn set_message(&mut self, message: &str) -> Result<(), StringError> {
+        self.message = DeviceSQLString::new(message.to_string())?;
+        Ok(())
+    }
+
+    /// Track information that is shared with online services (see [`RemoteInfo`]).
+    pub fn remote_info(&self) -> Result<RemoteInfo, StringError> {
+        Ok(RemoteInfo {
+            publish_track_information: self.publish_track_information(),
+            message: self.message.clone().into_string()?,
+        })
+    }
+
+    /// Path of the `.DAT` analysis file relative to the root of the export (e.g.
+    /// `/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT`), or an empty string if the track has not
+    /// been analyzed.
+    pub fn analyze_path(&self) -> Result<String, StringError> {
+        self.analyze_path.clone().into_string()
+    }
+
+    /// Date and analysis file path of the analysis of this track.
+    ///
+    /// Dates are stored as `YYYY-MM-DD` strings. Empty strings are returned as `None`, and so are
+    /// malformed dates (which are logged as a warning).
+    pub fn analysis_info(&self) -> Result<AnalysisInfo, StringError> {
+        let analyze_date = self.analyze_date.clone().into_string()?;
+        let analyze_date = match analyze_date.as_str() {
+            "" => None,
+            date => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .inspect_err(|err| {
+                    log::warn!(
+                        "Track {} has invalid analyze date {date:?}: {err}",
+                        self.id.0
+                    );
+                })
+                .ok(),
+        };
+        let analyze_path =
+            Some(normalize_path(&self.analyze_path()?)).filter(|path| !path.as_os_str().is_empty());
+        Ok(AnalysisInfo {
+            analyze_date,
+            analyze_path,
+        })
+    }
+
+    /// Offsets of the 21 strings of this row relative to the start of the row, in the order in
+    /// which they are stored in the offset table (ISRC first, file path last).
+    ///
+    /// The offsets are those used when writing the row. Since strings are written in the same
+    /// order and without padding as in Rekordbox exports, they match the offsets of the original
+    /// row. This is only useful for researching the row layout.
+    pub fn string_offsets(&self) -> BinResult<[u16; Self::STRING_COUNT]> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write_options(&mut writer, Endian::Little, ())?;
+        writer.set_position(Self::STRING_OFFSETS_POSITION);
+        <[u16; Self::STRING_COUNT]>::read_options(&mut writer, Endian::Little, ())
+    }
+
+    /// Sample rate, bitrate and sample depth of the audio file.
+    #[must_use]
+    pub fn audio_properties(&self) -> AudioProperties {
+        AudioProperties {
+            sample_rate_hz: self.sample_rate,
+            bitrate_kbps: self.bitrate,
+            sample_depth_bits: self.sample_depth,
+        }
+    }
+
+    /// Sample rate of the audio file in Hz (e.g. `44100`).
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Sample rate of the audio file in kHz (e.g. `44.1`).
+    #[must_use]
+    pub fn sample_rate_khz(&self) -> f64 {
+        f64::from(self.sample_rate) / 1000.0
+    }
+
+    /// Human-readable bitrate of the audio file, e.g. `320 kbps`.
+    ///
+    /// Some exports store a bitrate of `0` for uncompressed files, which is shown as `lossless`
+    /// (or `unknown` for lossy file types). Other exports store the bitrate of the PCM stream for
+    /// these files (see [`AudioProperties::bitrate_kbps`]), which is shown like any other bitrate.
+    #[must_use]
+    pub fn bitrate_display(&self) -> String {
+        match self.bitrate {
+            0 if self.is_lossless() => "lossless".to_string(),
+            0 => "unknown".to_string(),
+            bitrate => format!("{bitrate} kbps"),
+        }
+    }
+
+    /// Format of the audio file.
+    #[must_use]
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns `true` if the audio file is lossless (see [`FileType::is_lossless`]).
+    #[must_use]
+    pub fn is_lossless(&self) -> bool {
+        self.file_type.is_lossless()
+    }
+
+    /// Returns `true` if both rows describe the same track, ignoring fields that differ between
+    /// databases for the same track.
+    ///
+    /// The following fields are compared: title, artist, composer, remixer, original artist,
+    /// album, genre, label and key IDs, file path, file type, file size, duration, tempo, sample
+    /// rate, bitrate, sample depth, track and disc number, year, release date, mix name, ISRC,
+    /// comment, rating and color.
+    ///
+    /// Ignored are the row ID, the artwork ID, the play count, the date added, the analysis file
+    /// path and date, the string flags (message, KUVO, hot cue autoload) and all unknown fields.
+    #[must_use]
+    pub fn semantically_equals(&self, other: &Track) -> bool {
+        self.title == other.title
+            && self.artist_id == other.artist_id
+            && self.composer_id == other.composer_id
+            && self.remixer_id == other.remixer_id
+            && self.orig_artist_id == other.orig_artist_id
+            && self.album_id == other.album_id
+            && self.genre_id == other.genre_id
+            && self.label_id == other.label_id
+            && self.key_id == other.key_id
+            && self.file_path == other.file_path
+            && self.file_type == other.file_type
+            && self.file_size == other.file_size
+            && self.duration == other.duration
+            && self.tempo == other.tempo
+            && self.sample_rate == other.sample_rate
+            && self.bitrate == other.bitrate
+            && self.sample_depth == other.sample_depth
+            && self.track_number == other.track_number
+            && self.disc_number == other.disc_number
+            && self.year == other.year
+            && self.release_date == other.release_date
+            && self.mix_name == other.mix_name
+            && self.isrc == other.isrc
+            && self.comment == other.comment
+            && self.rating == other.rating
+            && self.color == other.color
+    }
+
+    /// Tempo of this track.
+    ///
+    /// Tracks that have not been analyzed have a tempo of `0`, and some exports contain garbage
+    /// values for those, so this returns an error if the stored tempo is not plausible.
+    pub fn bpm(&self) -> Result<Bpm, InvalidTempoError> {
+        Bpm::from_centi_bpm(self.tempo)
+    }
+}
+
 // #[bw(little)] on #[binread] types does
 // not seem to work so we manually define the endianness here.
 impl binrw::meta::WriteEndian for Track {
@@ -901,14 +2135,17 @@ impl BinWrite for Track {
         self.unknown5.write_options(writer, endian, ())?;
         self.color.write_options(writer, endian, ())?;
         self.rating.write_options(writer, endian, ())?;
-        self.unknown6.write_options(writer, endian, ())?;
+        self.file_type.write_options(writer, endian, ())?;
         self.unknown7.write_options(writer, endian, ())?;
 
         let start_of_string_section = writer.stream_position()?;
-        debug_assert_eq!(start_of_string_section - base_position, 0x5e);
+        debug_assert_eq!(
+            start_of_string_section - base_position,
+            Self::STRING_OFFSETS_POSITION
+        );
 
         // Skip offsets, because we want to write the actual strings first.
-        let mut string_offsets = [0u16; 21];
+        let mut string_offsets = [0u16; Self::STRING_COUNT];
         writer.seek(SeekFrom::Current(0x2a))?;
         for (i, string) in [
             &self.isrc,
@@ -953,65 +2190,497 @@ impl BinWrite for Track {
         string_offsets.write_options(writer, endian, ())?;
         writer.seek(SeekFrom::Start(end_of_row))?;
 
-        Ok(())
+        Ok(())
+    }
+}
+
+/// Identifies a "My Tag" tag or tag category.
+#[binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[brw(little)]
+pub struct TagId(pub u32);
+
+/// A "My Tag" tag or tag category, found in extended databases (`exportExt.pdb`).
+///
+/// Tags are grouped into categories (e.g. "Genre" or "Situation"), which are stored as rows of
+/// the same table.
+#[binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(little)]
+pub struct Tag {
+    /// Unknown field, usually `80 06`.
+    subtype: u16,
+    /// Unknown field, called `index_shift` by [@flesniak](https://github.com/flesniak).
+    index_shift: u16,
+    /// Unknown field.
+    unknown1: u64,
+    /// ID of the category that this tag belongs to, or `0` if this row is a category.
+    category: TagId,
+    /// Position of this tag in its category (or of this category in the list of categories).
+    category_pos: u32,
+    /// ID of this row.
+    id: TagId,
+    /// Non-zero if this row is a category.
+    raw_is_category: u32,
+    /// Unknown field, usually `3`.
+    unknown2: u8,
+    /// Offset of the `name` string, relative to the start of this row.
+    ofs_name_near: u8,
+    /// Offset of the `unknown_string` string, relative to the start of this row.
+    ofs_unknown_near: u8,
+    /// Name of this tag or category.
+    #[br(seek_before = SeekFrom::Current(i64::from(ofs_name_near) - Tag::STRINGS_OFFSET))]
+    #[bw(seek_before = SeekFrom::Current(i64::from(*ofs_name_near) - Tag::STRINGS_OFFSET))]
+    #[brw(restore_position)]
+    name: DeviceSQLString,
+    /// Unknown string field (usually empty).
+    ///
+    /// Rekordbox writes 8 zero bytes after this string, which are included when writing the row.
+    #[br(seek_before = SeekFrom::Current(i64::from(ofs_unknown_near) - Tag::STRINGS_OFFSET))]
+    #[br(restore_position)]
+    #[bw(seek_before = SeekFrom::Current(i64::from(*ofs_unknown_near) - Tag::STRINGS_OFFSET))]
+    #[bw(pad_after = Tag::PADDING_SIZE)]
+    unknown_string: DeviceSQLString,
+}
+
+impl Tag {
+    /// Offset of the end of the fixed-size fields, relative to the start of the row.
+    const STRINGS_OFFSET: i64 = 0x1F;
+    /// Number of zero bytes that follow the strings.
+    const PADDING_SIZE: usize = 8;
+
+    /// ID of this row.
+    #[must_use]
+    pub fn id(&self) -> TagId {
+        self.id
+    }
+
+    /// Name of this tag or category.
+    pub fn name(&self) -> Result<String, StringError> {
+        self.name.clone().into_string()
+    }
+
+    /// Set the name of this tag or category.
+    ///
+    /// The `unknown_string` field follows the name, so its offset is updated accordingly.
+    pub fn set_name(&mut self, name: &str) -> Result<(), StringError> {
+        let name = DeviceSQLString::new(name.to_string())?;
+        // The offset of the following string has to fit into a single byte.
+        let ofs_unknown_near = u8::try_from(name.encoded_len())
+            .ok()
+            .and_then(|len| self.ofs_name_near.checked_add(len))
+            .ok_or(StringError::TooLong)?;
+        self.name = name;
+        self.ofs_unknown_near = ofs_unknown_near;
+        Ok(())
+    }
+
+    /// Returns `true` if this row is a tag category (and not a tag).
+    #[must_use]
+    pub fn is_category(&self) -> bool {
+        self.raw_is_category != 0
+    }
+
+    /// ID of the category that this tag belongs to, or `None` if this row is a category.
+    #[must_use]
+    pub fn category(&self) -> Option<TagId> {
+        (!self.is_category()).then_some(self.category)
+    }
+
+    /// Position of this tag in its category (or of this category in the list of categories),
+    /// starting at `0`.
+    #[must_use]
+    pub fn category_position(&self) -> u32 {
+        self.category_pos
+    }
+}
+
+/// Links a track to a "My Tag" tag, found in extended databases (`exportExt.pdb`).
+#[binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(little)]
+pub struct TrackTag {
+    /// Unknown field, usually `0`.
+    unknown1: u32,
+    /// ID of the track.
+    track_id: TrackId,
+    /// ID of the tag.
+    tag_id: TagId,
+    /// Unknown field, usually `3`.
+    unknown2: u32,
+}
+
+impl TrackTag {
+    /// ID of the track.
+    #[must_use]
+    pub fn track_id(&self) -> TrackId {
+        self.track_id
+    }
+
+    /// ID of the tag.
+    #[must_use]
+    pub fn tag_id(&self) -> TagId {
+        self.tag_id
     }
 }
 
+/// A table row of an extended database (`exportExt.pdb`).
+#[binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(little)]
+#[br(import(page_type: ExtPageType))]
+pub enum ExtRow {
+    /// A "My Tag" tag or tag category.
+    #[br(pre_assert(page_type == ExtPageType::Tags))]
+    Tag(Tag),
+    /// Links a track to a "My Tag" tag.
+    #[br(pre_assert(page_type == ExtPageType::TrackTags))]
+    TrackTag(TrackTag),
+    /// The row format (and also its size) is unknown, which means it can't be parsed.
+    #[br(pre_assert(matches!(page_type, ExtPageType::Unknown(_))))]
+    Unknown,
+}
+
 /// A table row contains the actual data.
 #[binrw]
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[brw(little)]
-#[br(import(page_type: PageType))]
+#[br(import(page_type: PageType, database_type: DatabaseType))]
 // The large enum size is unfortunate, but since users of this library will probably use iterators
 // to consume the results on demand, we can live with this. The alternative of using a `Box` would
 // require a heap allocation per row, which is arguably worse. Hence, the warning is disabled for
-// this enum.
+// this enum. Use [`BoxedRow`] to store many rows in memory.
 #[allow(clippy::large_enum_variant)]
 pub enum Row {
     /// Contains the album name, along with an ID of the corresponding artist.
-    #[br(pre_assert(page_type == PageType::Albums))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Albums))]
     Album(Album),
     /// Contains the artist name and ID.
-    #[br(pre_assert(page_type == PageType::Artists))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Artists))]
     Artist(Artist),
     /// Contains the artwork path and ID.
-    #[br(pre_assert(page_type == PageType::Artwork))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Artwork))]
     Artwork(Artwork),
     /// Contains numeric color ID
-    #[br(pre_assert(page_type == PageType::Colors))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Colors))]
     Color(Color),
     /// Represents a musical genre.
-    #[br(pre_assert(page_type == PageType::Genres))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Genres))]
     Genre(Genre),
     /// Represents a history playlist.
-    #[br(pre_assert(page_type == PageType::HistoryPlaylists))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::HistoryPlaylists))]
     HistoryPlaylist(HistoryPlaylist),
     /// Represents a history playlist.
-    #[br(pre_assert(page_type == PageType::HistoryEntries))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::HistoryEntries))]
     HistoryEntry(HistoryEntry),
     /// Represents a musical key.
-    #[br(pre_assert(page_type == PageType::Keys))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Keys))]
     Key(Key),
     /// Represents a record label.
-    #[br(pre_assert(page_type == PageType::Labels))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Labels))]
     Label(Label),
     /// Represents a node in the playlist tree (either a folder or a playlist).
-    #[br(pre_assert(page_type == PageType::PlaylistTree))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::PlaylistTree))]
     PlaylistTreeNode(PlaylistTreeNode),
     /// Represents a track entry in a playlist.
-    #[br(pre_assert(page_type == PageType::PlaylistEntries))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::PlaylistEntries))]
     PlaylistEntry(PlaylistEntry),
     /// Contains the metadata categories by which Tracks can be browsed by.
-    #[br(pre_assert(page_type == PageType::Columns))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Columns))]
     ColumnEntry(ColumnEntry),
     /// Contains the album name, along with an ID of the corresponding artist.
-    #[br(pre_assert(page_type == PageType::Tracks))]
+    #[br(pre_assert(database_type == DatabaseType::Plain && page_type == PageType::Tracks))]
     Track(Track),
+    /// Contains a row of an extended database (`exportExt.pdb`).
+    #[br(pre_assert(database_type == DatabaseType::Ext))]
+    Ext(#[br(args(ExtPageType::from(page_type)))] ExtRow),
+    /// The row format (and also its size) is unknown, which means it can't be parsed.
+    #[br(pre_assert(
+        database_type == DatabaseType::Plain
+            && matches!(page_type, PageType::History | PageType::Unknown(_))
+    ))]
+    Unknown,
+}
+
+/// A [`Row`] that stores its large variants ([`Album`], [`Artist`] and [`Track`]) in a [`Box`].
+///
+/// Every [`Row`] is as large as a track row, so collections of many small rows (e.g. all playlist
+/// entries of a database) waste a lot of memory. Converting the rows into this type shrinks them
+/// to the size of the small variants, at the cost of a heap allocation per large row.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::pdb::{io::Database, BoxedRow, PageType};
+///
+/// let db = Database::open("data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb")?;
+/// let rows = db
+///     .iter_rows(PageType::PlaylistEntries)
+///     .map(|row| row.map(BoxedRow::from))
+///     .collect::<rekordcrate::Result<Vec<_>>>()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BoxedRow {
+    /// Contains the album name, along with an ID of the corresponding artist.
+    Album(Box<Album>),
+    /// Contains the artist name and ID.
+    Artist(Box<Artist>),
+    /// Contains the artwork path and ID.
+    Artwork(Artwork),
+    /// Contains numeric color ID
+    Color(Color),
+    /// Represents a musical genre.
+    Genre(Genre),
+    /// Represents a history playlist.
+    HistoryPlaylist(HistoryPlaylist),
+    /// Represents a history playlist.
+    HistoryEntry(HistoryEntry),
+    /// Represents a musical key.
+    Key(Key),
+    /// Represents a record label.
+    Label(Label),
+    /// Represents a node in the playlist tree (either a folder or a playlist).
+    PlaylistTreeNode(PlaylistTreeNode),
+    /// Represents a track entry in a playlist.
+    PlaylistEntry(PlaylistEntry),
+    /// Contains the metadata categories by which Tracks can be browsed by.
+    ColumnEntry(ColumnEntry),
+    /// Contains the album name, along with an ID of the corresponding artist.
+    Track(Box<Track>),
+    /// Contains a row of an extended database (`exportExt.pdb`).
+    Ext(ExtRow),
     /// The row format (and also its size) is unknown, which means it can't be parsed.
-    #[br(pre_assert(matches!(page_type, PageType::History | PageType::Unknown(_))))]
     Unknown,
 }
 
+impl From<Row> for BoxedRow {
+    fn from(row: Row) -> Self {
+        match row {
+            Row::Album(row) => Self::Album(Box::new(row)),
+            Row::Artist(row) => Self::Artist(Box::new(row)),
+            Row::Artwork(row) => Self::Artwork(row),
+            Row::Color(row) => Self::Color(row),
+            Row::Genre(row) => Self::Genre(row),
+            Row::HistoryPlaylist(row) => Self::HistoryPlaylist(row),
+            Row::HistoryEntry(row) => Self::HistoryEntry(row),
+            Row::Key(row) => Self::Key(row),
+            Row::Label(row) => Self::Label(row),
+            Row::PlaylistTreeNode(row) => Self::PlaylistTreeNode(row),
+            Row::PlaylistEntry(row) => Self::PlaylistEntry(row),
+            Row::ColumnEntry(row) => Self::ColumnEntry(row),
+            Row::Track(row) => Self::Track(Box::new(row)),
+            Row::Ext(row) => Self::Ext(row),
+            Row::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl From<BoxedRow> for Row {
+    fn from(row: BoxedRow) -> Self {
+        match row {
+            BoxedRow::Album(row) => Self::Album(*row),
+            BoxedRow::Artist(row) => Self::Artist(*row),
+            BoxedRow::Artwork(row) => Self::Artwork(row),
+            BoxedRow::Color(row) => Self::Color(row),
+            BoxedRow::Genre(row) => Self::Genre(row),
+            BoxedRow::HistoryPlaylist(row) => Self::HistoryPlaylist(row),
+            BoxedRow::HistoryEntry(row) => Self::HistoryEntry(row),
+            BoxedRow::Key(row) => Self::Key(row),
+            BoxedRow::Label(row) => Self::Label(row),
+            BoxedRow::PlaylistTreeNode(row) => Self::PlaylistTreeNode(row),
+            BoxedRow::PlaylistEntry(row) => Self::PlaylistEntry(row),
+            BoxedRow::ColumnEntry(row) => Self::ColumnEntry(row),
+            BoxedRow::Track(row) => Self::Track(*row),
+            BoxedRow::Ext(row) => Self::Ext(row),
+            BoxedRow::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// Kind of a [`Row`], without the row data, returned by [`Row::kind`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum RowKind {
+    /// [`Row::Album`]
+    Album,
+    /// [`Row::Artist`]
+    Artist,
+    /// [`Row::Artwork`]
+    Artwork,
+    /// [`Row::Color`]
+    Color,
+    /// [`Row::Genre`]
+    Genre,
+    /// [`Row::HistoryPlaylist`]
+    HistoryPlaylist,
+    /// [`Row::HistoryEntry`]
+    HistoryEntry,
+    /// [`Row::Key`]
+    Key,
+    /// [`Row::Label`]
+    Label,
+    /// [`Row::PlaylistTreeNode`]
+    PlaylistTreeNode,
+    /// [`Row::PlaylistEntry`]
+    PlaylistEntry,
+    /// [`Row::ColumnEntry`]
+    ColumnEntry,
+    /// [`Row::Track`]
+    Track,
+    /// [`ExtRow::Tag`]
+    Tag,
+    /// [`ExtRow::TrackTag`]
+    TrackTag,
+    /// [`Row::Unknown`] or [`ExtRow::Unknown`]
+    Unknown,
+}
+
+/// Generates the `is_*` and `as_*` accessors of [`Row`] for the given variants.
+macro_rules! row_accessors {
+    ($($variant:ident($type:ty) => $is:ident, $as:ident;)*) => {
+        $(
+            #[doc = concat!("Returns `true` if this is a [`", stringify!($type), "`] row.")]
+            #[must_use]
+            pub fn $is(&self) -> bool {
+                self.$as().is_some()
+            }
+
+            #[doc = concat!("Returns the [`", stringify!($type), "`] if this is a row of that type.")]
+            #[must_use]
+            pub fn $as(&self) -> Option<&$type> {
+                match self {
+                    Row::$variant(row) => Some(row),
+                    _ => None,
+                }
+            }
+        )*
+    };
+}
+
+/// A row type of the plain database (`export.pdb`) that is stored in the tables of a single page
+/// type, e.g. [`Track`] rows in the [`PageType::Tracks`] tables.
+///
+/// This is used by [`Database::iter_present_rows`](io::Database::iter_present_rows) to iterate
+/// over the rows of a specific type.
+pub trait TableRow: Sized {
+    /// Type of the pages that contain rows of this type.
+    const PAGE_TYPE: PageType;
+
+    /// Returns the row of this type if `row` contains one.
+    fn from_row(row: Row) -> Option<Self>;
+}
+
+/// Implements [`TableRow`] for the row types of the plain database, and [`Row::page_type`] using
+/// the same mapping.
+macro_rules! table_rows {
+    ($($variant:ident => $page_type:ident;)*) => {
+        $(
+            impl TableRow for $variant {
+                const PAGE_TYPE: PageType = PageType::$page_type;
+
+                fn from_row(row: Row) -> Option<Self> {
+                    match row {
+                        Row::$variant(inner) => Some(inner),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+
+        impl Row {
+            /// Type of the plain database (`export.pdb`) pages that contain this kind of row.
+            ///
+            /// Returns `None` for rows from the extended database (`exportExt.pdb`) and for
+            /// unknown rows.
+            #[must_use]
+            pub fn page_type(&self) -> Option<PageType> {
+                match self {
+                    $(Row::$variant(_) => Some(PageType::$page_type),)*
+                    Row::Ext(_) | Row::Unknown => None,
+                }
+            }
+        }
+    };
+}
+
+table_rows! {
+    Album => Albums;
+    Artist => Artists;
+    Artwork => Artwork;
+    Color => Colors;
+    Genre => Genres;
+    HistoryPlaylist => HistoryPlaylists;
+    HistoryEntry => HistoryEntries;
+    Key => Keys;
+    Label => Labels;
+    PlaylistTreeNode => PlaylistTree;
+    PlaylistEntry => PlaylistEntries;
+    ColumnEntry => Columns;
+    Track => Tracks;
+}
+
+impl Row {
+    /// Kind of this row.
+    ///
+    /// This allows filtering rows by kind without matching on the (nested) variants.
+    #[must_use]
+    pub fn kind(&self) -> RowKind {
+        match self {
+            Row::Album(_) => RowKind::Album,
+            Row::Artist(_) => RowKind::Artist,
+            Row::Artwork(_) => RowKind::Artwork,
+            Row::Color(_) => RowKind::Color,
+            Row::Genre(_) => RowKind::Genre,
+            Row::HistoryPlaylist(_) => RowKind::HistoryPlaylist,
+            Row::HistoryEntry(_) => RowKind::HistoryEntry,
+            Row::Key(_) => RowKind::Key,
+            Row::Label(_) => RowKind::Label,
+            Row::PlaylistTreeNode(_) => RowKind::PlaylistTreeNode,
+            Row::PlaylistEntry(_) => RowKind::PlaylistEntry,
+            Row::ColumnEntry(_) => RowKind::ColumnEntry,
+            Row::Track(_) => RowKind::Track,
+            Row::Ext(ExtRow::Tag(_)) => RowKind::Tag,
+            Row::Ext(ExtRow::TrackTag(_)) => RowKind::TrackTag,
+            Row::Ext(ExtRow::Unknown) | Row::Unknown => RowKind::Unknown,
+        }
+    }
+
+    row_accessors! {
+        Album(Album) => is_album, as_album;
+        Artist(Artist) => is_artist, as_artist;
+        Artwork(Artwork) => is_artwork, as_artwork;
+        Color(Color) => is_color, as_color;
+        Genre(Genre) => is_genre, as_genre;
+        HistoryPlaylist(HistoryPlaylist) => is_history_playlist, as_history_playlist;
+        HistoryEntry(HistoryEntry) => is_history_entry, as_history_entry;
+        Key(Key) => is_key, as_key;
+        Label(Label) => is_label, as_label;
+        PlaylistTreeNode(PlaylistTreeNode) => is_playlist_tree_node, as_playlist_tree_node;
+        PlaylistEntry(PlaylistEntry) => is_playlist_entry, as_playlist_entry;
+        ColumnEntry(ColumnEntry) => is_column_entry, as_column_entry;
+        Track(Track) => is_track, as_track;
+    }
+
+    /// Returns the [`Tag`] if this is a "My Tag" row from the extended database.
+    #[must_use]
+    pub fn as_tag(&self) -> Option<&Tag> {
+        match self {
+            Row::Ext(ExtRow::Tag(tag)) => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// Returns the [`TrackTag`] if this is a row from the extended database that links a track
+    /// to a "My Tag".
+    #[must_use]
+    pub fn as_track_tag(&self) -> Option<&TrackTag> {
+        match self {
+            Row::Ext(ExtRow::TrackTag(track_tag)) => Some(track_tag),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1187,6 +2856,25 @@ mod test {
         );
     }
 
+    /// A track row from the demo export ("Demo Track 1").
+    const TRACK_ROW: &[u8] = &[
+        36, 0, 160, 0, 0, 7, 12, 0, 68, 172, 0, 0, 0, 0, 0, 0, 168, 71, 105, 0, 218, 177, 193, 12,
+        128, 250, 231, 5, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 64, 1, 0, 0,
+        0, 0, 0, 0, 0, 50, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        16, 0, 172, 0, 41, 0, 0, 0, 1, 0, 3, 0, 136, 0, 137, 0, 138, 0, 140, 0, 142, 0, 143, 0,
+        144, 0, 145, 0, 148, 0, 149, 0, 150, 0, 161, 0, 162, 0, 163, 0, 164, 0, 208, 0, 219, 0,
+        249, 0, 6, 1, 7, 1, 24, 1, 3, 3, 5, 51, 5, 51, 3, 3, 3, 7, 79, 78, 3, 3, 23, 50, 48, 49,
+        56, 45, 48, 53, 45, 50, 53, 3, 3, 3, 89, 47, 80, 73, 79, 78, 69, 69, 82, 47, 85, 83, 66,
+        65, 78, 76, 90, 47, 80, 48, 49, 54, 47, 48, 48, 48, 48, 56, 55, 53, 69, 47, 65, 78, 76, 90,
+        48, 48, 48, 48, 46, 68, 65, 84, 23, 50, 48, 50, 50, 45, 48, 50, 45, 48, 50, 61, 84, 114,
+        97, 99, 107, 115, 32, 98, 121, 32, 119, 119, 119, 46, 108, 111, 111, 112, 109, 97, 115,
+        116, 101, 114, 115, 46, 99, 111, 109, 27, 68, 101, 109, 111, 32, 84, 114, 97, 99, 107, 32,
+        49, 3, 35, 68, 101, 109, 111, 32, 84, 114, 97, 99, 107, 32, 49, 46, 109, 112, 51, 105, 47,
+        67, 111, 110, 116, 101, 110, 116, 115, 47, 76, 111, 111, 112, 109, 97, 115, 116, 101, 114,
+        115, 47, 85, 110, 107, 110, 111, 119, 110, 65, 108, 98, 117, 109, 47, 68, 101, 109, 111,
+        32, 84, 114, 97, 99, 107, 32, 49, 46, 109, 112, 51,
+    ];
+
     #[test]
     fn track_row() {
         let row = Track {
@@ -1219,7 +2907,7 @@ mod test {
             unknown5: 41,
             color: ColorIndex::None,
             rating: 0,
-            unknown6: 1,
+            file_type: FileType::Mp3,
             unknown7: 3,
             isrc: DeviceSQLString::new_isrc("".to_string()).unwrap(),
             unknown_string1: DeviceSQLString::empty(),
@@ -1249,27 +2937,7 @@ mod test {
             )
             .unwrap(),
         };
-        test_roundtrip(
-            &[
-                36, 0, 160, 0, 0, 7, 12, 0, 68, 172, 0, 0, 0, 0, 0, 0, 168, 71, 105, 0, 218, 177,
-                193, 12, 128, 250, 231, 5, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0,
-                0, 64, 1, 0, 0, 0, 0, 0, 0, 0, 50, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0,
-                0, 0, 0, 0, 0, 0, 0, 0, 16, 0, 172, 0, 41, 0, 0, 0, 1, 0, 3, 0, 136, 0, 137, 0,
-                138, 0, 140, 0, 142, 0, 143, 0, 144, 0, 145, 0, 148, 0, 149, 0, 150, 0, 161, 0,
-                162, 0, 163, 0, 164, 0, 208, 0, 219, 0, 249, 0, 6, 1, 7, 1, 24, 1, 3, 3, 5, 51, 5,
-                51, 3, 3, 3, 7, 79, 78, 3, 3, 23, 50, 48, 49, 56, 45, 48, 53, 45, 50, 53, 3, 3, 3,
-                89, 47, 80, 73, 79, 78, 69, 69, 82, 47, 85, 83, 66, 65, 78, 76, 90, 47, 80, 48, 49,
-                54, 47, 48, 48, 48, 48, 56, 55, 53, 69, 47, 65, 78, 76, 90, 48, 48, 48, 48, 46, 68,
-                65, 84, 23, 50, 48, 50, 50, 45, 48, 50, 45, 48, 50, 61, 84, 114, 97, 99, 107, 115,
-                32, 98, 121, 32, 119, 119, 119, 46, 108, 111, 111, 112, 109, 97, 115, 116, 101,
-                114, 115, 46, 99, 111, 109, 27, 68, 101, 109, 111, 32, 84, 114, 97, 99, 107, 32,
-                49, 3, 35, 68, 101, 109, 111, 32, 84, 114, 97, 99, 107, 32, 49, 46, 109, 112, 51,
-                105, 47, 67, 111, 110, 116, 101, 110, 116, 115, 47, 76, 111, 111, 112, 109, 97,
-                115, 116, 101, 114, 115, 47, 85, 110, 107, 110, 111, 119, 110, 65, 108, 98, 117,
-                109, 47, 68, 101, 109, 111, 32, 84, 114, 97, 99, 107, 32, 49, 46, 109, 112, 51,
-            ],
-            row,
-        );
+        test_roundtrip(TRACK_ROW, row);
     }
 
     #[test]
@@ -1292,6 +2960,437 @@ mod test {
         );
     }
 
+    #[test]
+    fn track_string_offsets() {
+        let track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        let offsets = track.string_offsets().unwrap();
+        let raw_offsets: Vec<u16> = TRACK_ROW[0x5e..0x88]
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect();
+        assert_eq!(offsets.as_slice(), raw_offsets.as_slice());
+        assert_eq!(offsets[0], 0x88);
+    }
+
+    #[test]
+    fn page_flags() {
+        for (bits, is_index_page, unknown_bits) in [
+            (0x24u8, false, 0x24u8),
+            (0x34, false, 0x34),
+            (0x44, true, 0x04),
+            (0x64, true, 0x24),
+            (0x00, false, 0x00),
+        ] {
+            let flags = PageFlags::from_bits(bits);
+            test_roundtrip(&[bits], flags);
+            assert_eq!(flags.bits(), bits);
+            assert_eq!(flags.is_index_page(), is_index_page);
+            assert_eq!(flags.page_has_data(), !is_index_page);
+            assert_eq!(flags.unknown_bits(), unknown_bits);
+        }
+    }
+
+    #[test]
+    fn file_type() {
+        for (value, file_type, is_lossless) in [
+            (1u16, FileType::Mp3, false),
+            (4, FileType::M4a, false),
+            (5, FileType::Flac, true),
+            (11, FileType::Wav, true),
+            (12, FileType::Aiff, true),
+            (0, FileType::Unknown(0), false),
+        ] {
+            test_roundtrip(&value.to_le_bytes(), file_type);
+            assert_eq!(file_type.is_lossless(), is_lossless);
+        }
+    }
+
+    #[test]
+    fn track_isrc() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert_eq!(track.isrc.as_isrc(), None);
+
+        track.isrc = DeviceSQLString::new_isrc("GBAYE6700149".to_string()).unwrap();
+        let mut writer = Cursor::new(vec![]);
+        track.write_le(&mut writer).unwrap();
+        let track = Track::read_le(&mut Cursor::new(writer.into_inner())).unwrap();
+        assert_eq!(track.isrc.as_isrc().as_deref(), Some("GBAYE6700149"));
+    }
+
+    #[test]
+    fn track_sample_rate_and_bitrate() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert_eq!(track.file_type(), FileType::Mp3);
+        assert_eq!(track.sample_rate(), 44100);
+        assert_eq!(track.sample_rate_khz(), 44.1);
+        assert_eq!(track.bitrate_display(), "320 kbps");
+
+        track.file_type = FileType::Wav;
+        track.sample_rate = 48000;
+        track.bitrate = 2116;
+        assert_eq!(track.sample_rate_khz(), 48.0);
+        assert_eq!(track.bitrate_display(), "2116 kbps");
+        track.bitrate = 0;
+        assert_eq!(track.bitrate_display(), "lossless");
+
+        track.file_type = FileType::Mp3;
+        assert_eq!(track.bitrate_display(), "unknown");
+    }
+
+    #[test]
+    fn track_semantically_equals() {
+        let track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        let mut other = track.clone();
+        other.unknown2 = track.unknown2.wrapping_add(1);
+        assert_ne!(track, other);
+        assert!(track.semantically_equals(&other));
+
+        other.title = DeviceSQLString::new("Another Title".to_string()).unwrap();
+        assert!(!track.semantically_equals(&other));
+    }
+
+    #[test]
+    fn track_flags() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert!(track.autoload_hotcues());
+        assert!(!track.publish_track_information());
+
+        track.set_autoload_hotcues(false);
+        track.set_publish_track_information(true);
+        assert_eq!(track.autoload_hotcues, DeviceSQLString::empty());
+        assert_eq!(
+            track.kuvo_public,
+            DeviceSQLString::new("ON".to_string()).unwrap()
+        );
+
+        let mut writer = Cursor::new(vec![]);
+        track.write_le(&mut writer).unwrap();
+        writer.set_position(0);
+        let track = Track::read_le(&mut writer).unwrap();
+        assert!(!track.autoload_hotcues());
+        assert!(track.publish_track_information());
+    }
+
+    #[test]
+    fn track_remote_info() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert_eq!(
+            track.remote_info().unwrap(),
+            RemoteInfo {
+                publish_track_information: false,
+                message: String::new(),
+            }
+        );
+
+        track.set_publish_track_information(true);
+        track.message = DeviceSQLString::new("Live at the club".to_string()).unwrap();
+        assert_eq!(
+            track.remote_info().unwrap(),
+            RemoteInfo {
+                publish_track_information: true,
+                message: "Live at the club".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn track_mix_name_and_message() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert_eq!(track.mix_name().unwrap(), "");
+        assert_eq!(track.message().unwrap(), "");
+
+        let title = track.title().unwrap();
+        let offsets = track.string_offsets().unwrap();
+        track.set_mix_name("Extended Mix").unwrap();
+        track.set_message("Play this one loud").unwrap();
+        let mut writer = Cursor::new(vec![]);
+        track.write_le(&mut writer).unwrap();
+        writer.set_position(0);
+        let track = Track::read_le(&mut writer).unwrap();
+        assert_eq!(track.mix_name().unwrap(), "Extended Mix");
+        assert_eq!(track.message().unwrap(), "Play this one loud");
+        assert_eq!(track.remote_info().unwrap().message, "Play this one loud");
+        assert_eq!(track.title().unwrap(), title);
+        // The strings after the message are shifted.
+        assert_eq!(track.string_offsets().unwrap()[..6], offsets[..6]);
+        assert!(track.string_offsets().unwrap()[6..]
+            .iter()
+            .zip(&offsets[6..])
+            .all(|(new, old)| new > old));
+    }
+
+    #[test]
+    fn track_analysis_info() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert_eq!(
+            track.analysis_info().unwrap(),
+            AnalysisInfo {
+                analyze_date: chrono::NaiveDate::from_ymd_opt(2022, 2, 2),
+                analyze_path: Some(
+                    ["PIONEER", "USBANLZ", "P016", "0000875E", "ANLZ0000.DAT"]
+                        .iter()
+                        .collect()
+                ),
+            }
+        );
+
+        track.analyze_date = DeviceSQLString::new("2022-13-45".to_string()).unwrap();
+        track.analyze_path = DeviceSQLString::empty();
+        assert_eq!(
+            track.analysis_info().unwrap(),
+            AnalysisInfo {
+                analyze_date: None,
+                analyze_path: None,
+            }
+        );
+        track.analyze_date = DeviceSQLString::empty();
+        assert_eq!(track.analysis_info().unwrap().analyze_date, None);
+    }
+
+    #[test]
+    fn boxed_rows() {
+        let track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        let row = Row::Track(track.clone());
+        let boxed = BoxedRow::from(row.clone());
+        assert_eq!(boxed, BoxedRow::Track(Box::new(track)));
+        assert_eq!(Row::from(boxed), row);
+
+        assert!(std::mem::size_of::<Row>() >= std::mem::size_of::<Track>());
+        assert!(std::mem::size_of::<BoxedRow>() < std::mem::size_of::<Track>());
+    }
+
+    #[test]
+    fn row_kind_and_accessors() {
+        let track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        let row = Row::Track(track.clone());
+        assert_eq!(row.kind(), RowKind::Track);
+        assert_eq!(row.page_type(), Some(PageType::Tracks));
+        assert!(row.is_track());
+        assert_eq!(row.as_track(), Some(&track));
+        assert!(!row.is_artist());
+        assert_eq!(row.as_artist(), None);
+
+        let row = Row::Key(Key {
+            id: KeyId(1),
+            id2: 1,
+            name: DeviceSQLString::new("Dm".to_string()).unwrap(),
+        });
+        assert_eq!(row.kind(), RowKind::Key);
+        assert_eq!(row.page_type(), Some(PageType::Keys));
+        assert_eq!(row.as_key().unwrap().name().unwrap(), "Dm");
+        assert_eq!(row.as_track(), None);
+
+        let row = Row::Ext(ExtRow::Unknown);
+        assert_eq!(row.kind(), RowKind::Unknown);
+        assert_eq!(row.page_type(), None);
+        assert_eq!(row.as_tag(), None);
+    }
+
+    #[test]
+    fn track_and_disc_number() {
+        let mut track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        assert_eq!(track.track_number(), None);
+        assert_eq!(track.disc_number(), None);
+
+        track.track_number = 7;
+        track.disc_number = 2;
+        assert_eq!(track.track_number(), Some(7));
+        assert_eq!(track.disc_number(), Some(2));
+    }
+
+    #[test]
+    fn track_bpm() {
+        let track = Track::read_le(&mut Cursor::new(TRACK_ROW)).unwrap();
+        let bpm = track.bpm().unwrap();
+        assert_eq!(bpm, Bpm(128.0));
+        assert_eq!(bpm.to_string(), "128.00");
+    }
+
+    #[test]
+    fn bpm_range() {
+        assert_eq!(Bpm::from_centi_bpm(1), Ok(Bpm(0.01)));
+        assert_eq!(Bpm::from_centi_bpm(65535).unwrap().to_string(), "655.35");
+        assert_eq!(Bpm::from_centi_bpm(0), Err(InvalidTempoError(0)));
+        assert_eq!(Bpm::from_centi_bpm(65536), Err(InvalidTempoError(65536)));
+    }
+
+    #[test]
+    fn artist_name_offset() {
+        let near = Artist::read(&mut Cursor::new(&[
+            96, 0, 32, 0, 1, 0, 0, 0, 3, 10, 25, 76, 111, 111, 112, 109, 97, 115, 116, 101, 114,
+            115,
+        ]))
+        .unwrap();
+        assert_eq!(near.name_offset(), 10);
+        let far = Artist::read(&mut Cursor::new(&[
+            0x64, 0, 64, 0, 2, 0, 0, 0, 3, 10, 12, 0, 35, 76, 111, 110, 103, 32, 65, 114, 116, 105,
+            115, 116, 32, 78, 97, 109, 101,
+        ]))
+        .unwrap();
+        assert_eq!(far.name_offset(), 12);
+    }
+
+    #[test]
+    fn artist_row_far_name_offset() {
+        let row = Artist {
+            subtype: 0x64,
+            index_shift: 64,
+            id: ArtistId(2),
+            unknown1: 3,
+            ofs_name_near: 10,
+            ofs_name_far: Some(12),
+            name: DeviceSQLString::new("Long Artist Name".to_string()).unwrap(),
+        };
+        test_roundtrip(
+            &[
+                0x64, 0, 64, 0, 2, 0, 0, 0, 3, 10, 12, 0, 35, 76, 111, 110, 103, 32, 65, 114, 116,
+                105, 115, 116, 32, 78, 97, 109, 101,
+            ],
+            row,
+        );
+    }
+
+    /// Build a page that contains artist rows with both the near (`0x60`) and the far (`0x64`)
+    /// name offset subtype.
+    fn mixed_artist_page() -> Vec<u8> {
+        const PAGE_SIZE: usize = 4096;
+        #[rustfmt::skip]
+        let rows: [(u16, &[u8]); 3] = [
+            // Subtype 0x60, name at 8-bit offset
+            (0, &[
+                0x60, 0, 32, 0, 1, 0, 0, 0, 3, 10, 25, 76, 111, 111, 112, 109, 97, 115, 116, 101,
+                114, 115,
+            ]),
+            // Subtype 0x64, name at 16-bit offset
+            (24, &[
+                0x64, 0, 64, 0, 2, 0, 0, 0, 3, 10, 12, 0, 35, 76, 111, 110, 103, 32, 65, 114,
+                116, 105, 115, 116, 32, 78, 97, 109, 101,
+            ]),
+            // Subtype 0x60 again, following an unaligned 0x64 row
+            (56, &[0x60, 0, 96, 0, 3, 0, 0, 0, 3, 10, 13, 83, 104, 111, 114, 116]),
+        ];
+        let used_size: u16 = 72;
+
+        let mut page = vec![0u8; PAGE_SIZE];
+        #[rustfmt::skip]
+        let header: [u8; 0x28] = [
+            0, 0, 0, 0, // magic
+            0, 0, 0, 0, // page_index
+            2, 0, 0, 0, // page_type (artists)
+            1, 0, 0, 0, // next_page
+            0, 0, 0, 0, // unknown1
+            0, 0, 0, 0, // unknown2
+            3, 0, 0, 0x24, // num_rows_small, unknown3, unknown4, page_flags
+            0, 0, 0, 0, // free_size, used_size (filled in below)
+            0, 0, 0, 0, // unknown5, num_rows_large
+            0, 0, 0, 0, // unknown6, unknown7
+        ];
+        page[..header.len()].copy_from_slice(&header);
+        let free_size = u16::try_from(PAGE_SIZE - header.len() - 36).unwrap() - used_size;
+        page[28..30].copy_from_slice(&free_size.to_le_bytes());
+        page[30..32].copy_from_slice(&used_size.to_le_bytes());
+
+        for (i, (offset, data)) in rows.iter().enumerate() {
+            let start = header.len() + usize::from(*offset);
+            page[start..start + data.len()].copy_from_slice(data);
+            let ofs_pos = PAGE_SIZE - 4 - 2 * (i + 1);
+            page[ofs_pos..ofs_pos + 2].copy_from_slice(&offset.to_le_bytes());
+        }
+        page[PAGE_SIZE - 4..PAGE_SIZE - 2].copy_from_slice(&0b111u16.to_le_bytes());
+        page
+    }
+
+    #[test]
+    fn mixed_artist_subtype_page() {
+        let data = mixed_artist_page();
+        let page_size = u32::try_from(data.len()).unwrap();
+        let page = Page::read_options(
+            &mut Cursor::new(&data),
+            Endian::Little,
+            (page_size, DatabaseType::Plain),
+        )
+        .unwrap();
+
+        let mut names: Vec<String> = page
+            .rows()
+            .map(|row| match row {
+                Row::Artist(artist) => artist.name().unwrap(),
+                row => panic!("unexpected row: {row:?}"),
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, ["Long Artist Name", "Loopmasters", "Short"]);
+
+        // Write the rows back to the offsets they were read from. The resulting heap must match
+        // the original heap byte for byte, including the padding between the rows.
+        let heap_offset = usize::try_from(Page::HEADER_SIZE).unwrap();
+        let heap_end = heap_offset + usize::from(page.used_size);
+        let mut heap = vec![0u8; usize::from(page.used_size)];
+        let mut writer = Cursor::new(&mut heap);
+        for row in page
+            .row_groups
+            .iter()
+            .flat_map(|group| group.rows.iter().flatten())
+        {
+            writer.set_position(row.ptr.into());
+            row.value.write(&mut writer).unwrap();
+        }
+        assert_eq!(heap, &data[heap_offset..heap_end]);
+    }
+
+    #[test]
+    fn insert_more_than_255_rows() {
+        let mut data = mixed_artist_page();
+        let page_size = u32::try_from(data.len()).unwrap();
+        let read_page = |data: &[u8]| {
+            Page::read_options(
+                &mut Cursor::new(data),
+                Endian::Little,
+                (page_size, DatabaseType::Plain),
+            )
+            .unwrap()
+        };
+        let mut page = read_page(&data);
+        let mut num_rows = page.rows().count();
+        loop {
+            let row = Row::Artist(Artist {
+                subtype: 0x60,
+                index_shift: 0,
+                id: ArtistId(u32::try_from(num_rows).unwrap() + 1),
+                unknown1: 3,
+                ofs_name_near: 10,
+                ofs_name_far: None,
+                name: DeviceSQLString::new("x".to_string()).unwrap(),
+            });
+            if !page.insert_row(row, page_size, &mut data).unwrap() {
+                break;
+            }
+            num_rows += 1;
+        }
+        assert!(num_rows > 256);
+
+        let page = read_page(&data);
+        assert_eq!(usize::from(page.num_rows_large), num_rows);
+        assert_eq!(usize::from(page.num_rows()), num_rows);
+        assert_eq!(page.rows().count(), num_rows);
+    }
+
+    #[test]
+    fn rewrite_heap_with_invalid_used_size() {
+        let mut data = mixed_artist_page();
+        let page_size = u32::try_from(data.len()).unwrap();
+        let mut page = Page::read_options(
+            &mut Cursor::new(&data),
+            Endian::Little,
+            (page_size, DatabaseType::Plain),
+        )
+        .unwrap();
+        page.used_size = u16::try_from(page_size).unwrap();
+        let original = data.clone();
+        assert!(page.rewrite_heap(page_size, &mut data).is_err());
+        assert_eq!(data, original);
+    }
+
     #[test]
     fn label_row() {
         let row = Label {
@@ -1306,6 +3405,87 @@ mod test {
         );
     }
 
+    #[test]
+    fn rows_in_hash_set() {
+        use std::collections::HashSet;
+
+        let genre = |id, name: &str| Genre {
+            id: GenreId(id),
+            name: DeviceSQLString::new(name.to_string()).unwrap(),
+        };
+        let genres: HashSet<Genre> = [
+            genre(1, "Techno"),
+            genre(2, "House"),
+            genre(1, "Techno"),
+            genre(1, "House"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(genres.len(), 3);
+
+        // Like equality, the hash takes all fields into account, including the unknown ones.
+        let color = |unknown2| Color {
+            unknown1: 0,
+            unknown2,
+            color: ColorIndex::Pink,
+            unknown3: 0,
+            name: DeviceSQLString::new("Pink".to_string()).unwrap(),
+        };
+        let colors: HashSet<Color> = [color(1), color(1), color(2)].into_iter().collect();
+        assert_eq!(colors.len(), 2);
+    }
+
+    #[test]
+    fn search_names() {
+        let genre = Genre {
+            id: GenreId(1),
+            name: DeviceSQLString::new("Électro HOUSE".to_string()).unwrap(),
+        };
+        assert_eq!(genre.name().unwrap(), "Électro HOUSE");
+        assert_eq!(genre.search_name().unwrap(), "electro house");
+        let label = Label {
+            id: LabelId(1),
+            name: DeviceSQLString::new("Kompakt Köln".to_string()).unwrap(),
+        };
+        assert_eq!(label.search_name().unwrap(), "kompakt koln");
+        let key = Key {
+            id: KeyId(1),
+            id2: 1,
+            name: DeviceSQLString::new("Dm".to_string()).unwrap(),
+        };
+        assert_eq!(key.search_name().unwrap(), "dm");
+    }
+
+    #[test]
+    fn tag_row() {
+        let row = Tag {
+            subtype: 0x0680,
+            index_shift: 0x20,
+            unknown1: 0,
+            category: TagId(1),
+            category_pos: 0,
+            id: TagId(0x81E7_6EC2),
+            raw_is_category: 0,
+            unknown2: 3,
+            ofs_name_near: 0x1F,
+            ofs_unknown_near: 0x2A,
+            name: DeviceSQLString::new("Acid House".to_string()).unwrap(),
+            unknown_string: DeviceSQLString::empty(),
+        };
+        assert_eq!(row.name().unwrap(), "Acid House");
+        assert!(!row.is_category());
+        assert_eq!(row.category(), Some(TagId(1)));
+        test_roundtrip(
+            &[
+                0x80, 0x06, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc2, 0x6e, 0xe7, 0x81, 0x00, 0x00, 0x00, 0x00,
+                0x03, 0x1f, 0x2a, 0x17, 0x41, 0x63, 0x69, 0x64, 0x20, 0x48, 0x6f, 0x75, 0x73, 0x65,
+                0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            row,
+        );
+    }
+
     #[test]
     fn key_row() {
         let row = Key {
@@ -1316,6 +3496,39 @@ mod test {
         test_roundtrip(&[1, 0, 0, 0, 1, 0, 0, 0, 7, 68, 109], row);
     }
 
+    #[test]
+    fn key_ids() {
+        let mut row = Key {
+            id: KeyId(3),
+            id2: 3,
+            name: DeviceSQLString::new("Em".to_string()).unwrap(),
+        };
+        assert_eq!(row.id2(), 3);
+        assert!(row.has_matching_ids());
+        row.id2 = 4;
+        assert!(!row.has_matching_ids());
+    }
+
+    #[test]
+    fn key_notation() {
+        let row = Key {
+            id: KeyId(1),
+            id2: 1,
+            name: DeviceSQLString::new("Dm".to_string()).unwrap(),
+        };
+        assert_eq!(row.classical(), Some("Dm"));
+        assert_eq!(row.camelot().as_deref(), Some("7A"));
+        assert_eq!(row.open_key().as_deref(), Some("12m"));
+
+        let row = Key {
+            id: KeyId(2),
+            id2: 2,
+            name: DeviceSQLString::new("my custom key".to_string()).unwrap(),
+        };
+        assert_eq!(row.musical_key(), None);
+        assert_eq!(row.camelot(), None);
+    }
+
     #[test]
     fn color_row() {
         let row = Color {
@@ -1327,6 +3540,17 @@ mod test {
         };
         test_roundtrip(&[0, 0, 0, 0, 1, 1, 0, 0, 11, 80, 105, 110, 107], row);
     }
+    #[test]
+    fn column_entry_name() {
+        let row = ColumnEntry {
+            id: 1,
+            unknown0: 128,
+            column_name: DeviceSQLString::new("\u{fffa}GENRE\u{fffb}".into()).unwrap(),
+        };
+        assert_eq!(row.id(), 1);
+        assert_eq!(row.name().unwrap(), "GENRE");
+    }
+
     #[test]
     fn column_entry() {
         let row = ColumnEntry {
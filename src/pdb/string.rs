@@ -12,7 +12,7 @@
 //! See <https://djl-analysis.deepsymmetry.org/rekordbox-export-analysis/exports.html#devicesql-strings>
 
 use binrw::binrw;
-use std::{convert::TryInto, fmt};
+use std::{borrow::Cow, convert::TryInto, fmt};
 use thiserror::Error;
 
 const MAX_SHORTSTR_SIZE: usize = ((u8::MAX >> 1) - 1) as usize;
@@ -54,7 +54,7 @@ pub enum StringError {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Hash)]
 #[binrw]
 #[brw(little)]
 pub struct DeviceSQLString(DeviceSQLStringImpl);
@@ -64,24 +64,25 @@ impl DeviceSQLString {
         let len = string.len();
         let only_ascii = string.is_ascii();
         if only_ascii && len <= MAX_SHORTSTR_SIZE {
-            Ok(Self(DeviceSQLStringImpl::ShortASCII {
+            return Ok(Self(DeviceSQLStringImpl::ShortASCII {
                 content: string.into_bytes(),
-            }))
-        } else if len <= (i16::MAX as usize) {
-            if only_ascii {
-                Ok(Self(DeviceSQLStringImpl::Long {
-                    content: LongBody::Ascii(string.into_bytes()),
-                }))
-            } else {
-                Ok(Self(DeviceSQLStringImpl::Long {
-                    // note: The DeviceSQL database may only support UCS-2 so
-                    // we might need to do some additional filtering here
-                    content: LongBody::Ucs2le(string.encode_utf16().collect()),
-                }))
-            }
-        } else {
-            Err(StringError::TooLong)
+            }));
+        }
+        if len > (i16::MAX as usize) {
+            return Err(StringError::TooLong);
         }
+
+        let content = if only_ascii {
+            LongBody::Ascii(string.into_bytes())
+        } else {
+            // note: The DeviceSQL database may only support UCS-2 so
+            // we might need to do some additional filtering here
+            LongBody::Ucs2le(string.encode_utf16().collect())
+        };
+        // The UTF-16 representation may need more bytes than the UTF-8 one,
+        // so we need to make sure that the length field does not overflow.
+        content.length()?;
+        Ok(Self(DeviceSQLStringImpl::Long { content }))
     }
 
     /// Creates a [`DeviceSQLString`] containing an ISRC instead of an expected string
@@ -104,6 +105,23 @@ impl DeviceSQLString {
         }))
     }
 
+    /// Returns the ISRC if this string is stored in the mangled ISRC form (see
+    /// [`DeviceSQLString::new_isrc`]), or `None` otherwise (e.g. for an empty ISRC field).
+    ///
+    /// Rekordbox stores ISRCs as long strings with the flags of a UTF-16 string (`0x90`), but the
+    /// body actually consists of a `0x03` byte, the 12 ASCII characters of the ISRC and a
+    /// trailing null byte. This strips these extra bytes and returns the canonical ISRC (e.g.
+    /// `GBAYE6700149`).
+    #[must_use]
+    pub fn as_isrc(&self) -> Option<String> {
+        match &self.0 {
+            DeviceSQLStringImpl::Long {
+                content: LongBody::Isrc(string),
+            } => String::from_utf8(string.0.clone()).ok(),
+            _ => None,
+        }
+    }
+
     /// Extract the Rust string from the DeviceSQLString.
     ///
     /// Consumes itself in the process.
@@ -124,6 +142,60 @@ impl DeviceSQLString {
         }
     }
 
+    /// Raw bytes of the string body as stored in the file, i.e. everything after the header
+    /// (one byte for short ASCII strings, four bytes for long strings), without decoding it.
+    ///
+    /// This is useful for comparing strings byte by byte or debugging encoding issues. For UCS-2
+    /// strings and ISRCs (which are stored with a leading `0x03` byte and a trailing null byte),
+    /// the body is re-encoded from the parsed value, so these bytes are returned as an owned
+    /// buffer.
+    #[must_use]
+    pub fn as_bytes(&self) -> Cow<'_, [u8]> {
+        match &self.0 {
+            DeviceSQLStringImpl::ShortASCII { content }
+            | DeviceSQLStringImpl::Long {
+                content: LongBody::Ascii(content),
+            } => Cow::Borrowed(content),
+            DeviceSQLStringImpl::Long {
+                content: LongBody::Isrc(string),
+            } => Cow::Owned(
+                std::iter::once(0x03)
+                    .chain(string.0.iter().copied())
+                    .chain(std::iter::once(0x00))
+                    .collect(),
+            ),
+            DeviceSQLStringImpl::Long {
+                content: LongBody::Ucs2le(units),
+            } => Cow::Owned(units.iter().copied().flat_map(u16::to_le_bytes).collect()),
+        }
+    }
+
+    /// Number of bytes that this string occupies when serialized, including the header (one
+    /// byte for short ASCII strings, four bytes for long strings).
+    ///
+    /// This is needed to compute the offsets of strings in rows without serializing them.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        match &self.0 {
+            DeviceSQLStringImpl::ShortASCII { content } => 1 + content.len(),
+            DeviceSQLStringImpl::Long { content } => {
+                usize::from(LongBody::HEADER_SIZE) + content.body_len()
+            }
+        }
+    }
+
+    /// Create a [`DeviceSQLString`] that holds a boolean flag, i.e. `ON` if `value` is `true` and
+    /// an empty string otherwise.
+    pub(crate) fn from_flag(value: bool) -> Self {
+        let content = if value { b"ON".to_vec() } else { Vec::new() };
+        Self(DeviceSQLStringImpl::ShortASCII { content })
+    }
+
+    /// Interpret this string as a boolean flag (see [`DeviceSQLString::from_flag`]).
+    pub(crate) fn is_flag_set(&self) -> bool {
+        matches!(&self.0, DeviceSQLStringImpl::ShortASCII { content } if content == b"ON")
+    }
+
     /// Create an empty [`DeviceSQLString`].
     ///
     /// Should be used to construct known empty strings.
@@ -149,7 +221,7 @@ impl fmt::Debug for DeviceSQLString {
 ///
 /// This implementation forces them to be immutable for now
 #[binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 enum DeviceSQLStringImpl {
     /// Short-String optimization case
     ShortASCII {
@@ -171,12 +243,12 @@ enum DeviceSQLStringImpl {
         #[bw(calc = content.flags())]
         flags: u8,
 
-        #[br(temp)]
-        #[bw(calc = content.byte_count().unwrap() + 4)]
+        #[br(temp, assert(length >= LongBody::HEADER_SIZE))]
+        #[bw(try_calc = content.length())]
         length: u16,
 
         #[brw(magic(0u8))] // padding
-        #[br(args(flags, length - 4))]
+        #[br(args(flags, length - LongBody::HEADER_SIZE))]
         content: LongBody,
     },
 }
@@ -199,16 +271,39 @@ enum LongBody {
     Ucs2le(#[br(count = usize::from(len / 2))] Vec<u16>),
 }
 
+// `NullString` does not implement `Hash`, so this can't be derived.
+impl std::hash::Hash for LongBody {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Isrc(string) => string.0.hash(state),
+            Self::Ascii(bytes) => bytes.hash(state),
+            Self::Ucs2le(units) => units.hash(state),
+        }
+    }
+}
+
 impl LongBody {
-    pub fn byte_count(&self) -> Result<u16, StringError> {
+    /// Size of the long string header (flags, length and padding) in bytes.
+    const HEADER_SIZE: u16 = 4;
+
+    /// Size of the body in bytes, i.e. without the header.
+    pub fn body_len(&self) -> usize {
         match self {
             // ISRC offset is compensating for trailing nullbyte + 0x3 magic byte.
             Self::Isrc(null_str) => null_str.len() + 2,
             Self::Ascii(buf) => buf.len(),
             Self::Ucs2le(buf) => buf.len() * 2,
         }
-        .try_into()
-        .map_err(|_| StringError::TooLong)
+    }
+    pub fn byte_count(&self) -> Result<u16, StringError> {
+        self.body_len().try_into().map_err(|_| StringError::TooLong)
+    }
+    /// Value of the length field, which includes the size of the header.
+    pub fn length(&self) -> Result<u16, StringError> {
+        self.byte_count()?
+            .checked_add(Self::HEADER_SIZE)
+            .ok_or(StringError::TooLong)
     }
     pub fn flags(&self) -> u8 {
         match self {
@@ -228,6 +323,7 @@ impl Default for DeviceSQLString {
 mod test {
     use super::*;
     use crate::util::testing::test_roundtrip;
+    use binrw::{BinRead, BinWrite};
 
     #[test]
     fn default_string() -> Result<(), StringError> {
@@ -276,6 +372,100 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn non_ascii_emoji_and_cjk() -> Result<(), StringError> {
+        let serialized = [
+            0x90, 0x12, 0x00, 0x00, 0x71, 0x67, 0xAC, 0x4E, 0x3C, 0xD8, 0xA7, 0xDF, 0xCA, 0x30,
+            0xA4, 0x30, 0xC8, 0x30,
+        ];
+        test_roundtrip(
+            &serialized,
+            DeviceSQLString::new("東京🎧ナイト".to_string())?,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn long_non_ascii() -> Result<(), StringError> {
+        // Longer than 255 bytes, so that the upper byte of the length field is used as well.
+        let string = "日本語のタイトル".repeat(20);
+        let body: Vec<u8> = string.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(body.len(), 320);
+        let serialized: Vec<u8> = [0x90, 0x44, 0x01, 0x00].into_iter().chain(body).collect();
+        test_roundtrip(&serialized, DeviceSQLString::new(string.clone())?);
+        assert_eq!(DeviceSQLString::new(string.clone())?.into_string()?, string);
+        Ok(())
+    }
+
+    #[test]
+    fn as_bytes() {
+        let long_string: Vec<u8> = [0x40, 0x84, 0x00, 0x00]
+            .into_iter()
+            .chain([b'A'; 128])
+            .collect();
+        let serialized_strings: [(&[u8], usize); 5] = [
+            (&[0x3], 1),
+            (&[0x9, 0x66, 0x6F, 0x6F], 1),
+            (&long_string, 4),
+            (
+                &[
+                    0x90, 0x14, 0x00, 0x00, 0x49, 0x00, 0x20, 0x00, 0x64, 0x27, 0x20, 0x00, 0x52,
+                    0x00, 0x75, 0x00, 0x73, 0x00, 0x74, 0x00,
+                ],
+                4,
+            ),
+            (
+                &[
+                    0x90, 0x12, 0x00, 0x00, 0x03, 0x47, 0x42, 0x41, 0x59, 0x45, 0x36, 0x37, 0x30,
+                    0x30, 0x31, 0x34, 0x39, 0x00,
+                ],
+                4,
+            ),
+        ];
+        for (serialized, header_size) in serialized_strings {
+            let string = DeviceSQLString::read(&mut binrw::io::Cursor::new(serialized)).unwrap();
+            assert_eq!(*string.as_bytes(), serialized[header_size..]);
+        }
+    }
+
+    #[test]
+    fn encoded_len() -> Result<(), StringError> {
+        let strings = [
+            DeviceSQLString::empty(),
+            DeviceSQLString::new("foo".to_owned())?,
+            DeviceSQLString::new("A".repeat(MAX_SHORTSTR_SIZE))?,
+            DeviceSQLString::new("A".repeat(MAX_SHORTSTR_SIZE + 1))?,
+            DeviceSQLString::new("I ❤ Rust".to_string())?,
+            DeviceSQLString::new("日本語のタイトル".repeat(20))?,
+            DeviceSQLString::new_isrc("GBAYE6700149".to_string())?,
+            // Empty string with a long body.
+            DeviceSQLString::read(&mut binrw::io::Cursor::new([0x40, 0x04, 0x00, 0x00])).unwrap(),
+        ];
+        for string in strings {
+            let mut writer = binrw::io::Cursor::new(vec![]);
+            string.write(&mut writer).unwrap();
+            assert_eq!(string.encoded_len(), writer.get_ref().len(), "{:?}", string);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn too_long_non_ascii_string() {
+        // Fits into the length field when encoded as UTF-8, but not when encoded as UTF-16.
+        let string = format!("{}é", "A".repeat(usize::from(u16::MAX) / 2 - 2));
+        assert!(string.len() <= i16::MAX as usize);
+        assert_eq!(
+            DeviceSQLString::new(string).unwrap_err(),
+            StringError::TooLong
+        );
+    }
+
+    #[test]
+    fn invalid_length() {
+        let mut reader = binrw::io::Cursor::new([0x90, 0x02, 0x00, 0x00]);
+        assert!(DeviceSQLString::read(&mut reader).is_err());
+    }
+
     #[test]
     fn too_long_string() {
         // construct super long string containing just "AAAAAAA"...
@@ -306,6 +496,14 @@ mod test {
         );
         test_roundtrip(&[0x3], DeviceSQLString::new_isrc("".to_string())?);
 
+        let parsed = DeviceSQLString::read(&mut binrw::io::Cursor::new(serialized)).unwrap();
+        assert_eq!(parsed.as_isrc().as_deref(), Some("GBAYE6700149"));
+        assert_eq!(DeviceSQLString::new_isrc("".to_string())?.as_isrc(), None);
+        assert_eq!(
+            DeviceSQLString::new("GBAYE6700149".to_string())?.as_isrc(),
+            None
+        );
+
         assert_eq!(
             DeviceSQLString::new_isrc("non-conforming garbage".to_string()).unwrap_err(),
             StringError::InvalidISRC
@@ -0,0 +1,38 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Re-exports of the most commonly used types.
+//!
+//! Instead of importing types from the individual modules, the whole prelude can be imported at
+//! once:
+//!
+//! ```
+//! # pub fn main() -> rekordcrate::Result<()> {
+//! use rekordcrate::prelude::*;
+//!
+//! let export = DeviceExport::open("data/complete_export/demo_tracks")?;
+//! let track = export.merged_track(TrackId(1))?.expect("track not found");
+//! assert_eq!(track.track.title()?, "Demo Track 1");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Types are only added to the prelude if they are needed in most applications, and removing a
+//! type from the prelude is considered a breaking change.
+
+pub use crate::anlz::{TrackAnalysis, ANLZ};
+pub use crate::device::{DeviceExport, MergedTrack};
+pub use crate::pdb::io::Database;
+pub use crate::pdb::{
+    Album, AlbumId, Artist, ArtistId, ArtworkId, DatabaseType, ExtPageType, ExtRow, Genre, GenreId,
+    HistoryPlaylistId, Key, KeyId, Label, LabelId, PageType, PlaylistEntry, PlaylistTreeNode,
+    PlaylistTreeNodeId, Row, Tag, TagId, Track, TrackId,
+};
+pub use crate::setting::{Setting, SettingType};
+pub use crate::util::ColorIndex;
+pub use crate::{Error, Result};
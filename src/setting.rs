@@ -77,13 +77,19 @@ pub struct Setting {
 }
 
 impl Setting {
-    /// Create a new object containing with the given brand string and data.
+    /// Create a new object containing with the given brand string, version string and data.
+    ///
+    /// Rekordbox uses a different version string for each kind of file.
     #[must_use]
-    fn default_with_brand_and_data(brand: NullString, data: SettingData) -> Self {
+    fn default_with_brand_and_data(
+        brand: NullString,
+        version: NullString,
+        data: SettingData,
+    ) -> Self {
         Self {
             brand,
             software: "rekordbox".into(),
-            version: "6.6.1".into(),
+            version,
             data,
         }
     }
@@ -93,6 +99,7 @@ impl Setting {
     pub fn default_devsetting() -> Self {
         Self::default_with_brand_and_data(
             "PIONEER DJ".into(),
+            "6.6.1".into(),
             SettingData::DevSetting(DevSetting::default()),
         )
     }
@@ -102,6 +109,7 @@ impl Setting {
     pub fn default_djmmysetting() -> Self {
         Self::default_with_brand_and_data(
             "PioneerDJ".into(),
+            "1.000".into(),
             SettingData::DJMMySetting(DJMMySetting::default()),
         )
     }
@@ -111,6 +119,7 @@ impl Setting {
     pub fn default_mysetting() -> Self {
         Self::default_with_brand_and_data(
             "PIONEER".into(),
+            "0.001".into(),
             SettingData::MySetting(MySetting::default()),
         )
     }
@@ -120,9 +129,61 @@ impl Setting {
     pub fn default_mysetting2() -> Self {
         Self::default_with_brand_and_data(
             "PIONEER".into(),
+            "0.001".into(),
             SettingData::MySetting2(MySetting2::default()),
         )
     }
+
+    /// Create a new object containing the default values of a settings file of the given type.
+    ///
+    /// Writing the returned object produces the same file that Rekordbox writes to an export when
+    /// the settings have not been changed (including the checksum), e.g.:
+    ///
+    /// ```
+    /// # pub fn main() -> binrw::BinResult<()> {
+    /// use binrw::{io::Cursor, BinWrite};
+    /// use rekordcrate::setting::{Setting, SettingType};
+    ///
+    /// let mut writer = Cursor::new(vec![]);
+    /// Setting::default_for(SettingType::MySetting).write(&mut writer)?;
+    /// assert_eq!(writer.into_inner().len(), 148);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn default_for(setting_type: SettingType) -> Self {
+        match setting_type {
+            SettingType::DevSetting => Self::default_devsetting(),
+            SettingType::DJMMySetting => Self::default_djmmysetting(),
+            SettingType::MySetting => Self::default_mysetting(),
+            SettingType::MySetting2 => Self::default_mysetting2(),
+        }
+    }
+
+    /// Returns the `DJMMYSETTING.DAT` payload of this file, if it is a mixer settings file.
+    ///
+    /// This provides named access to the mixer settings (e.g. channel fader curve, crossfader
+    /// curve or mic low cut) without having to match on [`SettingData`] manually.
+    #[must_use]
+    pub fn djm_my_setting(&self) -> Option<&DJMMySetting> {
+        match &self.data {
+            SettingData::DJMMySetting(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the `DJMMYSETTING.DAT` payload of this file, if it is a
+    /// mixer settings file.
+    ///
+    /// The checksum is recalculated when the file is written, so the returned settings can be
+    /// modified in place.
+    #[must_use]
+    pub fn djm_my_setting_mut(&mut self) -> Option<&mut DJMMySetting> {
+        match &mut self.data {
+            SettingData::DJMMySetting(data) => Some(data),
+            _ => None,
+        }
+    }
 }
 
 impl Setting
@@ -154,6 +215,40 @@ where
     }
 }
 
+/// Kind of `*SETTING.DAT` file.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SettingType {
+    /// `DEVSETTING.DAT`
+    DevSetting,
+    /// `DJMMYSETTING.DAT`
+    DJMMySetting,
+    /// `MYSETTING.DAT`
+    MySetting,
+    /// `MYSETTING2.DAT`
+    MySetting2,
+}
+
+impl SettingType {
+    /// All kinds of setting files.
+    pub const ALL: [Self; 4] = [
+        Self::DevSetting,
+        Self::DJMMySetting,
+        Self::MySetting,
+        Self::MySetting2,
+    ];
+
+    /// Name of the setting file (e.g. `MYSETTING.DAT`).
+    #[must_use]
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::DevSetting => "DEVSETTING.DAT",
+            Self::DJMMySetting => "DJMMYSETTING.DAT",
+            Self::MySetting => "MYSETTING.DAT",
+            Self::MySetting2 => "MYSETTING2.DAT",
+        }
+    }
+}
+
 /// Data section of a `*SETTING.DAT` file.
 #[binrw]
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -1286,3 +1381,63 @@ pub enum KeyDisplayFormat {
     /// Named "Alphanumeric" in the Rekordbox preferences.
     Alphanumeric,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_for_matches_rekordbox() {
+        use binrw::BinRead;
+
+        for setting_type in SettingType::ALL {
+            let path = format!(
+                "data/complete_export/empty/PIONEER/{}",
+                setting_type.file_name()
+            );
+            let expected = std::fs::read(path).unwrap();
+
+            let setting = Setting::default_for(setting_type);
+            let mut writer = Cursor::new(vec![]);
+            setting.write(&mut writer).unwrap();
+            let data = writer.into_inner();
+            assert_eq!(data, expected, "{setting_type:?}");
+
+            let reread = Setting::read(&mut Cursor::new(data)).unwrap();
+            assert_eq!(reread, setting);
+        }
+    }
+
+    #[test]
+    fn player_setting_labels() {
+        assert_eq!(PlayMode::Continue.to_string(), "Continue / On");
+        assert_eq!(PlayMode::Single.to_string(), "Single / Off");
+        assert_eq!(EjectLock::Unlock.to_string(), "Unlock");
+        assert_eq!(QuantizeBeatValue::EighthBeat.to_string(), "1/8 Beat");
+        assert_eq!(QuantizeBeatValue::FullBeat.to_string(), "1 Beat");
+        assert_eq!(TempoRange::SixPercent.to_string(), "±6%");
+        assert_eq!(TempoRange::TenPercent.to_string(), "±10%");
+        assert_eq!(TempoRange::SixteenPercent.to_string(), "±16%");
+        assert_eq!(TempoRange::Wide.to_string(), "Wide");
+        assert_eq!(PhaseMeter::Type1.to_string(), "Type 1");
+        assert_eq!(PhaseMeter::Type2.to_string(), "Type 2");
+        assert_eq!(Waveform::PhaseMeter.to_string(), "Phase Meter");
+        assert_eq!(
+            VinylSpeedAdjust::TouchRelease.to_string(),
+            "Touch & Release"
+        );
+        assert_eq!(JogRingBrightness::Dark.to_string(), "1 (Dark)");
+        assert_eq!(Language::German.to_string(), "Deutsch");
+        assert_eq!(WaveformColor::TriBand.to_string(), "3Band");
+    }
+
+    #[test]
+    fn mixer_setting_labels() {
+        assert_eq!(ChannelFaderCurve::SteepTop.to_string(), "Steep Top");
+        assert_eq!(ChannelFaderCurve::SteepBottom.to_string(), "Steep Bottom");
+        assert_eq!(CrossfaderCurve::ConstantPower.to_string(), "Constant Power");
+        assert_eq!(CrossfaderCurve::FastCut.to_string(), "Fast Cut");
+        assert_eq!(TalkOverLevel::Minus24dB.to_string(), "-24dB");
+        assert_eq!(TalkOverLevel::Minus6dB.to_string(), "-6dB");
+    }
+}
@@ -8,25 +8,58 @@
 
 //! Common types used in multiple modules.
 
-use crate::pdb::string::StringError;
+use crate::pdb::{string::StringError, InvalidTempoError, PlaylistTreeNodeId};
 use binrw::binrw;
 use thiserror::Error;
 
 /// Enumerates errors returned by this library.
+///
+/// The errors of the underlying libraries and of the individual modules are converted into this
+/// type automatically (e.g. using the `?` operator). The `Display` implementation only describes
+/// the kind of failure. The underlying error is returned by [`std::error::Error::source`], so
+/// that error reporters which print the whole chain of causes don't show it twice.
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum RekordcrateError {
     /// Represents a failure to decode a DeviceSQL string.
-    #[error(transparent)]
+    #[error("invalid string")]
     StringError(#[from] StringError),
 
     /// Represents a failure to parse input.
-    #[error(transparent)]
+    #[error("failed to parse")]
     ParseError(#[from] binrw::Error),
 
     /// Represents an `std::io::Error`.
-    #[error(transparent)]
+    #[error("I/O error")]
     IOError(#[from] std::io::Error),
+
+    /// Represents a failure to parse a rekordbox XML file.
+    #[error("failed to parse XML")]
+    XmlError(#[from] quick_xml::DeError),
+
+    /// A track has a tempo value that is out of range.
+    #[error(transparent)]
+    InvalidTempo(#[from] InvalidTempoError),
+
+    /// A playlist contains more than one entry with the same index, so the track order is
+    /// ambiguous.
+    #[error("playlist {} contains multiple entries at index {entry_index}", .playlist_id.0)]
+    DuplicateEntryIndex {
+        /// ID of the playlist.
+        playlist_id: PlaylistTreeNodeId,
+        /// The index that is used more than once.
+        entry_index: u32,
+    },
+
+    /// A track rating is out of range (see [`Track::MAX_RATING`](crate::pdb::Track::MAX_RATING)).
+    #[error("invalid rating {0} (must be between 0 and 5)")]
+    InvalidRating(u8),
+}
+
+impl From<quick_xml::Error> for RekordcrateError {
+    fn from(error: quick_xml::Error) -> Self {
+        Self::XmlError(error.into())
+    }
 }
 
 /// Type alias for results where the error is a `RekordcrateError`.
@@ -34,7 +67,7 @@ pub type RekordcrateResult<T> = std::result::Result<T, RekordcrateError>;
 
 /// Indexed Color identifiers used for memory cues and tracks.
 #[binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum ColorIndex {
     /// No color.
     #[brw(magic = 0u8)]
@@ -65,6 +98,253 @@ pub enum ColorIndex {
     Purple,
 }
 
+/// The mode (or "scale") of a musical key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum KeyMode {
+    /// Major key.
+    Major,
+    /// Minor key.
+    Minor,
+}
+
+/// A musical key, i.e. a tonic pitch class together with its mode.
+///
+/// Rekordbox stores key names in classical notation (e.g. `Dm` or `F#`), but DJs often prefer the
+/// [Camelot Wheel](https://mixedinkey.com/camelot-wheel/) (e.g. `7A`) or the Open Key notation
+/// (e.g. `12m`) for harmonic mixing. This type can be used to convert between these notations.
+///
+/// ```
+/// use rekordcrate::util::MusicalKey;
+///
+/// let key = MusicalKey::parse("Dm").unwrap();
+/// assert_eq!(key.camelot(), "7A");
+/// assert_eq!(key.open_key(), "12m");
+/// assert_eq!(MusicalKey::parse("7A"), Some(key));
+/// assert_eq!(MusicalKey::parse("not a key"), None);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct MusicalKey {
+    /// Pitch class of the tonic in semitones above C (`0..12`).
+    pitch_class: u8,
+    /// Mode of the key.
+    mode: KeyMode,
+}
+
+impl MusicalKey {
+    /// Names of the major keys in classical notation, indexed by pitch class.
+    const MAJOR_NAMES: [&'static str; 12] = [
+        "C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+    ];
+    /// Names of the minor keys in classical notation, indexed by pitch class.
+    const MINOR_NAMES: [&'static str; 12] = [
+        "Cm", "C#m", "Dm", "Ebm", "Em", "Fm", "F#m", "Gm", "G#m", "Am", "Bbm", "Bm",
+    ];
+
+    /// Create a new key from a pitch class (in semitones above C) and a mode.
+    ///
+    /// Returns `None` if the pitch class is out of range.
+    #[must_use]
+    pub fn new(pitch_class: u8, mode: KeyMode) -> Option<Self> {
+        (pitch_class < 12).then_some(Self { pitch_class, mode })
+    }
+
+    /// Pitch class of the tonic in semitones above C (`0..12`).
+    #[must_use]
+    pub fn pitch_class(&self) -> u8 {
+        self.pitch_class
+    }
+
+    /// Mode of the key.
+    #[must_use]
+    pub fn mode(&self) -> KeyMode {
+        self.mode
+    }
+
+    /// Parse a key name in classical, Camelot or Open Key notation.
+    ///
+    /// Returns `None` if the name is not recognized (e.g. because it's a free-text value).
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::from_classical(name)
+            .or_else(|| Self::from_camelot(name))
+            .or_else(|| Self::from_open_key(name))
+    }
+
+    /// Parse a key name in classical notation (e.g. `Dm`, `F#`, `Bbm` or `A minor`).
+    #[must_use]
+    pub fn from_classical(name: &str) -> Option<Self> {
+        let mut chars = name.trim().chars();
+        let base: i8 = match chars.next()?.to_ascii_uppercase() {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return None,
+        };
+        let rest = chars.as_str();
+        let (offset, rest) = match rest.chars().next() {
+            Some('#' | '♯') => (1, &rest[rest.chars().next()?.len_utf8()..]),
+            Some('b' | '♭') => (-1, &rest[rest.chars().next()?.len_utf8()..]),
+            _ => (0, rest),
+        };
+        let mode = match rest.trim().to_ascii_lowercase().as_str() {
+            "" | "maj" | "major" => KeyMode::Major,
+            "m" | "min" | "minor" => KeyMode::Minor,
+            _ => return None,
+        };
+        let pitch_class = u8::try_from((base + offset).rem_euclid(12)).ok()?;
+        Self::new(pitch_class, mode)
+    }
+
+    /// Parse a key name in Camelot notation (e.g. `7A`).
+    #[must_use]
+    pub fn from_camelot(name: &str) -> Option<Self> {
+        let (number, mode) = Self::parse_wheel_notation(name, 'B', 'A')?;
+        // 8B is C major, and each step clockwise is a fifth up.
+        Self::from_wheel_position((number + 4) % 12, mode)
+    }
+
+    /// Parse a key name in Open Key notation (e.g. `12m`).
+    #[must_use]
+    pub fn from_open_key(name: &str) -> Option<Self> {
+        let (number, mode) = Self::parse_wheel_notation(name, 'd', 'm')?;
+        // 1d is C major, and each step is a fifth up.
+        Self::from_wheel_position((number + 11) % 12, mode)
+    }
+
+    /// Parse a wheel position (`1..=12`) followed by a mode suffix.
+    fn parse_wheel_notation(name: &str, major: char, minor: char) -> Option<(u8, KeyMode)> {
+        let name = name.trim();
+        let suffix = name.chars().last()?;
+        let mode = if suffix.eq_ignore_ascii_case(&major) {
+            KeyMode::Major
+        } else if suffix.eq_ignore_ascii_case(&minor) {
+            KeyMode::Minor
+        } else {
+            return None;
+        };
+        let number: u8 = name[..name.len() - suffix.len_utf8()].parse().ok()?;
+        (1..=12).contains(&number).then_some((number, mode))
+    }
+
+    /// Create a key from its number of fifths above C major (or A minor, respectively).
+    fn from_wheel_position(fifths: u8, mode: KeyMode) -> Option<Self> {
+        let relative_major = (fifths * 7) % 12;
+        let pitch_class = match mode {
+            KeyMode::Major => relative_major,
+            KeyMode::Minor => (relative_major + 9) % 12,
+        };
+        Self::new(pitch_class, mode)
+    }
+
+    /// Number of fifths of the relative major key above C major (`0..12`).
+    fn wheel_position(&self) -> u8 {
+        let relative_major = match self.mode {
+            KeyMode::Major => self.pitch_class,
+            KeyMode::Minor => (self.pitch_class + 3) % 12,
+        };
+        // Multiplying by 7 (the inverse of 7 modulo 12) converts semitones into fifths.
+        (relative_major * 7) % 12
+    }
+
+    /// Key name in classical notation (e.g. `Dm`), using the same spelling as Rekordbox.
+    #[must_use]
+    pub fn classical(&self) -> &'static str {
+        let names = match self.mode {
+            KeyMode::Major => &Self::MAJOR_NAMES,
+            KeyMode::Minor => &Self::MINOR_NAMES,
+        };
+        names[usize::from(self.pitch_class)]
+    }
+
+    /// Key name in Camelot notation (e.g. `7A`).
+    #[must_use]
+    pub fn camelot(&self) -> String {
+        let number = (self.wheel_position() + 7) % 12 + 1;
+        let suffix = match self.mode {
+            KeyMode::Major => 'B',
+            KeyMode::Minor => 'A',
+        };
+        format!("{number}{suffix}")
+    }
+
+    /// Key name in Open Key notation (e.g. `12m`).
+    #[must_use]
+    pub fn open_key(&self) -> String {
+        let number = self.wheel_position() + 1;
+        let suffix = match self.mode {
+            KeyMode::Major => 'd',
+            KeyMode::Minor => 'm',
+        };
+        format!("{number}{suffix}")
+    }
+}
+
+/// Convert a path stored in the database (e.g. the file path of a track) to a path relative to
+/// the root of the export.
+///
+/// Paths are usually stored with forward slashes (e.g. `/Contents/Artist/Track.mp3`), but some
+/// exports created by Rekordbox on Windows contain backslashes instead. Both are treated as
+/// separators, and leading, trailing and repeated separators are ignored, so that the result can
+/// be joined to the export root on any platform. The `.` and `..` components are dropped as
+/// well, so that the result can't point outside of the export root.
+pub(crate) fn normalize_path(path: &str) -> std::path::PathBuf {
+    path.split(['/', '\\'])
+        .filter(|component| !matches!(*component, "" | "." | ".."))
+        .collect()
+}
+
+/// Normalize a string for searching, so that it can be compared to other strings regardless of
+/// case and diacritics.
+///
+/// The string is converted to lowercase and letters with diacritics from the Latin-1 Supplement
+/// and Latin Extended-A blocks are replaced by their base letters (e.g. `é` becomes `e`).
+/// Ligatures and special letters are expanded (`æ` becomes `ae`, `ß` becomes `ss`). All other
+/// characters are kept as-is.
+///
+/// The exact normalization rules of the player firmware are not known, but this reproduces how
+/// searching on a CDJ ignores case and accents (e.g. searching for `beyonce` finds `Beyoncé`).
+pub(crate) fn search_form(string: &str) -> String {
+    let mut result = String::with_capacity(string.len());
+    for c in string.chars().flat_map(char::to_lowercase) {
+        let folded = match c {
+            'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+            'æ' => "ae",
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+            'ð' | 'ď' | 'đ' => "d",
+            'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+            'ĥ' | 'ħ' => "h",
+            'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+            'ĳ' => "ij",
+            'ĵ' => "j",
+            'ķ' => "k",
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+            'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' => "n",
+            'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+            'œ' => "oe",
+            'ŕ' | 'ŗ' | 'ř' => "r",
+            'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+            'ß' => "ss",
+            'ţ' | 'ť' | 'ŧ' => "t",
+            'þ' => "th",
+            'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+            'ŵ' => "w",
+            'ý' | 'ÿ' | 'ŷ' => "y",
+            'ź' | 'ż' | 'ž' => "z",
+            _ => {
+                result.push(c);
+                continue;
+            }
+        };
+        result.push_str(folded);
+    }
+    result
+}
+
 #[cfg(test)]
 pub(crate) mod testing {
     use binrw::{
@@ -129,3 +409,152 @@ pub(crate) mod testing {
         );
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn search_form_folds_case_and_diacritics() {
+        assert_eq!(search_form("Beyoncé"), "beyonce");
+        assert_eq!(search_form("MØ"), "mo");
+        assert_eq!(search_form("Straße"), "strasse");
+        assert_eq!(search_form("Ærøskøbing Łódź"), "aeroskobing lodz");
+        assert_eq!(search_form("Drum & Bass"), "drum & bass");
+        assert_eq!(search_form("きゃりーぱみゅぱみゅ"), "きゃりーぱみゅぱみゅ");
+    }
+
+    #[test]
+    fn normalize_path_accepts_both_separators() {
+        let expected: std::path::PathBuf =
+            ["Contents", "Artist", "Demo Track 1.mp3"].iter().collect();
+        assert_eq!(
+            normalize_path("/Contents/Artist/Demo Track 1.mp3"),
+            expected
+        );
+        assert_eq!(
+            normalize_path("\\Contents\\Artist\\Demo Track 1.mp3"),
+            expected
+        );
+        assert_eq!(
+            normalize_path("Contents\\Artist//Demo Track 1.mp3"),
+            expected
+        );
+        assert_eq!(normalize_path(""), std::path::PathBuf::new());
+        assert_eq!(
+            normalize_path("/Contents/../../Artist/./Demo Track 1.mp3"),
+            ["Contents", "Artist", "Demo Track 1.mp3"]
+                .iter()
+                .collect::<std::path::PathBuf>()
+        );
+        assert_eq!(normalize_path("..\\..\\.."), std::path::PathBuf::new());
+    }
+
+    #[test]
+    fn error_conversions() {
+        let error = RekordcrateError::from(StringError::TooLong);
+        assert!(matches!(
+            error,
+            RekordcrateError::StringError(StringError::TooLong)
+        ));
+        assert_eq!(error.to_string(), "invalid string");
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "string is too long for DeviceSQL"
+        );
+
+        let error = RekordcrateError::from(binrw::Error::AssertFail {
+            pos: 4,
+            message: "bad magic".to_string(),
+        });
+        assert!(matches!(error, RekordcrateError::ParseError(_)));
+        assert_eq!(error.to_string(), "failed to parse");
+        assert!(error.source().is_some());
+
+        let error = RekordcrateError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(matches!(error, RekordcrateError::IOError(_)));
+        assert_eq!(error.to_string(), "I/O error");
+        assert_eq!(error.source().unwrap().to_string(), "entity not found");
+
+        let error = RekordcrateError::from(quick_xml::DeError::Custom("bad XML".to_string()));
+        assert!(matches!(error, RekordcrateError::XmlError(_)));
+        assert_eq!(error.to_string(), "failed to parse XML");
+        assert_eq!(error.source().unwrap().to_string(), "bad XML");
+
+        let error = RekordcrateError::from(quick_xml::Error::Io(std::sync::Arc::new(
+            std::io::Error::from(std::io::ErrorKind::UnexpectedEof),
+        )));
+        assert!(matches!(error, RekordcrateError::XmlError(_)));
+
+        let error = RekordcrateError::from(InvalidTempoError(0));
+        assert!(matches!(
+            error,
+            RekordcrateError::InvalidTempo(InvalidTempoError(0))
+        ));
+        assert_eq!(
+            error.to_string(),
+            "invalid tempo of 0 centi-BPM (expected 1 to 65535)"
+        );
+
+        let error = RekordcrateError::DuplicateEntryIndex {
+            playlist_id: PlaylistTreeNodeId(93),
+            entry_index: 3,
+        };
+        assert_eq!(
+            error.to_string(),
+            "playlist 93 contains multiple entries at index 3"
+        );
+    }
+
+    #[test]
+    fn musical_key_notations() {
+        let all_keys = (0..12).flat_map(|pitch_class| {
+            [KeyMode::Major, KeyMode::Minor]
+                .into_iter()
+                .map(move |mode| MusicalKey::new(pitch_class, mode).unwrap())
+        });
+        for key in all_keys {
+            assert_eq!(MusicalKey::from_classical(key.classical()), Some(key));
+            assert_eq!(MusicalKey::from_camelot(&key.camelot()), Some(key));
+            assert_eq!(MusicalKey::from_open_key(&key.open_key()), Some(key));
+        }
+    }
+
+    #[test]
+    fn musical_key_conversion() {
+        for (classical, camelot, open_key) in [
+            ("C", "8B", "1d"),
+            ("Am", "8A", "1m"),
+            ("Dm", "7A", "12m"),
+            ("F#", "2B", "7d"),
+            ("Bbm", "3A", "8m"),
+            ("B", "1B", "6d"),
+            ("Ebm", "2A", "7m"),
+        ] {
+            let key = MusicalKey::parse(classical).unwrap();
+            assert_eq!(key.classical(), classical);
+            assert_eq!(key.camelot(), camelot);
+            assert_eq!(key.open_key(), open_key);
+            assert_eq!(MusicalKey::parse(camelot), Some(key));
+            assert_eq!(MusicalKey::parse(open_key), Some(key));
+        }
+    }
+
+    #[test]
+    fn musical_key_alternative_spellings() {
+        let key = MusicalKey::new(1, KeyMode::Minor).unwrap();
+        assert_eq!(MusicalKey::parse("C#m"), Some(key));
+        assert_eq!(MusicalKey::parse("Dbm"), Some(key));
+        assert_eq!(MusicalKey::parse("D♭ minor"), Some(key));
+        assert_eq!(MusicalKey::parse("12a"), Some(key));
+        assert_eq!(MusicalKey::parse("Cb"), MusicalKey::parse("B"));
+    }
+
+    #[test]
+    fn musical_key_free_text() {
+        for name in ["", "Dm7", "13A", "0B", "Hm", "1x", "A#b", "unknown"] {
+            assert_eq!(MusicalKey::parse(name), None, "{name}");
+        }
+    }
+}
@@ -15,9 +15,18 @@
 //! - <https://rekordbox.com/en/support/developer/>
 //! - <https://cdn.rekordbox.com/files/20200410160904/xml_format_list.pdf>
 //! - <https://pyrekordbox.readthedocs.io/en/stable/formats/xml.html>
+//!
+//! # Large files
+//!
+//! Deserializing a [`Document`] loads the whole file into memory. For very large collections, use
+//! [`TrackStream`] instead, which yields the tracks of the `COLLECTION` element one at a time.
 type NaiveDate = String; //Replace with "use chrono::naive::NaiveDate;"
+use crate::util::RekordcrateResult;
+use quick_xml::{events::Event, DeError, Reader, Writer};
 use serde::{de::Error, ser::Serializer, Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fmt;
+use std::io::BufRead;
 
 /// The XML root element of a rekordbox XML file.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -36,6 +45,20 @@ pub struct Document {
     playlists: Playlists,
 }
 
+impl Document {
+    /// The tracks in the collection.
+    #[must_use]
+    pub fn tracks(&self) -> &[Track] {
+        &self.collection.track
+    }
+
+    /// The root folder of the playlist tree (usually named `ROOT`).
+    #[must_use]
+    pub fn playlists(&self) -> &PlaylistFolderNode {
+        &self.playlists.node
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 struct Product {
     /// Name of product
@@ -63,114 +86,284 @@ struct Collection {
 
 /// "Location" is essential for each track ;
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-struct Track {
+pub struct Track {
     /// Identification of track
     #[serde(rename = "@TrackID")]
-    trackid: i32,
+    pub(crate) trackid: i32,
     /// Name of track
     #[serde(rename = "@Name")]
-    name: Option<String>,
+    pub(crate) name: Option<String>,
     /// Name of artist
     #[serde(rename = "@Artist")]
-    artist: Option<String>,
+    pub(crate) artist: Option<String>,
     /// Name of composer (or producer)
     #[serde(rename = "@Composer")]
-    composer: Option<String>,
+    pub(crate) composer: Option<String>,
     /// Name of Album
     #[serde(rename = "@Album")]
-    album: Option<String>,
+    pub(crate) album: Option<String>,
     /// Name of goupe
     #[serde(rename = "@Grouping")]
-    grouping: Option<String>,
+    pub(crate) grouping: Option<String>,
     /// Name of genre
     #[serde(rename = "@Genre")]
-    genre: Option<String>,
+    pub(crate) genre: Option<String>,
     /// Type of audio file
     #[serde(rename = "@Kind")]
-    kind: Option<String>,
+    pub(crate) kind: Option<String>,
     /// Size of audio file
     /// Unit : Octet
     #[serde(rename = "@Size")]
-    size: Option<i64>,
+    pub(crate) size: Option<i64>,
     /// Duration of track
     /// Unit : Second (without decimal numbers)
     #[serde(rename = "@TotalTime")]
-    totaltime: Option<f64>,
+    pub(crate) totaltime: Option<f64>,
     /// Order number of the disc of the album
     #[serde(rename = "@DiscNumber")]
-    discnumber: Option<i32>,
+    pub(crate) discnumber: Option<i32>,
     /// Order number of the track in the album
     #[serde(rename = "@TrackNumber")]
-    tracknumber: Option<i32>,
+    pub(crate) tracknumber: Option<i32>,
     /// Year of release
     #[serde(rename = "@Year")]
-    year: Option<i32>,
+    pub(crate) year: Option<i32>,
     /// Value of average BPM
     /// Unit : Second (with decimal numbers)
     #[serde(rename = "@AverageBpm")]
-    averagebpm: Option<f64>,
+    pub(crate) averagebpm: Option<f64>,
     /// Date of last modification
     /// Format : yyyy- mm- dd ; ex. : 2010- 08- 21
     #[serde(rename = "@DateModified")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    datemodified: Option<NaiveDate>,
+    pub(crate) datemodified: Option<NaiveDate>,
     /// Date of addition
     /// Format : yyyy- mm- dd ; ex. : 2010- 08- 21
     #[serde(rename = "@DateAdded")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    dateadded: Option<NaiveDate>,
+    pub(crate) dateadded: Option<NaiveDate>,
     /// Encoding bit rate
     /// Unit : Kbps
     #[serde(rename = "@BitRate")]
-    bitrate: Option<i32>,
+    pub(crate) bitrate: Option<i32>,
     /// Frequency of sampling
     /// Unit : Hertz
     #[serde(rename = "@SampleRate")]
-    samplerate: Option<f64>,
+    pub(crate) samplerate: Option<f64>,
     /// Comments
     #[serde(rename = "@Comments")]
-    comments: Option<String>,
+    pub(crate) comments: Option<String>,
     /// Play count of the track
     #[serde(rename = "@PlayCount")]
-    playcount: Option<i32>,
+    pub(crate) playcount: Option<i32>,
     /// Date of last playing
     /// Format : yyyy- mm- dd ; ex. : 2010- 08- 21
     #[serde(rename = "@LastPlayed")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    lastplayed: Option<NaiveDate>,
+    pub(crate) lastplayed: Option<NaiveDate>,
     /// Rating of the track
     /// 0 star = "@0", 1 star = "51", 2 stars = "102", 3 stars = "153", 4 stars = "204", 5 stars = "255"
     #[serde(rename = "@Rating")]
-    rating: Option<i32>,
+    pub(crate) rating: Option<i32>,
     /// Location of the file
     /// includes the file name (URI formatted)
     #[serde(rename = "@Location")]
-    location: String,
+    pub(crate) location: String,
     /// Name of remixer
     #[serde(rename = "@Remixer")]
-    remixer: Option<String>,
+    pub(crate) remixer: Option<String>,
     /// Tonality (Kind of musical key)
     #[serde(rename = "@Tonality")]
-    tonality: Option<String>,
+    pub(crate) tonality: Option<String>,
     /// Name of record label
     #[serde(rename = "@Label")]
-    label: Option<String>,
+    pub(crate) label: Option<String>,
     /// Name of mix
     #[serde(rename = "@Mix")]
-    mix: Option<String>,
+    pub(crate) mix: Option<String>,
     /// Colour for track grouping
     /// RGB format (3 bytes) ; rekordbox : Rose(0xFF007F), Red(0xFF0000), Orange(0xFFA500), Lemon(0xFFFF00), Green(0x00FF00), Turquoise(0x25FDE9),  Blue(0x0000FF), Violet(0x660099)
     #[serde(rename = "@Colour")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    colour: Option<String>,
+    pub(crate) colour: Option<String>,
     #[serde(rename = "TEMPO")]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
-    tempos: Vec<Tempo>,
+    pub(crate) tempos: Vec<Tempo>,
     #[serde(rename = "POSITION_MARK")]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
-    position_marks: Vec<PositionMark>,
+    pub(crate) position_marks: Vec<PositionMark>,
+}
+
+impl Track {
+    /// Identification of the track in the collection.
+    #[must_use]
+    pub fn track_id(&self) -> i32 {
+        self.trackid
+    }
+
+    /// Name (title) of the track.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Name of the artist.
+    #[must_use]
+    pub fn artist(&self) -> Option<&str> {
+        self.artist.as_deref()
+    }
+
+    /// Name of the composer (or producer).
+    #[must_use]
+    pub fn composer(&self) -> Option<&str> {
+        self.composer.as_deref()
+    }
+
+    /// Name of the album.
+    #[must_use]
+    pub fn album(&self) -> Option<&str> {
+        self.album.as_deref()
+    }
+
+    /// Name of the grouping.
+    #[must_use]
+    pub fn grouping(&self) -> Option<&str> {
+        self.grouping.as_deref()
+    }
+
+    /// Name of the genre.
+    #[must_use]
+    pub fn genre(&self) -> Option<&str> {
+        self.genre.as_deref()
+    }
+
+    /// Type of the audio file (e.g. `MP3 File`).
+    #[must_use]
+    pub fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    /// Size of the audio file in bytes.
+    #[must_use]
+    pub fn size(&self) -> Option<i64> {
+        self.size
+    }
+
+    /// Duration of the track in seconds.
+    #[must_use]
+    pub fn total_time(&self) -> Option<f64> {
+        self.totaltime
+    }
+
+    /// Number of the disc of the album.
+    #[must_use]
+    pub fn disc_number(&self) -> Option<i32> {
+        self.discnumber
+    }
+
+    /// Number of the track on the album.
+    #[must_use]
+    pub fn track_number(&self) -> Option<i32> {
+        self.tracknumber
+    }
+
+    /// Year of release.
+    #[must_use]
+    pub fn year(&self) -> Option<i32> {
+        self.year
+    }
+
+    /// Average tempo in beats per minute.
+    #[must_use]
+    pub fn average_bpm(&self) -> Option<f64> {
+        self.averagebpm
+    }
+
+    /// Date of the last modification (e.g. `2010-08-21`).
+    #[must_use]
+    pub fn date_modified(&self) -> Option<&str> {
+        self.datemodified.as_deref()
+    }
+
+    /// Date on which the track was added (e.g. `2010-08-21`).
+    #[must_use]
+    pub fn date_added(&self) -> Option<&str> {
+        self.dateadded.as_deref()
+    }
+
+    /// Encoding bit rate in kbps.
+    #[must_use]
+    pub fn bit_rate(&self) -> Option<i32> {
+        self.bitrate
+    }
+
+    /// Sample rate in Hz.
+    #[must_use]
+    pub fn sample_rate(&self) -> Option<f64> {
+        self.samplerate
+    }
+
+    /// Comments of the track.
+    #[must_use]
+    pub fn comments(&self) -> Option<&str> {
+        self.comments.as_deref()
+    }
+
+    /// Number of times the track was played.
+    #[must_use]
+    pub fn play_count(&self) -> Option<i32> {
+        self.playcount
+    }
+
+    /// Date on which the track was last played (e.g. `2010-08-21`).
+    #[must_use]
+    pub fn last_played(&self) -> Option<&str> {
+        self.lastplayed.as_deref()
+    }
+
+    /// Rating of the track (`0` for no stars up to `255` for five stars, in steps of `51`).
+    #[must_use]
+    pub fn rating(&self) -> Option<i32> {
+        self.rating
+    }
+
+    /// Location of the audio file as a percent-encoded file URI.
+    #[must_use]
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Name of the remixer.
+    #[must_use]
+    pub fn remixer(&self) -> Option<&str> {
+        self.remixer.as_deref()
+    }
+
+    /// Musical key of the track (e.g. `Fm`).
+    #[must_use]
+    pub fn tonality(&self) -> Option<&str> {
+        self.tonality.as_deref()
+    }
+
+    /// Name of the record label.
+    #[must_use]
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Name of the mix (e.g. `Original Mix`).
+    #[must_use]
+    pub fn mix(&self) -> Option<&str> {
+        self.mix.as_deref()
+    }
+
+    /// Color of the track as an RGB value (e.g. `0xFF0000`).
+    #[must_use]
+    pub fn colour(&self) -> Option<&str> {
+        self.colour.as_deref()
+    }
 }
 
 /// 0 star = "@0", 1 star = "51", 2 stars = "102", 3 stars = "153", 4 stars = "204", 5 stars = "255"
@@ -187,7 +380,7 @@ enum StarRating {
 
 /// For BeatGrid; More than two "TEMPO" can exist for each track
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-struct Tempo {
+pub(crate) struct Tempo {
     /// Start position of BeatGrid
     /// Unit : Second (with decimal numbers)
     #[serde(rename = "@Inizio")]
@@ -208,7 +401,7 @@ struct Tempo {
 
 /// More than two "POSITION MARK" can exist for each track
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-struct PositionMark {
+pub(crate) struct PositionMark {
     /// Name of position mark
     #[serde(rename = "@Name")]
     name: String,
@@ -237,15 +430,35 @@ struct Playlists {
     node: PlaylistFolderNode,
 }
 
+/// A node in the playlist tree, which is either a folder or a playlist.
 #[derive(Debug, PartialEq, Clone, Serialize)]
 #[serde(tag = "@Type")]
-enum PlaylistGenericNode {
+pub enum PlaylistGenericNode {
+    /// A folder that contains other nodes.
     #[serde(rename = "0")]
     Folder(PlaylistFolderNode),
+    /// A playlist that contains tracks.
     #[serde(rename = "1")]
     Playlist(PlaylistPlaylistNode),
 }
 
+impl PlaylistGenericNode {
+    /// Name of the folder or playlist.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Folder(folder) => folder.name(),
+            Self::Playlist(playlist) => playlist.name(),
+        }
+    }
+
+    /// Indicates whether this node is a folder.
+    #[must_use]
+    pub fn is_folder(&self) -> bool {
+        matches!(self, Self::Folder(_))
+    }
+}
+
 impl<'de> Deserialize<'de> for PlaylistGenericNode {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -358,8 +571,9 @@ impl<'de> Deserialize<'de> for PlaylistGenericNode {
     }
 }
 
+/// A folder in the playlist tree.
 #[derive(Debug, PartialEq, Clone, Deserialize)]
-struct PlaylistFolderNode {
+pub struct PlaylistFolderNode {
     /// Name of NODE
     #[serde(rename = "@Name")]
     name: String,
@@ -370,6 +584,20 @@ struct PlaylistFolderNode {
     nodes: Vec<PlaylistGenericNode>,
 }
 
+impl PlaylistFolderNode {
+    /// Name of the folder.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The folders and playlists in this folder.
+    #[must_use]
+    pub fn nodes(&self) -> &[PlaylistGenericNode] {
+        &self.nodes
+    }
+}
+
 impl Serialize for PlaylistFolderNode {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -398,8 +626,9 @@ impl Serialize for PlaylistFolderNode {
     }
 }
 
+/// A playlist in the playlist tree.
 #[derive(Debug, PartialEq, Clone, Deserialize)]
-struct PlaylistPlaylistNode {
+pub struct PlaylistPlaylistNode {
     /// Name of NODE
     #[serde(rename = "@Name")]
     name: String,
@@ -413,6 +642,28 @@ struct PlaylistPlaylistNode {
     tracks: Vec<PlaylistTrack>,
 }
 
+impl PlaylistPlaylistNode {
+    /// Name of the playlist.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Kind of identification used by the tracks of this playlist, i.e. `"0"` if the
+    /// [`PlaylistTrack::key`] refers to the `TrackID` of the track in the collection, or `"1"`
+    /// if it refers to the `Location`.
+    #[must_use]
+    pub fn key_type(&self) -> &str {
+        &self.keytype
+    }
+
+    /// The tracks in this playlist, in order.
+    #[must_use]
+    pub fn tracks(&self) -> &[PlaylistTrack] {
+        &self.tracks
+    }
+}
+
 impl Serialize for PlaylistPlaylistNode {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -445,10 +696,185 @@ impl Serialize for PlaylistPlaylistNode {
     }
 }
 
+/// A track in a playlist.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-struct PlaylistTrack {
+pub struct PlaylistTrack {
     /// Identification of track
     /// "Track ID" or "Location" in "COLLECTION"
     #[serde(rename = "@Key")]
     key: i32,
 }
+
+impl PlaylistTrack {
+    /// Identification of the track in the collection (see [`PlaylistPlaylistNode::key_type`]).
+    #[must_use]
+    pub fn key(&self) -> i32 {
+        self.key
+    }
+}
+
+/// Streaming reader for the tracks in the `COLLECTION` element of a rekordbox XML file.
+///
+/// In contrast to deserializing a [`Document`], this only holds a single track in memory at a
+/// time. Reading stops at the end of the `COLLECTION` element, so the playlists are not parsed.
+///
+/// ```
+/// # pub fn main() -> rekordcrate::Result<()> {
+/// use rekordcrate::xml::TrackStream;
+///
+/// let file = std::fs::File::open("data/xml/database.xml")?;
+/// let tracks = TrackStream::new(std::io::BufReader::new(file))
+///     .collect::<rekordcrate::Result<Vec<_>>>()?;
+/// assert_eq!(tracks.len(), 6);
+/// assert_eq!(tracks[4].name(), Some("Demo Track 1"));
+/// assert_eq!(tracks[4].artist(), Some("Loopmasters"));
+/// assert_eq!(tracks[4].average_bpm(), Some(128.0));
+/// # Ok(())
+/// # }
+/// ```
+pub struct TrackStream<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    in_collection: bool,
+    done: bool,
+}
+
+impl<R: BufRead> fmt::Debug for TrackStream<R> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TrackStream")
+            .field("in_collection", &self.in_collection)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: BufRead> TrackStream<R> {
+    /// Create a new stream that reads the tracks from the given reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: Reader::from_reader(reader),
+            buf: Vec::new(),
+            in_collection: false,
+            done: false,
+        }
+    }
+
+    /// Read the next `TRACK` element from the collection, or `None` if there are no tracks left.
+    fn read_track(&mut self) -> Result<Option<Track>, DeError> {
+        loop {
+            self.buf.clear();
+            let mut writer = Writer::new(Vec::new());
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Start(e) if e.name().as_ref() == b"COLLECTION" => {
+                    self.in_collection = true;
+                    continue;
+                }
+                Event::End(e) if e.name().as_ref() == b"COLLECTION" => return Ok(None),
+                Event::Eof => return Ok(None),
+                Event::Empty(e) if self.in_collection && e.name().as_ref() == b"TRACK" => {
+                    writer
+                        .write_event(Event::Empty(e))
+                        .map_err(quick_xml::Error::from)?;
+                }
+                Event::Start(e) if self.in_collection && e.name().as_ref() == b"TRACK" => {
+                    // Copy the element including its children (`TEMPO`, `POSITION_MARK`), so
+                    // that it can be deserialized on its own.
+                    writer
+                        .write_event(Event::Start(e))
+                        .map_err(quick_xml::Error::from)?;
+                    let mut depth = 0usize;
+                    let mut inner_buf = Vec::new();
+                    loop {
+                        inner_buf.clear();
+                        let event = self.reader.read_event_into(&mut inner_buf)?;
+                        match &event {
+                            Event::Start(_) => depth += 1,
+                            Event::End(_) if depth == 0 => {
+                                writer.write_event(event).map_err(quick_xml::Error::from)?;
+                                break;
+                            }
+                            Event::End(_) => depth -= 1,
+                            Event::Eof => {
+                                return Err(DeError::UnexpectedEof);
+                            }
+                            _ => {}
+                        }
+                        writer.write_event(event).map_err(quick_xml::Error::from)?;
+                    }
+                }
+                _ => continue,
+            }
+
+            return quick_xml::de::from_reader(writer.into_inner().as_slice()).map(Some);
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TrackStream<R> {
+    type Item = RekordcrateResult<Track>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read_track().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result.map(|track| track.map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn streaming_tracks_match_document() {
+        let data = include_str!("../data/xml/database.xml");
+        let document: Document = quick_xml::de::from_str(data).unwrap();
+        let tracks = TrackStream::new(data.as_bytes())
+            .collect::<RekordcrateResult<Vec<Track>>>()
+            .unwrap();
+        assert_eq!(tracks.len(), 6);
+        assert_eq!(tracks, document.tracks());
+    }
+
+    #[test]
+    fn playlist_tree() {
+        let data = include_str!("../data/xml/database.xml");
+        let document: Document = quick_xml::de::from_str(data).unwrap();
+        let root = document.playlists();
+        assert_eq!(root.name(), "ROOT");
+        let names: Vec<(&str, bool)> = root
+            .nodes()
+            .iter()
+            .map(|node| (node.name(), node.is_folder()))
+            .collect();
+        assert_eq!(names, [("Folder", true), ("Playlist1", false)]);
+
+        let PlaylistGenericNode::Folder(folder) = &root.nodes()[0] else {
+            panic!("expected folder");
+        };
+        let PlaylistGenericNode::Playlist(playlist) = &folder.nodes()[0] else {
+            panic!("expected playlist");
+        };
+        assert_eq!(playlist.name(), "Sub Playlist");
+        assert_eq!(playlist.key_type(), "0");
+        let keys: Vec<i32> = playlist.tracks().iter().map(PlaylistTrack::key).collect();
+        assert_eq!(keys, [5, 6]);
+        assert!(keys
+            .iter()
+            .all(|key| document.tracks().iter().any(|track| track.trackid == *key)));
+    }
+
+    #[test]
+    fn streaming_tracks_truncated() {
+        let data = include_str!("../data/xml/database.xml");
+        let end = data.find("</TRACK>").unwrap();
+        let results: Vec<_> = TrackStream::new(&data.as_bytes()[..end]).collect();
+        // The tracks before the truncated one are still returned, then iteration stops.
+        assert!(results.last().unwrap().is_err());
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 4);
+    }
+}
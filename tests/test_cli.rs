@@ -0,0 +1,261 @@
+// Copyright (c) 2026 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::process::Command;
+
+fn rekordcrate(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_rekordcrate"))
+        .args(args)
+        .output()
+        .expect("failed to run rekordcrate")
+}
+
+#[test]
+fn dump_setting_success() {
+    let output = rekordcrate(&[
+        "dump-setting",
+        "data/complete_export/demo_tracks/PIONEER/MYSETTING.DAT",
+    ]);
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+}
+
+#[test]
+fn dump_setting_invalid_file() {
+    let output = rekordcrate(&[
+        "dump-setting",
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: "));
+}
+
+#[test]
+fn dump_xml_invalid_file() {
+    let output = rekordcrate(&[
+        "dump-xml",
+        "data/complete_export/demo_tracks/PIONEER/MYSETTING.DAT",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: "));
+}
+
+#[test]
+fn missing_file() {
+    let output = rekordcrate(&["dump-pdb", "data/does-not-exist.pdb"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("Error: I/O error: "), "{stderr}");
+}
+
+#[test]
+fn diff_identical() {
+    let output = rekordcrate(&[
+        "diff",
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Tracks: 0 added, 0 removed, 0 changed\nPlaylists: 0 added, 0 removed, 0 changed\n"
+    );
+}
+
+#[test]
+fn diff_detailed() {
+    let output = rekordcrate(&[
+        "diff",
+        "--detailed",
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+        "data/complete_export/empty/PIONEER/rekordbox/export.pdb",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Tracks: 0 added, 2 removed, 0 changed\n  - 1 Demo Track 1\n  - 2 Demo Track 2\n\
+         Playlists: 0 added, 0 removed, 0 changed\n"
+    );
+}
+
+#[test]
+fn extract_anlz_cues() {
+    for path in [
+        "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.DAT",
+        "data/complete_export/demo_tracks/PIONEER/USBANLZ/P016/0000875E/ANLZ0000.EXT",
+    ] {
+        let output = rekordcrate(&["extract-anlz-cues", path]);
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "[]\n");
+    }
+}
+
+#[test]
+fn extract_anlz_cues_with_cues() {
+    let output = rekordcrate(&["extract-anlz-cues", "data/anlz/cues/ANLZ0000.EXT"]);
+    assert!(output.status.success());
+    let cues: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        cues,
+        serde_json::json!([
+            {
+                "type": "hot",
+                "hot_cue": 1,
+                "time_ms": 1000,
+                "beat": 2.079,
+                "loop_end_ms": null,
+                "loop_end_beat": null,
+                "color": null,
+                "hot_cue_color": "#e62828",
+                "comment": "Intro",
+            },
+            {
+                "type": "hot",
+                "hot_cue": 2,
+                "time_ms": 5000,
+                "beat": 10.612,
+                "loop_end_ms": 7000,
+                "loop_end_beat": 14.878,
+                "color": null,
+                "hot_cue_color": null,
+                "comment": "",
+            },
+            {
+                "type": "memory",
+                "hot_cue": 0,
+                "time_ms": 15000,
+                "beat": 31.947,
+                "loop_end_ms": null,
+                "loop_end_beat": null,
+                "color": "red",
+                "hot_cue_color": null,
+                "comment": "drop",
+            },
+            {
+                "type": "memory",
+                "hot_cue": 0,
+                "time_ms": 30000,
+                "beat": 63.947,
+                "loop_end_ms": null,
+                "loop_end_beat": null,
+                "color": null,
+                "hot_cue_color": null,
+                "comment": "",
+            },
+            {
+                "type": "memory",
+                "hot_cue": 0,
+                "time_ms": 45000,
+                "beat": 95.947,
+                "loop_end_ms": 50000,
+                "loop_end_beat": 106.613,
+                "color": "aqua",
+                "hot_cue_color": null,
+                "comment": "breakdown",
+            },
+        ])
+    );
+}
+
+#[test]
+fn extract_anlz_cues_by_track_id() {
+    let output = rekordcrate(&[
+        "extract-anlz-cues",
+        "--track-id",
+        "1",
+        "data/complete_export/demo_tracks",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[]\n");
+
+    let output = rekordcrate(&[
+        "extract-anlz-cues",
+        "--track-id",
+        "999",
+        "data/complete_export/demo_tracks",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: "));
+}
+
+#[test]
+fn stats() {
+    let output = rekordcrate(&[
+        "stats",
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Tables:\n  Tracks: 2\n"));
+    assert!(stdout.contains("Genres:\n  (none): 2\n"));
+    assert!(stdout.contains("BPM:\n  120-129: 2\n"));
+    assert!(stdout.ends_with("Top artists:\n  Loopmasters: 2\n"));
+}
+
+#[test]
+fn stats_json() {
+    let output = rekordcrate(&[
+        "stats",
+        "--format",
+        "json",
+        "--pretty",
+        "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+    ]);
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["tables"]["Tracks"], 2);
+    assert_eq!(stats["genres"], serde_json::json!({"(none)": 2}));
+    assert_eq!(stats["bpm"], serde_json::json!({"120-129": 2}));
+    assert_eq!(
+        stats["top_artists"],
+        serde_json::json!([{"name": "Loopmasters", "tracks": 2}])
+    );
+}
+
+#[test]
+fn json_compact() {
+    let json = |command: &[&str], args: &[&str]| {
+        let output = rekordcrate(&[command, args].concat());
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    for command in [
+        &[
+            "stats",
+            "--format",
+            "json",
+            "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+        ][..],
+        &["extract-anlz-cues", "data/anlz/cues/ANLZ0000.EXT"][..],
+    ] {
+        let pretty = json(command, &["--pretty"]);
+        let compact = json(command, &["--compact"]);
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(compact.lines().count(), 1);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&pretty).unwrap()
+        );
+        // The output is not a terminal, so the compact form is the default.
+        assert_eq!(json(command, &[]), compact);
+    }
+    // The order of the counts is kept.
+    let compact = json(
+        &[
+            "stats",
+            "--format",
+            "json",
+            "data/complete_export/demo_tracks/PIONEER/rekordbox/export.pdb",
+        ],
+        &["--compact"],
+    );
+    assert!(compact.starts_with("{\"tables\":{\"Tracks\":2,"));
+    assert!(!rekordcrate(&["stats", "--pretty", "--compact", "x.pdb"])
+        .status
+        .success());
+}
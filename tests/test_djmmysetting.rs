@@ -6,7 +6,7 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use binrw::{io::Cursor, BinRead};
+use binrw::{io::Cursor, BinRead, BinWrite};
 use rekordcrate::setting::*;
 
 macro_rules! read_djmmysetting {
@@ -179,3 +179,45 @@ fn read_djmsetting_talkover_mode_normal() {
     let data = read_djmmysetting!("../data/djmmysetting/talkover_mode_normal/DJMMYSETTING.DAT");
     assert_eq!(data.talk_over_mode, TalkOverMode::Normal);
 }
+
+#[test]
+fn djmmysetting_typed_view_roundtrip() {
+    let data = include_bytes!("../data/djmmysetting/chfadercurve_steeptop/DJMMYSETTING.DAT");
+    let setting = Setting::read(&mut Cursor::new(data)).expect("failed to parse setting file");
+    let mixer = setting.djm_my_setting().expect("not a mixer settings file");
+    assert_eq!(mixer.channel_fader_curve, ChannelFaderCurve::SteepTop);
+    assert_eq!(mixer.crossfader_curve, CrossfaderCurve::default());
+    assert_eq!(mixer.mic_low_cut, MicLowCut::default());
+
+    let mut writer = Cursor::new(Vec::with_capacity(data.len()));
+    setting
+        .write(&mut writer)
+        .expect("failed to write setting file");
+    assert_eq!(writer.into_inner(), data);
+}
+
+#[test]
+fn djmmysetting_typed_view_modify() {
+    let data =
+        include_bytes!("../data/djmmysetting/crossfadercurve_constantpower/DJMMYSETTING.DAT");
+    let mut setting = Setting::read(&mut Cursor::new(include_bytes!(
+        "../data/complete_export/empty/PIONEER/DJMMYSETTING.DAT"
+    )))
+    .expect("failed to parse setting file");
+    setting
+        .djm_my_setting_mut()
+        .expect("not a mixer settings file")
+        .crossfader_curve = CrossfaderCurve::ConstantPower;
+
+    let mut writer = Cursor::new(Vec::with_capacity(data.len()));
+    setting
+        .write(&mut writer)
+        .expect("failed to write setting file");
+    assert_eq!(writer.into_inner(), data);
+}
+
+#[test]
+fn djmmysetting_typed_view_other_file() {
+    assert!(Setting::default_mysetting().djm_my_setting().is_none());
+    assert!(Setting::default_djmmysetting().djm_my_setting().is_some());
+}
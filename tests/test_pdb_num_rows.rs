@@ -7,7 +7,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use binrw::BinRead;
-use rekordcrate::pdb::{Header, PageType};
+use rekordcrate::pdb::{io::Database, Header, PageType};
 use std::io::Cursor;
 
 fn assert_pdb_row_count(page_type: PageType, expected_row_count: usize) {
@@ -38,6 +38,14 @@ fn assert_pdb_row_count(page_type: PageType, expected_row_count: usize) {
         "wrong row count for page type {:?}",
         table.page_type
     );
+
+    let db = Database::open_non_persistent(Cursor::new(data)).expect("failed to open database");
+    assert_eq!(
+        db.row_count(page_type).expect("failed to count rows"),
+        expected_row_count,
+        "wrong row count from page headers for page type {:?}",
+        table.page_type
+    );
 }
 
 #[test]